@@ -48,9 +48,9 @@ fn main() {
         }
     };
 
-    match fmod.init_with_parameters(10i32, FmodInitFlag(FMOD_INIT_NORMAL)) {
-        fmod::Ok => {}
-        e => {
+    match fmod.init_with_parameters(10i32, FMOD_INIT_NORMAL) {
+        Ok(_) => {}
+        Err(e) => {
             fail!("FmodSys.init failed : {}", e);
         }
     };
@@ -60,12 +60,12 @@ fn main() {
     println!("=========================================");
 
     let arg1 = tmp.get(0).unwrap();
-    let sound = match fmod.create_sound((*arg1).as_slice(), Some(FmodMode(FMOD_3D | FMOD_SOFTWARE)), None) {
+    let sound = match fmod.create_sound((*arg1).as_slice(), Some(FMOD_3D | FMOD_SOFTWARE), None) {
         Ok(s) => s,
         Err(e) => fail!("create sound error: {}", e)
     };
     sound.set_3D_min_max_distance(4f32, 10000f32);
-    sound.set_mode(FmodMode(FMOD_LOOP_NORMAL));
+    sound.set_mode(FMOD_LOOP_NORMAL);
 
     let chan = match sound.play() {
         Ok(c) => c,
@@ -30,7 +30,6 @@ extern crate rfmod;
 
 use rfmod::enums::*;
 use rfmod::*;
-use rfmod::types::FmodMode;
 use std::os;
 use std::default::Default;
 
@@ -68,15 +67,15 @@ fn main() {
     };
 
     match fmod.init() {
-        fmod::Ok => {}
-        e => {
+        Ok(_) => {}
+        Err(e) => {
             fail!("FmodSys.init failed : {}", e);
         }
     };
 
     let arg1 = tmp.get(0).unwrap();
 
-    let sound = match fmod.create_sound((*arg1).as_slice(), Some(FmodMode(FMOD_SOFTWARE | FMOD_LOOP_NORMAL)), None) {
+    let sound = match fmod.create_sound((*arg1).as_slice(), Some(FMOD_SOFTWARE | FMOD_LOOP_NORMAL), None) {
         Ok(s) => s,
         Err(err) => {fail!("FmodSys.create_sound failed : {}", err);}
     };
@@ -88,9 +88,9 @@ fn main() {
         }
     };
 
-    match fmod.init_with_parameters(32i32, FmodInitFlag(FMOD_INIT_NORMAL)) {
-        fmod::Ok => {}
-        e => {
+    match fmod.init_with_parameters(32i32, FMOD_INIT_NORMAL) {
+        Ok(_) => {}
+        Err(e) => {
             fail!("FmodSys.init failed : {}", e);
         }
     };
@@ -100,11 +100,11 @@ fn main() {
     println!("==============================================");
 
     let arg1 = tmp.get(0).unwrap();
-    let sound = match fmod.create_sound((*arg1).as_slice(), Some(FmodMode(FMOD_SOFTWARE)), None) {
+    let sound = match fmod.create_sound((*arg1).as_slice(), Some(FMOD_SOFTWARE), None) {
         Ok(s) => s,
         Err(e) => fail!("create sound error: {}", e)
     };
-    sound.set_mode(FmodMode(FMOD_LOOP_NORMAL));
+    sound.set_mode(FMOD_LOOP_NORMAL);
 
     match sound.play() {
         Ok(_) => {},
@@ -106,8 +106,7 @@ fn main() {
     println!("--- CHOOSE A PLAYBACK DRIVER ---");
     println!("--------------------------------");
     while it < num_drivers as i32 {
-        //check this function
-        let t = match fmod.get_driver_info(it, 256u) {
+        let t = match fmod.get_driver_info(it) {
             Ok((_, name)) => name,
             Err(e) => fail!("get_driver_info error: {}", e)
         };
@@ -136,8 +135,8 @@ fn main() {
     }
 
     match fmod.init() {
-        fmod::Ok => {}
-        e => {
+        Ok(_) => {}
+        Err(e) => {
             fail!("FmodSys.init failed : {}", e);
         }
     };
@@ -150,7 +149,7 @@ fn main() {
     exinfo.default_frequency = 44100;
     exinfo.length            = (exinfo.default_frequency * mem::size_of::<i16>() as i32 * exinfo.num_channels * secs) as u32;
 
-    let sound = match fmod.create_sound("", Some(FmodMode(FMOD_2D | FMOD_SOFTWARE | FMOD_OPENUSER)), Some(&mut exinfo)) {
+    let sound = match fmod.create_sound("", Some(FMOD_2D | FMOD_SOFTWARE | FMOD_OPENUSER), Some(&mut exinfo)) {
         Ok(s) => s,
         Err(e) => fail!("create sound error: {}", e)
     };
@@ -170,15 +169,15 @@ fn main() {
                 match match nb {
                     0 => {
                         match fmod.start_record(record_driver, &sound, false) {
-                            fmod::Ok => {
+                            Ok(_) => {
                                 while fmod.is_recording(record_driver).unwrap() == true {
                                     print!("\rRecording : {}", fmod.get_record_position(record_driver).unwrap());
                                     fmod.update();
                                     sleep(15);
                                 }
-                                Some(fmod::Ok)
+                                Some(Ok(()))
                             }
-                            e => Some(e)
+                            Err(e) => Some(Err(e))
                         }
                     },
                     1 => {
@@ -190,9 +189,9 @@ fn main() {
                                     fmod.update();
                                     sleep(15);
                                 }
-                                Some(fmod::Ok)
+                                Some(Ok(()))
                             }
-                            Err(e) => Some(e)
+                            Err(e) => Some(Err(e))
                         }
                     },
                     2 => {
@@ -202,13 +201,10 @@ fn main() {
                         match reader.read_line() {
                             Ok(mut name) => {
                                 name.pop_char().unwrap();
-                                match sound.save_to_wav(&name) {
-                                    Ok(b) => if b {
+                                match sound.save_to_wav(name.as_slice()) {
+                                    Ok(_) => {
                                         println!("export succeeded");
                                         None
-                                    } else {
-                                        println!("export failed");
-                                        None
                                     },
                                     Err(e) => {
                                         println!("save_to_wav error: {}", e);
@@ -222,8 +218,8 @@ fn main() {
                     -1 => break,
                     _ => None
                 } {
-                    Some(r) if r == fmod::Ok => {}
-                    Some(e) => {
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
                         println!("Error : {}", e);
                         break;
                     }
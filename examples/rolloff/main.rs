@@ -0,0 +1,128 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+#![feature(globs, unboxed_closures)]
+
+extern crate libc;
+extern crate rfmod;
+
+use rfmod::enums::*;
+use rfmod::types::*;
+use rfmod::*;
+use std::any::Any;
+use std::default::Default;
+use std::io::timer::sleep;
+use std::os;
+
+fn main() {
+    let args = os::args();
+    let tmp = args.tail();
+
+    if tmp.len() < 1 {
+        fail!("USAGE: ./rolloff [music_file]");
+    }
+    let mut fmod = match FmodSys::new() {
+        Ok(f) => f,
+        Err(e) => {
+            fail!("FmodSys.new : {}", e);
+        }
+    };
+
+    match fmod.init_with_parameters(10i32, FMOD_INIT_NORMAL) {
+        Ok(_) => {}
+        Err(e) => {
+            fail!("FmodSys.init failed : {}", e);
+        }
+    };
+
+    println!("=========================================");
+    println!("============ Rolloff example ============");
+    println!("=========================================");
+
+    // each channel carries the distance at which it becomes silent
+    match fmod.set_3D_rolloff_callback(|&mut: channel: &Channel, distance: f32| -> f32 {
+        match channel.with_user_data(|radius: &f32| 1f32 - distance / *radius) {
+            Some(volume) if volume > 0f32 => volume,
+            Some(_) => 0f32,
+            None => 1f32
+        }
+    }) {
+        Ok(_) => {}
+        Err(e) => fail!("FmodSys.set_3D_rolloff_callback failed : {}", e)
+    };
+
+    let arg1 = tmp.get(0).unwrap();
+    let sound = match fmod.create_sound((*arg1).as_slice(), Some(FMOD_3D | FMOD_SOFTWARE | FMOD_3D_CUSTOMROLLOFF | FMOD_LOOP_NORMAL), None) {
+        Ok(s) => s,
+        Err(e) => fail!("create sound error: {}", e)
+    };
+
+    let mut near = match sound.play() {
+        Ok(c) => c,
+        Err(e) => fail!("sound.play error: {}", e)
+    };
+    let mut far = match sound.play() {
+        Ok(c) => c,
+        Err(e) => fail!("sound.play error: {}", e)
+    };
+    match near.set_3D_attributes(&FmodVector{x: -10f32, y: 0f32, z: 0f32}, &Default::default()) {
+        Ok(_) => {}
+        Err(e) => fail!("channel.set_3D_attributes error: {}", e)
+    };
+    match far.set_3D_attributes(&FmodVector{x: 10f32, y: 0f32, z: 0f32}, &Default::default()) {
+        Ok(_) => {}
+        Err(e) => fail!("channel.set_3D_attributes error: {}", e)
+    };
+    match near.set_user_data(box 15f32 as Box<Any>) {
+        Ok(_) => {}
+        Err(e) => fail!("channel.set_user_data error: {}", e)
+    };
+    match far.set_user_data(box 40f32 as Box<Any>) {
+        Ok(_) => {}
+        Err(e) => fail!("channel.set_user_data error: {}", e)
+    };
+
+    let forward = FmodVector{x: 0f32, y: 0f32, z: 1f32};
+    let up = FmodVector{x: 0f32, y: 1f32, z: 0f32};
+    let mut listener_pos = FmodVector::new();
+    let mut t = 0f32;
+
+    while near.is_playing().unwrap() && far.is_playing().unwrap() && t < 200f32 {
+        listener_pos.x = (t * 0.05f32).sin() * 30f32;
+        t += 1f32;
+        match fmod.set_3D_listener_attributes(0, &listener_pos, &Default::default(), &forward, &up) {
+            Ok(_) => {}
+            Err(e) => fail!("FmodSys.set_3D_listener_attributes failed : {}", e)
+        };
+        match fmod.update() {
+            Ok(_) => {}
+            Err(e) => fail!("FmodSys.update failed : {}", e)
+        };
+
+        print!("listener at {:6.2} : near channel volume {:4.2}, far channel volume {:4.2}\r", listener_pos.x,
+            near.get_audibility().unwrap_or(0f32), far.get_audibility().unwrap_or(0f32));
+        sleep(50);
+    }
+    println!("");
+}
@@ -31,12 +31,12 @@ use rfmod::*;
 use std::os;
 use std::io::timer::sleep;
 
-fn play_to_the_end(sound: Sound, len: uint) -> fmod::Result {
+fn play_to_the_end(sound: Sound) -> Result<(), FmodError> {
     let length = match sound.get_length(FMOD_TIMEUNIT_MS) {
         Ok(l) => l,
         Err(e) => fail!("sound.get_length error: {}", e)
     };
-    let name = match sound.get_name(len as u32) {
+    let name = match sound.get_name() {
         Ok(n) => n,
         Err(e) => fail!("sound.get_name error: {}", e)
     };
@@ -59,12 +59,12 @@ fn play_to_the_end(sound: Sound, len: uint) -> fmod::Result {
                             break;
                         }
                     },
-                    Err(e) => return e,
+                    Err(e) => return Err(e),
                 }
             }
-            fmod::Ok
+            Ok(())
         }
-        Err(err) => err,
+        Err(err) => Err(err),
     }
 }
 
@@ -83,8 +83,8 @@ fn main() {
     };
 
     match fmod.init() {
-        fmod::Ok => {}
-        e => {
+        Ok(_) => {}
+        Err(e) => {
             fail!("FmodSys.init failed : {}", e);
         }
     };
@@ -96,8 +96,8 @@ fn main() {
         Err(err) => {fail!("FmodSys.create_sound failed : {}", err);},
     };
 
-    match play_to_the_end(sound, arg1.len()) {
-        fmod::Ok => {println!("Ok !");},
-        err => {fail!("FmodSys.play_to_the_end : {}", err);}
+    match play_to_the_end(sound) {
+        Ok(_) => {println!("Ok !");},
+        Err(err) => {fail!("FmodSys.play_to_the_end : {}", err);}
     };
 }
\ No newline at end of file
@@ -30,32 +30,37 @@ extern crate rfmod;
 use rfmod::enums::*;
 use rfmod::types::*;
 use rfmod::*;
-use std::default::Default;
 use std::io::timer::sleep;
 
-#[allow(unused_variable)]
-fn pcmreadcallback(sound: &Sound, data: &mut [i16]) -> fmod::Result {
-    static mut t1 : f32 = 0f32; // time
-    static mut t2 : f32 = 0f32; // time
-    static mut v1 : f32 = 0f32; // velocity
-    static mut v2 : f32 = 0f32; // velocity
-    let mut count = 0u;
-
-    while count < data.len() {
-        unsafe {
-            data[count] = (t1.sin() * 32767f32) as i16; // left channel
+struct Siren {
+    t1: f32, // time
+    t2: f32, // time
+    v1: f32, // velocity
+    v2: f32  // velocity
+}
+
+impl PcmSource for Siren {
+    fn read(&mut self, data: PcmBuffer) -> fmod::Result {
+        let data = match data {
+            BufferPCM16(d) => d,
+            _ => return fmod::ErrFormat
+        };
+        let mut count = 0u;
+
+        while count < data.len() {
+            data[count] = (self.t1.sin() * 32767f32) as i16; // left channel
             count += 1;
-            data[count] = (t2.sin() * 32767f32) as i16; // right channel
+            data[count] = (self.t2.sin() * 32767f32) as i16; // right channel
             count += 1;
 
-            t1 += 0.01f32 + v1;
-            t2 += 0.0142f32 + v2;
-            v1 += t1.sin() * 0.002f32;
-            v2 += t2.sin() * 0.002f32;
+            self.t1 += 0.01f32 + self.v1;
+            self.t2 += 0.0142f32 + self.v2;
+            self.v1 += self.t1.sin() * 0.002f32;
+            self.v2 += self.t2.sin() * 0.002f32;
         }
-    }
 
-    fmod::Ok
+        fmod::Ok
+    }
 }
 
 fn get_key() -> Result<int, std::io::IoError> {
@@ -85,9 +90,9 @@ fn main() {
         }
     };
 
-    match fmod.init_with_parameters(32i32, FmodInitFlag(FMOD_INIT_NORMAL)) {
-        fmod::Ok => {}
-        e => {
+    match fmod.init_with_parameters(32i32, FMOD_INIT_NORMAL) {
+        Ok(_) => {}
+        Err(e) => {
             fail!("FmodSys.init failed : {}", e);
         }
     };
@@ -110,18 +115,13 @@ fn main() {
         println!("Invalid entry");
         ret = get_key().unwrap();
     }
-    let mut exinfo : FmodCreateSoundexInfo = Default::default();
-
-    exinfo.decode_buffer_size = 44100;
-    exinfo.length = 44100u32 * channels as u32 * std::mem::size_of::<i16>() as u32 * 5u32;
-    exinfo.num_channels = channels;
-    exinfo.default_frequency  = 44100;
-    exinfo.format = fmod::SoundFormatPCM16;
-    exinfo.pcm_read_callback = Some(pcmreadcallback);
+    let siren = box Siren{t1: 0f32, t2: 0f32, v1: 0f32, v2: 0f32};
+    let length = 44100u32 * 5u32;
 
     let sound = match match ret {
-        1 => fmod.create_sound("", Some(FmodMode(FMOD_2D | FMOD_OPENUSER | FMOD_HARDWARE | FMOD_LOOP_NORMAL | FMOD_CREATESTREAM)), Some(&mut exinfo)),
-        2 => fmod.create_sound("", Some(FmodMode(FMOD_2D | FMOD_OPENUSER | FMOD_HARDWARE | FMOD_LOOP_NORMAL)), Some(&mut exinfo)),
+        1 => fmod.create_user_sound(siren, channels, 44100, fmod::SoundFormatPCM16, length,
+            Some(FMOD_2D | FMOD_HARDWARE | FMOD_LOOP_NORMAL | FMOD_CREATESTREAM)),
+        2 => fmod.create_user_sound(siren, channels, 44100, fmod::SoundFormatPCM16, length, Some(FMOD_2D | FMOD_HARDWARE | FMOD_LOOP_NORMAL)),
         _ => return
     } {
         Ok(s) => s,
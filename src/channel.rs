@@ -24,7 +24,7 @@
 
 use enums::*;
 use types::*;
-use libc::{c_int, c_uint, c_void};
+use libc::{c_int, c_uint};
 use ffi;
 use dsp;
 use dsp::Dsp;
@@ -34,12 +34,56 @@ use channel_group;
 use channel_group::ChannelGroup;
 use fmod_sys;
 use fmod_sys::{FmodMemoryUsageDetails, FmodSys};
+use error;
+use error::FmodError;
+use std::any::Any;
 use vector;
 use sound;
 use sound::Sound;
-use std::mem::transmute;
 use std::default::Default;
 
+/// Events sent to the closure given to [`Channel::set_callback`](struct.Channel.html#method.set_callback).
+#[deriving(Show, PartialEq, Clone)]
+pub enum ChannelEvent {
+    /// The sound has ended. The closure is removed after this event.
+    End,
+    /// The voice has been swapped out (true, it became virtual) or swapped in (false).
+    VirtualVoice(bool),
+    /// A sync point has been reached. Contains the sync point index and its name.
+    SyncPoint(i32, String),
+    /// The geometry occlusion of the channel has been calculated. Contains the direct and the reverb occlusion.
+    Occlusion(f32, f32)
+}
+
+/// State of the voice behind a channel handle, returned by [`Channel::state`](struct.Channel.html#method.state).
+#[deriving(Show, PartialEq, Clone)]
+pub enum ChannelState {
+    ChannelPlaying,
+    ChannelPaused,
+    /// The channel is still playing but has been swapped out to make room for more important ones.
+    ChannelVirtual,
+    /// The sound ended or the channel was stopped.
+    ChannelStopped,
+    /// The voice has been reused to play another sound.
+    ChannelStolen
+}
+
+/// Settings of a channel remembered by a [`ResumableChannel`](struct.ResumableChannel.html).
+#[deriving(Show, PartialEq)]
+pub struct ChannelSettings {
+    pub volume: f32,
+    pub frequency: f32,
+    pub pan: f32,
+    pub mute: bool,
+    pub paused: bool,
+    pub priority: i32,
+    pub loop_count: i32,
+    /// Position in milliseconds.
+    pub position: uint,
+    /// 3D position and velocity. None for 2D sounds.
+    pub attributes_3D: Option<(vector::FmodVector, vector::FmodVector)>
+}
+
 /// Structure which contains data for [`Channel::set_speaker_mix`](struct.Channel.html#method.set_speaker_mix) and [`Channel::get_speaker_mix`](struct.Channel.html#method.get_speaker_mix)
 #[deriving(Show, PartialEq, PartialOrd, Clone)]
 pub struct FmodSpeakerMixOptions {
@@ -85,16 +129,23 @@ pub fn get_ffi(channel: &mut Channel) -> *mut ffi::FMOD_CHANNEL {
 }
 
 pub fn new() -> Channel {
-    Channel{channel: ::std::ptr::mut_null()}
+    Channel{channel: ::std::ptr::mut_null(), system: fmod_sys::SystemRef::none()}
 }
 
-pub fn from_ptr(channel: *mut ffi::FMOD_CHANNEL) -> Channel {
-    Channel{channel: channel}
+pub fn from_ptr(channel: *mut ffi::FMOD_CHANNEL, system: fmod_sys::SystemRef) -> Channel {
+    Channel{channel: channel, system: system}
+}
+
+/// Wraps a channel which just started playing, dropping the user data left by a stopped channel which had the same handle.
+pub fn from_new_ptr(channel: *mut ffi::FMOD_CHANNEL, system: fmod_sys::SystemRef) -> Channel {
+    system.remove_user_data(channel as uint);
+    from_ptr(channel, system)
 }
 
 /// Channel Object
 pub struct Channel {
-    channel: *mut ffi::FMOD_CHANNEL
+    channel: *mut ffi::FMOD_CHANNEL,
+    system: fmod_sys::SystemRef
 }
 
 impl Drop for Channel {
@@ -108,21 +159,31 @@ impl Channel {
         self.channel = ::std::ptr::mut_null();
     }
 
-    pub fn get_system_object(&self) -> Result<FmodSys, fmod::Result> {
+    pub fn get_system_object(&self) -> Result<FmodSys, FmodError> {
         let mut system = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Channel_GetSystemObject(self.channel, &mut system) } {
-            fmod::Ok => Ok(fmod_sys::from_ptr(system)),
-            e => Err(e)
+            fmod::Ok => Ok(fmod_sys::from_ptr(system, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn stop(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Stop(self.channel) }
+    pub fn stop(&self) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Stop(self.channel) })
+    }
+
+    /// Sets a closure called when the sound ends, when the voice goes virtual or real, when a sync point is reached
+    /// or when the occlusion is calculated.
+    ///
+    /// The closure is called from [`FmodSys::update`](struct.FmodSys.html#method.update), it can read the data attached to the channel
+    /// with [`Channel::with_user_data`](struct.Channel.html#method.with_user_data). If the closure sets a new callback on the channel,
+    /// the new one is kept.
+    pub fn set_callback<F: FnMut(&Channel, ChannelEvent) + 'static>(&self, callback: F) -> Result<(), FmodError> {
+        error::check(fmod_sys::set_channel_callback(self.channel, box callback as Box<FnMut(&Channel, ChannelEvent)>))
     }
 
     /// channel_offset:  0/1 -> left channel/right channel
-    pub fn get_spectrum(&self, spectrum_size: uint, channel_offset: Option<i32>, window_type: Option<fmod::DSP_FFT_Window>) -> Result<Vec<f32>, fmod::Result> {
+    pub fn get_spectrum(&self, spectrum_size: uint, channel_offset: Option<i32>, window_type: Option<fmod::DSP_FFT_Window>) -> Result<Vec<f32>, FmodError> {
         let mut ptr = Vec::from_elem(spectrum_size, 0f32);
         let c_window_type = match window_type {
             Some(wt) => wt,
@@ -135,16 +196,16 @@ impl Channel {
 
         match unsafe { ffi::FMOD_Channel_GetSpectrum(self.channel, ptr.as_mut_ptr(), spectrum_size as c_int, c_channel_offset, c_window_type) } {
             fmod::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn get_wave_data(&self, wave_size: uint, channel_offset: i32) -> Result<Vec<f32>, fmod::Result> {
+    pub fn get_wave_data(&self, wave_size: uint, channel_offset: i32) -> Result<Vec<f32>, FmodError> {
         let mut ptr = Vec::from_elem(wave_size, 0f32);
 
         match unsafe { ffi::FMOD_Channel_GetWaveData(self.channel, ptr.as_mut_ptr(), wave_size as c_int, channel_offset) } {
             fmod::Ok => Ok(ptr),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
@@ -152,99 +213,121 @@ impl Channel {
         self.channel.is_not_null()
     }
 
-    pub fn is_playing(&self) -> Result<bool, fmod::Result> {
+    pub fn is_playing(&self) -> Result<bool, FmodError> {
         let mut is_playing = 0;
 
         match unsafe { ffi::FMOD_Channel_IsPlaying(self.channel, &mut is_playing) } {
             fmod::Ok => Ok(is_playing == 1),
-            err => Err(err),
+            err => Err(FmodError::new(err)),
         }
     }
 
-    pub fn is_virtual(&self) -> Result<bool, fmod::Result> {
+    pub fn is_virtual(&self) -> Result<bool, FmodError> {
         let mut is_virtual = 0i32;
 
         match unsafe { ffi::FMOD_Channel_IsVirtual(self.channel, &mut is_virtual) } {
             fmod::Ok => Ok(is_virtual == 1),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_audibility(&self) -> Result<f32, fmod::Result> {
+    /// Returns the state of the voice behind this handle. It keeps working once the voice has stopped or has been stolen,
+    /// when the other methods return a [`DeadHandle`](enum.ErrorKind.html) error.
+    pub fn state(&self) -> Result<ChannelState, FmodError> {
+        let mut is_playing = 0;
+
+        match unsafe { ffi::FMOD_Channel_IsPlaying(self.channel, &mut is_playing) } {
+            fmod::Ok => {}
+            fmod::ErrChannelStolen => return Ok(ChannelStolen),
+            fmod::ErrInvalidHandle => return Ok(ChannelStopped),
+            e => return Err(FmodError::new(e))
+        }
+        if is_playing == 0 {
+            Ok(ChannelStopped)
+        } else if try!(self.is_virtual()) {
+            Ok(ChannelVirtual)
+        } else if try!(self.get_paused()) {
+            Ok(ChannelPaused)
+        } else {
+            Ok(ChannelPlaying)
+        }
+    }
+
+    pub fn get_audibility(&self) -> Result<f32, FmodError> {
         let mut audibility = 0f32;
 
         match unsafe { ffi::FMOD_Channel_GetAudibility(self.channel, &mut audibility) } {
             fmod::Ok => Ok(audibility),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_current_sound(&self) -> Result<Sound, fmod::Result> {
+    pub fn get_current_sound(&self) -> Result<Sound, FmodError> {
         let mut sound = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Channel_GetCurrentSound(self.channel, &mut sound) } {
-            fmod::Ok => Ok(sound::from_ptr(sound)),
-            e => Err(e)
+            fmod::Ok => Ok(sound::from_ptr(sound, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_index(&self) -> Result<i32, fmod::Result> {
+    pub fn get_index(&self) -> Result<i32, FmodError> {
         let mut index = 0i32;
 
         match unsafe { ffi::FMOD_Channel_GetIndex(self.channel, &mut index) } {
             fmod::Ok => Ok(index),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_volume(&self, volume: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetVolume(self.channel, volume) }
+    pub fn set_volume(&self, volume: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetVolume(self.channel, volume) })
     }
 
-    pub fn get_volume(&self) -> Result<f32, fmod::Result> {
+    pub fn get_volume(&self) -> Result<f32, FmodError> {
         let mut volume = 0f32;
 
         match unsafe { ffi::FMOD_Channel_GetVolume(self.channel, &mut volume) } {
             fmod::Ok => Ok(volume),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_frequency(&self, frequency: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetFrequency(self.channel, frequency) }
+    pub fn set_frequency(&self, frequency: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetFrequency(self.channel, frequency) })
     }
 
-    pub fn get_frequency(&self) -> Result<f32, fmod::Result> {
+    pub fn get_frequency(&self) -> Result<f32, FmodError> {
         let mut frequency = 0f32;
 
         match unsafe { ffi::FMOD_Channel_GetFrequency(self.channel, &mut frequency) } {
             fmod::Ok => Ok(frequency),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_pan(&self, pan: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetPan(self.channel, pan) }
+    pub fn set_pan(&self, pan: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetPan(self.channel, pan) })
     }
 
-    pub fn get_pan(&self) -> Result<f32, fmod::Result> {
+    pub fn get_pan(&self) -> Result<f32, FmodError> {
         let mut pan = 0f32;
 
         match unsafe { ffi::FMOD_Channel_GetPan(self.channel, &mut pan) } {
             fmod::Ok => Ok(pan),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_mute(&self, mute: bool) -> fmod::Result {
+    pub fn set_mute(&self, mute: bool) -> Result<(), FmodError> {
         let t = match mute {
             true => 1,
             false => 0,
         };
-        unsafe { ffi::FMOD_Channel_SetMute(self.channel, t) }
+        error::check(unsafe { ffi::FMOD_Channel_SetMute(self.channel, t) })
     }
 
-    pub fn get_mute(&self) -> Result<bool, fmod::Result> {
+    pub fn get_mute(&self) -> Result<bool, FmodError> {
         let mut mute = 0;
 
         match unsafe { ffi::FMOD_Channel_GetMute(self.channel, &mut mute) } {
@@ -252,19 +335,19 @@ impl Channel {
                 1 => true,
                 _ => false,
             }),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_paused(&self, paused: bool) -> fmod::Result {
+    pub fn set_paused(&self, paused: bool) -> Result<(), FmodError> {
         let t: ffi::FMOD_BOOL = match paused {
             true => 1,
             false => 0,
         };
-        unsafe { ffi::FMOD_Channel_SetPaused(self.channel, t) }
+        error::check(unsafe { ffi::FMOD_Channel_SetPaused(self.channel, t) })
     }
 
-    pub fn get_paused(&self) -> Result<bool, fmod::Result> {
+    pub fn get_paused(&self) -> Result<bool, FmodError> {
         let mut t = 0;
 
         match unsafe { ffi::FMOD_Channel_GetPaused(self.channel, &mut t) } {
@@ -272,99 +355,99 @@ impl Channel {
                 1 => true,
                 _ => false,
             }),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_delay(&self, delay_type: fmod::DelayType, delay_hi: uint, delay_lo: uint) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetDelay(self.channel, delay_type, delay_hi as u32, delay_lo as u32) }
+    pub fn set_delay(&self, delay_type: fmod::DelayType, delay_hi: uint, delay_lo: uint) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetDelay(self.channel, delay_type, delay_hi as u32, delay_lo as u32) })
     }
 
-    pub fn get_delay(&self, delaytype: fmod::DelayType) -> Result<(fmod::DelayType, uint, uint), fmod::Result> {
+    pub fn get_delay(&self, delaytype: fmod::DelayType) -> Result<(fmod::DelayType, uint, uint), FmodError> {
         let mut delaylo = 0u32;
         let mut delayhi = 0u32;
 
         match unsafe { ffi::FMOD_Channel_GetDelay(self.channel, delaytype, &mut delayhi, &mut delaylo) } {
             fmod::Ok => Ok((delaytype, delayhi as uint, delaylo as uint)),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_speaker_mix(&self, smo: &FmodSpeakerMixOptions) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetSpeakerMix(self.channel, smo.front_left, smo.front_right, smo.center, smo.lfe,
-                                            smo.back_left, smo.back_right, smo.side_left, smo.side_right) }
+    pub fn set_speaker_mix(&self, smo: &FmodSpeakerMixOptions) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetSpeakerMix(self.channel, smo.front_left, smo.front_right, smo.center, smo.lfe,
+                                            smo.back_left, smo.back_right, smo.side_left, smo.side_right) })
     }
 
-    pub fn get_speaker_mix(&self) -> Result<FmodSpeakerMixOptions, fmod::Result> {
+    pub fn get_speaker_mix(&self) -> Result<FmodSpeakerMixOptions, FmodError> {
         let mut smo = FmodSpeakerMixOptions{front_left: 0f32, front_right: 0f32, center: 0f32, lfe: 0f32, back_left: 0f32,
                                     back_right: 0f32, side_left: 0f32, side_right: 0f32};
 
         match unsafe { ffi::FMOD_Channel_GetSpeakerMix(self.channel, &mut smo.front_left, &mut smo.front_right, &mut smo.center, &mut smo.lfe,
                                                 &mut smo.back_left, &mut smo.back_right, &mut smo.side_left, &mut smo.side_right) } {
             fmod::Ok => Ok(smo),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_speaker_level(&self, speaker: fmod::Speaker, levels: &mut Vec<f32>) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetSpeakerLevels(self.channel, speaker, levels.as_mut_ptr(), levels.len() as i32) }
+    pub fn set_speaker_level(&self, speaker: fmod::Speaker, levels: &mut Vec<f32>) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetSpeakerLevels(self.channel, speaker, levels.as_mut_ptr(), levels.len() as i32) })
     }
 
-    pub fn get_speaker_level(&self, speaker: fmod::Speaker, num_levels: uint) -> Result<Vec<f32>, fmod::Result> {
+    pub fn get_speaker_level(&self, speaker: fmod::Speaker, num_levels: uint) -> Result<Vec<f32>, FmodError> {
         let mut ptr = Vec::from_elem(num_levels, 0f32);
 
         match unsafe { ffi::FMOD_Channel_GetSpeakerLevels(self.channel, speaker, ptr.as_mut_ptr(), num_levels as i32) } {
             fmod::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_input_channel_mix(&self, levels: &mut Vec<f32>) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetInputChannelMix(self.channel, levels.as_mut_ptr(), levels.len() as i32) }
+    pub fn set_input_channel_mix(&self, levels: &mut Vec<f32>) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetInputChannelMix(self.channel, levels.as_mut_ptr(), levels.len() as i32) })
     }
 
-    pub fn get_input_channel_mix(&self, num_levels: uint) -> Result<Vec<f32>, fmod::Result> {
+    pub fn get_input_channel_mix(&self, num_levels: uint) -> Result<Vec<f32>, FmodError> {
         let mut ptr = Vec::from_elem(num_levels, 0f32);
 
         match unsafe { ffi::FMOD_Channel_GetInputChannelMix(self.channel, ptr.as_mut_ptr(), num_levels as i32) } {
             fmod::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_priority(&self, priority: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetPriority(self.channel, priority) }
+    pub fn set_priority(&self, priority: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetPriority(self.channel, priority) })
     }
 
-    pub fn get_priority(&self) -> Result<i32, fmod::Result> {
+    pub fn get_priority(&self) -> Result<i32, FmodError> {
         let mut t = 0i32;
 
         match unsafe { ffi::FMOD_Channel_GetPriority(self.channel, &mut t) } {
             fmod::Ok => Ok(t),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_position(&self, position: uint, FmodTimeUnit(postype): FmodTimeUnit) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetPosition(self.channel, position as u32, postype) }
+    pub fn set_position(&self, position: uint, FmodTimeUnit(postype): FmodTimeUnit) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetPosition(self.channel, position as u32, postype) })
     }
 
-    pub fn get_position(&self, FmodTimeUnit(postype): FmodTimeUnit) -> Result<uint, fmod::Result> {
+    pub fn get_position(&self, FmodTimeUnit(postype): FmodTimeUnit) -> Result<uint, FmodError> {
         let mut t = 0u32;
 
         match unsafe { ffi::FMOD_Channel_GetPosition(self.channel, &mut t, postype) } {
             fmod::Ok => Ok(t as uint),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_reverb_properties(&self, prop: &FmodReverbChannelProperties) -> fmod::Result {
+    pub fn set_reverb_properties(&self, prop: &FmodReverbChannelProperties) -> Result<(), FmodError> {
         let t = ffi::FMOD_REVERB_CHANNELPROPERTIES{Direct: prop.direct, Room: prop.room, Flags: prop.flags, ConnectionPoint: ::std::ptr::mut_null()};
 
-        unsafe { ffi::FMOD_Channel_SetReverbProperties(self.channel, &t) }
+        error::check(unsafe { ffi::FMOD_Channel_SetReverbProperties(self.channel, &t) })
     }
 
-    pub fn get_reverb_properties(&self) -> Result<FmodReverbChannelProperties, fmod::Result> {
+    pub fn get_reverb_properties(&self) -> Result<FmodReverbChannelProperties, FmodError> {
         let mut t = ffi::FMOD_REVERB_CHANNELPROPERTIES{Direct: 0, Room: 0, Flags: 0, ConnectionPoint: ::std::ptr::mut_null()};
 
         match unsafe { ffi::FMOD_Channel_GetReverbProperties(self.channel, &mut t) } {
@@ -372,108 +455,108 @@ impl Channel {
                 direct: t.Direct,
                 room: t.Room,
                 flags: t.Flags,
-                connection_point: dsp::from_ptr(t.ConnectionPoint)}),
-            e => Err(e),
+                connection_point: dsp::from_ptr(t.ConnectionPoint, self.system.clone())}),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_low_pass_gain(&self, gain: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetLowPassGain(self.channel, gain) }
+    pub fn set_low_pass_gain(&self, gain: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetLowPassGain(self.channel, gain) })
     }
 
-    pub fn get_low_pass_gain(&self) -> Result<f32, fmod::Result> {
+    pub fn get_low_pass_gain(&self) -> Result<f32, FmodError> {
         let mut t = 0f32;
 
         match unsafe { ffi::FMOD_Channel_GetLowPassGain(self.channel, &mut t) } {
             fmod::Ok => Ok(t),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn set_channel_group(&mut self, channel_group: &ChannelGroup) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetChannelGroup(self.channel, channel_group::get_ffi(channel_group)) }
+    pub fn set_channel_group(&mut self, channel_group: &ChannelGroup) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetChannelGroup(self.channel, channel_group::get_ffi(channel_group)) })
     }
 
-    pub fn get_channel_group(&self) -> Result<ChannelGroup, fmod::Result> {
+    pub fn get_channel_group(&self) -> Result<ChannelGroup, FmodError> {
         let mut channel_group = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Channel_GetChannelGroup(self.channel, &mut channel_group) } {
-            fmod::Ok => Ok(channel_group::from_ptr(channel_group)),
-            e => Err(e)
+            fmod::Ok => Ok(channel_group::from_ptr(channel_group, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_attributes(&self, position: &vector::FmodVector, velocity: &vector::FmodVector) -> fmod::Result {
+    pub fn set_3D_attributes(&self, position: &vector::FmodVector, velocity: &vector::FmodVector) -> Result<(), FmodError> {
         let mut t_position = vector::get_ffi(position);
         let mut t_velocity = vector::get_ffi(velocity);
 
-        unsafe { ffi::FMOD_Channel_Set3DAttributes(self.channel, &mut t_position, &mut t_velocity) }
+        error::check(unsafe { ffi::FMOD_Channel_Set3DAttributes(self.channel, &mut t_position, &mut t_velocity) })
     }
 
-    pub fn get_3D_attributes(&self) -> Result<(vector::FmodVector, vector::FmodVector), fmod::Result> {
+    pub fn get_3D_attributes(&self) -> Result<(vector::FmodVector, vector::FmodVector), FmodError> {
         let mut position = vector::get_ffi(&vector::FmodVector::new());
         let mut velocity = vector::get_ffi(&vector::FmodVector::new());
 
         match unsafe { ffi::FMOD_Channel_Get3DAttributes(self.channel, &mut position, &mut velocity) } {
             fmod::Ok => Ok((vector::from_ptr(position), vector::from_ptr(velocity))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_min_max_distance(&self, min_distance: f32, max_distance: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Set3DMinMaxDistance(self.channel, min_distance, max_distance) }
+    pub fn set_3D_min_max_distance(&self, min_distance: f32, max_distance: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Set3DMinMaxDistance(self.channel, min_distance, max_distance) })
     }
 
-    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), fmod::Result> {
+    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), FmodError> {
         let mut min_distance = 0f32;
         let mut max_distance = 0f32;
 
         match unsafe { ffi::FMOD_Channel_Get3DMinMaxDistance(self.channel, &mut min_distance, &mut max_distance) } {
             fmod::Ok => Ok((min_distance, max_distance)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_cone_settings(&self, inside_cone_angle: f32, outside_cone_angle: f32, outside_volume: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Set3DConeSettings(self.channel, inside_cone_angle, outside_cone_angle, outside_volume) }
+    pub fn set_3D_cone_settings(&self, inside_cone_angle: f32, outside_cone_angle: f32, outside_volume: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Set3DConeSettings(self.channel, inside_cone_angle, outside_cone_angle, outside_volume) })
     }
 
-    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), fmod::Result> {
+    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), FmodError> {
         let mut inside_cone_angle = 0f32;
         let mut outside_cone_angle = 0f32;
         let mut outside_volume = 0f32;
 
         match unsafe { ffi::FMOD_Channel_Get3DConeSettings(self.channel, &mut inside_cone_angle, &mut outside_cone_angle, &mut outside_volume) } {
             fmod::Ok => Ok((inside_cone_angle, outside_cone_angle, outside_volume)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_cone_orientation(&self, orientation: &vector::FmodVector) -> fmod::Result {
+    pub fn set_3D_cone_orientation(&self, orientation: &vector::FmodVector) -> Result<(), FmodError> {
         let mut t_orientation = vector::get_ffi(orientation);
 
-        unsafe { ffi::FMOD_Channel_Set3DConeOrientation(self.channel, &mut t_orientation) }
+        error::check(unsafe { ffi::FMOD_Channel_Set3DConeOrientation(self.channel, &mut t_orientation) })
     }
 
-    pub fn get_3D_cone_orientation(&self) -> Result<vector::FmodVector, fmod::Result> {
+    pub fn get_3D_cone_orientation(&self) -> Result<vector::FmodVector, FmodError> {
         let mut orientation = vector::get_ffi(&vector::FmodVector::new());
 
         match unsafe { ffi::FMOD_Channel_Get3DConeOrientation(self.channel, &mut orientation) } {
             fmod::Ok => Ok(vector::from_ptr(orientation)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_custom_rolloff(&self, points: &Vec<vector::FmodVector>) -> fmod::Result {
+    pub fn set_3D_custom_rolloff(&self, points: &Vec<vector::FmodVector>) -> Result<(), FmodError> {
         let mut t_points = Vec::new();
 
         for tmp in points.iter() {
             t_points.push(vector::get_ffi(tmp));
         }
-        unsafe { ffi::FMOD_Channel_Set3DCustomRolloff(self.channel, t_points.as_mut_ptr(), points.len() as c_int) }
+        error::check(unsafe { ffi::FMOD_Channel_Set3DCustomRolloff(self.channel, t_points.as_mut_ptr(), points.len() as c_int) })
     }
 
-    pub fn get_3D_custom_rolloff(&self) -> Result<Vec<vector::FmodVector>, fmod::Result> {
+    pub fn get_3D_custom_rolloff(&self) -> Result<Vec<vector::FmodVector>, FmodError> {
         let mut points = ::std::ptr::mut_null();
         let mut num_points = 0i32;
 
@@ -487,168 +570,263 @@ impl Channel {
                     }
                     Ok(ret_points)
                 }
-                e => Err(e)
+                e => Err(FmodError::new(e))
             }
         }
     }
 
-    pub fn set_3D_occlusion(&self, direct_occlusion: f32, reverb_occlusion: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Set3DOcclusion(self.channel, direct_occlusion, reverb_occlusion) }
+    pub fn set_3D_occlusion(&self, direct_occlusion: f32, reverb_occlusion: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Set3DOcclusion(self.channel, direct_occlusion, reverb_occlusion) })
     }
 
-    pub fn get_3D_occlusion(&self) -> Result<(f32, f32), fmod::Result> {
+    pub fn get_3D_occlusion(&self) -> Result<(f32, f32), FmodError> {
         let mut direct_occlusion = 0f32;
         let mut reverb_occlusion = 0f32;
 
         match unsafe { ffi::FMOD_Channel_Get3DOcclusion(self.channel, &mut direct_occlusion, &mut reverb_occlusion) } {
             fmod::Ok => Ok((direct_occlusion, reverb_occlusion)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_spread(&self, angle: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Set3DSpread(self.channel, angle) }
+    pub fn set_3D_spread(&self, angle: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Set3DSpread(self.channel, angle) })
     }
 
-    pub fn get_3D_spread(&self) -> Result<f32, fmod::Result> {
+    pub fn get_3D_spread(&self) -> Result<f32, FmodError> {
         let mut angle = 0f32;
 
         match unsafe { ffi::FMOD_Channel_Get3DSpread(self.channel, &mut angle) } {
             fmod::Ok => Ok(angle),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_pan_level(&self, level: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Set3DPanLevel(self.channel, level) }
+    pub fn set_3D_pan_level(&self, level: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Set3DPanLevel(self.channel, level) })
     }
 
-    pub fn get_3D_pan_level(&self) -> Result<f32, fmod::Result> {
+    pub fn get_3D_pan_level(&self) -> Result<f32, FmodError> {
         let mut level = 0f32;
 
         match unsafe { ffi::FMOD_Channel_Get3DPanLevel(self.channel, &mut level) } {
             fmod::Ok => Ok(level),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_doppler_level(&self, level: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Set3DDopplerLevel(self.channel, level) }
+    pub fn set_3D_doppler_level(&self, level: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Set3DDopplerLevel(self.channel, level) })
     }
 
-    pub fn get_3D_doppler_level(&self) -> Result<f32, fmod::Result> {
+    pub fn get_3D_doppler_level(&self) -> Result<f32, FmodError> {
         let mut level = 0f32;
 
         match unsafe { ffi::FMOD_Channel_Get3DDopplerLevel(self.channel, &mut level) } {
             fmod::Ok => Ok(level),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_distance_filter(&self, custom: bool, custom_level: f32, center_freq: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_Set3DDistanceFilter(self.channel, if custom {
+    pub fn set_3D_distance_filter(&self, custom: bool, custom_level: f32, center_freq: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_Set3DDistanceFilter(self.channel, if custom {
                 1
             } else {
                 0
-            }, custom_level, center_freq) }
+            }, custom_level, center_freq) })
     }
 
-    pub fn get_3D_distance_filter(&self) -> Result<(bool, f32, f32), fmod::Result> {
+    pub fn get_3D_distance_filter(&self) -> Result<(bool, f32, f32), FmodError> {
         let mut custom = 0i32;
         let mut custom_level = 0f32;
         let mut center_freq = 0f32;
 
         match unsafe { ffi::FMOD_Channel_Get3DDistanceFilter(self.channel, &mut custom, &mut custom_level, &mut center_freq) } {
             fmod::Ok => Ok((custom == 1, custom_level, center_freq)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_DSP_head(&self) -> Result<Dsp, fmod::Result> {
+    pub fn get_DSP_head(&self) -> Result<Dsp, FmodError> {
         let mut dsp = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Channel_GetDSPHead(self.channel, &mut dsp) } {
-            fmod::Ok => Ok(dsp::from_ptr(dsp)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr(dsp, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn add_DSP(&self, dsp: &Dsp) -> Result<DspConnection, fmod::Result> {
+    pub fn add_DSP(&self, dsp: &Dsp) -> Result<DspConnection, FmodError> {
         let mut connection = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Channel_AddDSP(self.channel, dsp::get_ffi(dsp), &mut connection) } {
-            fmod::Ok => Ok(dsp_connection::from_ptr(connection)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp_connection::from_ptr(connection, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_mode(&self, FmodMode(mode): FmodMode) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetMode(self.channel, mode) }
+    /// Returns an [`InvalidFlags`](enum.ErrorKind.html) error if `mode` holds contradictory flags.
+    pub fn set_mode(&self, mode: FmodMode) -> Result<(), FmodError> {
+        try!(mode.check());
+        error::check(unsafe { ffi::FMOD_Channel_SetMode(self.channel, mode.bits()) })
     }
 
-    pub fn get_mode(&self) -> Result<FmodMode, fmod::Result> {
+    pub fn get_mode(&self) -> Result<FmodMode, FmodError> {
         let mut mode = 0u32;
 
         match unsafe { ffi::FMOD_Channel_GetMode(self.channel, &mut mode) } {
             fmod::Ok => Ok(FmodMode(mode)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_loop_count(&self, loop_count: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetLoopCount(self.channel, loop_count) }
+    pub fn set_loop_count(&self, loop_count: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetLoopCount(self.channel, loop_count) })
     }
 
-    pub fn get_loop_count(&self) -> Result<i32, fmod::Result> {
+    pub fn get_loop_count(&self) -> Result<i32, FmodError> {
         let mut loop_count = 0i32;
 
         match unsafe { ffi::FMOD_Channel_GetLoopCount(self.channel, &mut loop_count) } {
             fmod::Ok => Ok(loop_count),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn set_loop_points(&self, loop_start: u32, FmodTimeUnit(loop_start_type): FmodTimeUnit,
-        loop_end: u32, FmodTimeUnit(loop_end_type): FmodTimeUnit) -> fmod::Result {
-            unsafe { ffi::FMOD_Channel_SetLoopPoints(self.channel, loop_start, loop_start_type, loop_end, loop_end_type) }
+        loop_end: u32, FmodTimeUnit(loop_end_type): FmodTimeUnit) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Channel_SetLoopPoints(self.channel, loop_start, loop_start_type, loop_end, loop_end_type) })
     }
 
-    pub fn get_loop_points(&self, FmodTimeUnit(loop_start_type): FmodTimeUnit, FmodTimeUnit(loop_end_type): FmodTimeUnit) -> Result<(u32, u32), fmod::Result> {
+    pub fn get_loop_points(&self, FmodTimeUnit(loop_start_type): FmodTimeUnit, FmodTimeUnit(loop_end_type): FmodTimeUnit) -> Result<(u32, u32), FmodError> {
         let mut loop_start = 0u32;
         let mut loop_end = 0u32;
 
         match unsafe { ffi::FMOD_Channel_GetLoopPoints(self.channel, &mut loop_start, loop_start_type, &mut loop_end, loop_end_type) } {
             fmod::Ok => Ok((loop_start, loop_end)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_user_data<T>(&self, user_data: &mut T) -> fmod::Result {
-        unsafe { ffi::FMOD_Channel_SetUserData(self.channel, transmute(user_data)) }
+    /// Attaches `user_data` to the channel, replacing and dropping the previous one. It's dropped by [`FmodSys::update`](struct.FmodSys.html#method.update) once the channel has stopped.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.system.set_user_data(self.channel as uint, true, user_data)
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, fmod::Result> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::mut_null();
-
-            match ffi::FMOD_Channel_GetUserData(self.channel, &mut user_data) {
-                fmod::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    Ok(tmp)
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the data attached to the channel and returns its result. Returns None if no data is attached or if it isn't a `T`.
+    pub fn with_user_data<T: 'static, R>(&self, f: |&T| -> R) -> Option<R> {
+        self.system.with_user_data(self.channel as uint, f)
     }
 
     pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
         match unsafe { ffi::FMOD_Channel_GetMemoryInfo(self.channel, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
             fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
+        }
+    }
+}
+
+/// Channel playing a sound which remembers its settings, so the sound can be restarted where it was if FMOD steals the voice.
+///
+/// Call [`ResumableChannel::save`](struct.ResumableChannel.html#method.save) after changing the channel and regularly while
+/// it plays, then [`ResumableChannel::resume`](struct.ResumableChannel.html#method.resume) restarts it from the last saved settings.
+pub struct ResumableChannel<'a> {
+    sound: &'a Sound,
+    channel: Channel,
+    settings: ChannelSettings
+}
+
+impl<'a> ResumableChannel<'a> {
+    /// Plays `sound` and saves the settings of the new channel.
+    pub fn new(sound: &'a Sound) -> Result<ResumableChannel<'a>, FmodError> {
+        let channel = try!(sound.play());
+        let settings = try!(ResumableChannel::read_settings(&channel));
+
+        Ok(ResumableChannel{sound: sound, channel: channel, settings: settings})
+    }
+
+    fn read_settings(channel: &Channel) -> Result<ChannelSettings, FmodError> {
+        Ok(ChannelSettings {
+            volume: try!(channel.get_volume()),
+            frequency: try!(channel.get_frequency()),
+            pan: try!(channel.get_pan()),
+            mute: try!(channel.get_mute()),
+            paused: try!(channel.get_paused()),
+            priority: try!(channel.get_priority()),
+            loop_count: try!(channel.get_loop_count()),
+            position: try!(channel.get_position(FMOD_TIMEUNIT_MS)),
+            attributes_3D: match channel.get_3D_attributes() {
+                Ok(attributes) => Some(attributes),
+                Err(_) => None
+            }
+        })
+    }
+
+    pub fn get_channel<'r>(&'r self) -> &'r Channel {
+        &self.channel
+    }
+
+    /// Last saved settings.
+    pub fn get_settings<'r>(&'r self) -> &'r ChannelSettings {
+        &self.settings
+    }
+
+    pub fn state(&self) -> Result<ChannelState, FmodError> {
+        self.channel.state()
+    }
+
+    /// Saves the current settings of the channel. Does nothing if the voice has already stopped or been stolen.
+    pub fn save(&mut self) -> Result<(), FmodError> {
+        match try!(self.channel.state()) {
+            ChannelStopped | ChannelStolen => Ok(()),
+            _ => {
+                self.settings = try!(ResumableChannel::read_settings(&self.channel));
+                Ok(())
+            }
         }
     }
-}
\ No newline at end of file
+
+    /// Plays the sound again with the saved settings if the voice has been stolen. Returns true if the sound was restarted.
+    pub fn resume(&mut self) -> Result<bool, FmodError> {
+        if try!(self.channel.state()) != ChannelStolen {
+            return Ok(false);
+        }
+        let system = try!(self.sound.get_system_object());
+        let mut chan = ::std::ptr::mut_null();
+
+        try!(error::check(unsafe { ffi::FMOD_System_PlaySound(fmod_sys::get_ffi(&system), fmod::ChannelFree, sound::get_ffi(self.sound), 1,
+            &mut chan) }));
+        let channel = from_new_ptr(chan, self.channel.system.clone());
+
+        // the new channel starts paused, it's only kept once all the settings have been applied
+        match ResumableChannel::apply_settings(&channel, &self.settings) {
+            Ok(_) => {
+                self.channel = channel;
+                Ok(true)
+            }
+            Err(e) => {
+                let _ = channel.stop();
+                Err(e)
+            }
+        }
+    }
+
+    fn apply_settings(channel: &Channel, settings: &ChannelSettings) -> Result<(), FmodError> {
+        try!(channel.set_volume(settings.volume));
+        try!(channel.set_frequency(settings.frequency));
+        try!(channel.set_pan(settings.pan));
+        try!(channel.set_mute(settings.mute));
+        try!(channel.set_priority(settings.priority));
+        try!(channel.set_loop_count(settings.loop_count));
+        try!(channel.set_position(settings.position, FMOD_TIMEUNIT_MS));
+        match settings.attributes_3D {
+            Some((ref position, ref velocity)) => try!(channel.set_3D_attributes(position, velocity)),
+            None => {}
+        }
+        channel.set_paused(settings.paused)
+    }
+}
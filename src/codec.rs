@@ -0,0 +1,340 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use types::*;
+use enums::*;
+use fmod_sys::FmodCodecDescription;
+use libc::{c_void, c_uint, c_int, c_char};
+use std::mem::transmute;
+use std::c_vec::CVec;
+use std::default::Default;
+
+struct CodecInstance {
+    codec: Box<Codec>,
+    wave_format: ffi::FMOD_CODEC_WAVEFORMAT
+}
+
+unsafe fn get_instance<'r>(codec_state: *mut ffi::FMOD_CODEC_STATE) -> Option<&'r mut CodecInstance> {
+    if codec_state.is_not_null() && (*codec_state).plugindata.is_not_null() {
+        Some(transmute((*codec_state).plugindata))
+    } else {
+        None
+    }
+}
+
+fn get_wave_format_ffi(wave_format: &CodecWaveFormat) -> ffi::FMOD_CODEC_WAVEFORMAT {
+    let mut name = [0 as c_char, ..256];
+
+    for (it, c) in wave_format.name.as_bytes().iter().take(255).enumerate() {
+        name[it] = *c as c_char;
+    }
+    ffi::FMOD_CODEC_WAVEFORMAT {
+        name: name,
+        format: wave_format.format,
+        channels: wave_format.channels,
+        frequency: wave_format.frequency,
+        lengthbytes: wave_format.length_bytes,
+        lengthpcm: wave_format.length_pcm,
+        blockalign: wave_format.block_align,
+        loopstart: wave_format.loop_start,
+        loopend: wave_format.loop_end,
+        mode: match wave_format.mode {FmodMode(v) => v},
+        channelmask: wave_format.channel_mask
+    }
+}
+
+/* FMOD doesn't give any user data to the open callback, so each registered codec type gets its own instance of it. */
+extern "C" fn open_callback<C: Codec + Default + 'static>(codec_state: *mut ffi::FMOD_CODEC_STATE, _user_mode: ffi::FMOD_MODE,
+    _userexinfo: *mut ffi::FMOD_CREATESOUNDEXINFO) -> fmod::Result {
+    unsafe {
+        if codec_state.is_null() {
+            return fmod::ErrFormat;
+        }
+        let new_codec : C = Default::default();
+        let mut codec = box new_codec as Box<Codec>;
+        let mut file = CodecFile{codec_state: codec_state};
+
+        match codec.open(&mut file) {
+            Ok(wave_format) => {
+                let tags = codec.metadata();
+                let mut instance = box CodecInstance {
+                    codec: codec,
+                    wave_format: get_wave_format_ffi(&wave_format)
+                };
+
+                (*codec_state).numsubsounds = 0;
+                (*codec_state).waveformat = &mut instance.wave_format;
+                (*codec_state).plugindata = transmute::<Box<CodecInstance>, *mut c_void>(instance);
+                for tag in tags.iter() {
+                    file.add_tag(tag);
+                }
+                fmod::Ok
+            }
+            Err(e) => e
+        }
+    }
+}
+
+extern "C" fn close_callback(codec_state: *mut ffi::FMOD_CODEC_STATE) -> fmod::Result {
+    unsafe {
+        if codec_state.is_not_null() && (*codec_state).plugindata.is_not_null() {
+            let mut instance : Box<CodecInstance> = transmute((*codec_state).plugindata);
+
+            instance.codec.close();
+            (*codec_state).plugindata = ::std::ptr::mut_null();
+            (*codec_state).waveformat = ::std::ptr::mut_null();
+        }
+        fmod::Ok
+    }
+}
+
+extern "C" fn read_callback(codec_state: *mut ffi::FMOD_CODEC_STATE, buffer: *mut c_void, size_bytes: c_uint, bytes_read: *mut c_uint) -> fmod::Result {
+    unsafe {
+        match get_instance(codec_state) {
+            Some(instance) => {
+                let mut t_buffer = CVec::new(buffer as *mut u8, size_bytes as uint);
+                let mut file = CodecFile{codec_state: codec_state};
+
+                match instance.codec.read(&mut file, t_buffer.as_mut_slice()) {
+                    Ok(read) => {
+                        *bytes_read = read as c_uint;
+                        if read < size_bytes as u32 {
+                            fmod::ErrFileEOF
+                        } else {
+                            fmod::Ok
+                        }
+                    }
+                    Err(e) => e
+                }
+            }
+            None => fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn get_length_callback(codec_state: *mut ffi::FMOD_CODEC_STATE, length: *mut c_uint, length_type: ffi::FMOD_TIMEUNIT) -> fmod::Result {
+    unsafe {
+        match get_instance(codec_state) {
+            Some(instance) => match instance.codec.get_length(FmodTimeUnit(length_type)) {
+                Ok(l) => {
+                    *length = l as c_uint;
+                    fmod::Ok
+                }
+                Err(e) => e
+            },
+            None => fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn set_position_callback(codec_state: *mut ffi::FMOD_CODEC_STATE, sub_sound: c_int, position: c_uint, postype: ffi::FMOD_TIMEUNIT) -> fmod::Result {
+    unsafe {
+        match get_instance(codec_state) {
+            Some(instance) => {
+                let mut file = CodecFile{codec_state: codec_state};
+
+                instance.codec.set_position(&mut file, sub_sound as i32, position as u32, FmodTimeUnit(postype))
+            }
+            None => fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn get_position_callback(codec_state: *mut ffi::FMOD_CODEC_STATE, position: *mut c_uint, postype: ffi::FMOD_TIMEUNIT) -> fmod::Result {
+    unsafe {
+        match get_instance(codec_state) {
+            Some(instance) => match instance.codec.get_position(FmodTimeUnit(postype)) {
+                Ok(p) => {
+                    *position = p as c_uint;
+                    fmod::Ok
+                }
+                Err(e) => e
+            },
+            None => fmod::ErrInvalidHandle
+        }
+    }
+}
+
+/// Format of the data decoded by a [`Codec`](trait.Codec.html).
+#[deriving(Show, PartialEq, Clone)]
+pub struct CodecWaveFormat {
+    /// [w] Name of the sound.
+    pub name        : String,
+    /// [w] Format of the decoded data, ie fmod::SoundFormatPCM16.
+    pub format      : fmod::SoundFormat,
+    /// [w] Number of channels, ie mono = 1, stereo = 2.
+    pub channels    : i32,
+    /// [w] Default frequency in hz, ie 44100.
+    pub frequency   : i32,
+    /// [w] Length in bytes of the source data.
+    pub length_bytes: u32,
+    /// [w] Length in decompressed PCM samples. Used for [`Sound::get_length`](struct.Sound.html#method.get_length) and for the memory allocation of static samples.
+    pub length_pcm  : u32,
+    /// [w] Optimal decode chunk size in PCM samples. [`Codec::read`](trait.Codec.html#tymethod.read) will be called with multiples of this value.
+    pub block_align : i32,
+    /// [w] Loop start in PCM samples.
+    pub loop_start  : i32,
+    /// [w] Loop end in PCM samples.
+    pub loop_end    : i32,
+    /// [w] Default mode of the sound (looping, 2D/3D...).
+    pub mode        : FmodMode,
+    /// [w] Microsoft speaker channel mask. Leave at 0 to play in natural speaker order.
+    pub channel_mask: u32
+}
+
+impl Default for CodecWaveFormat {
+    fn default() -> CodecWaveFormat {
+        CodecWaveFormat {
+            name: String::new(),
+            format: fmod::SoundFormatPCM16,
+            channels: 2i32,
+            frequency: 44100i32,
+            length_bytes: 0u32,
+            length_pcm: 0u32,
+            block_align: 0i32,
+            loop_start: 0i32,
+            loop_end: 0i32,
+            mode: FmodMode(0u32),
+            channel_mask: 0u32
+        }
+    }
+}
+
+/// Tag sent to FMOD by [`Codec::metadata`](trait.Codec.html#method.metadata). It can be read afterwards with [`Sound::get_tag`](struct.Sound.html#method.get_tag).
+#[deriving(Show, PartialEq, Clone)]
+pub struct CodecTag {
+    /// [w] Type of the tag.
+    pub tag_type : fmod::TagType,
+    /// [w] Name of the tag.
+    pub name     : String,
+    /// [w] Content of the tag.
+    pub data     : Vec<u8>,
+    /// [w] Type of the content.
+    pub data_type: fmod::TagDataType,
+    /// [w] If true, the tag replaces the one with the same name.
+    pub unique   : bool
+}
+
+/// Access to the file opened by FMOD, given to the [`Codec`](trait.Codec.html) methods.
+pub struct CodecFile {
+    codec_state: *mut ffi::FMOD_CODEC_STATE
+}
+
+impl CodecFile {
+    /// Size of the file in bytes.
+    pub fn get_size(&self) -> u32 {
+        unsafe { (*self.codec_state).filesize as u32 }
+    }
+
+    /// Reads from the file. Returns the number of bytes read.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<u32, fmod::Result> {
+        let mut read = 0u32;
+
+        match unsafe { (*self.codec_state).fileread } {
+            Some(f) => match f(unsafe { (*self.codec_state).filehandle }, buffer.as_mut_ptr() as *mut c_void, buffer.len() as c_uint, &mut read,
+                ::std::ptr::mut_null()) {
+                fmod::Ok | fmod::ErrFileEOF => Ok(read),
+                e => Err(e)
+            },
+            None => Err(fmod::ErrUnsupported)
+        }
+    }
+
+    /// Seeks to `pos` bytes from the beginning of the file.
+    pub fn seek(&mut self, pos: u32) -> fmod::Result {
+        match unsafe { (*self.codec_state).fileseek } {
+            Some(f) => f(unsafe { (*self.codec_state).filehandle }, pos as c_uint, ::std::ptr::mut_null()),
+            None => fmod::ErrUnsupported
+        }
+    }
+
+    fn add_tag(&mut self, tag: &CodecTag) -> fmod::Result {
+        match unsafe { (*self.codec_state).metadata } {
+            Some(f) => {
+                let mut data = tag.data.clone();
+
+                tag.name.with_c_str(|c_name| {
+                    f(self.codec_state, tag.tag_type, c_name as *mut c_char, data.as_mut_ptr() as *mut c_void, data.len() as c_uint,
+                        tag.data_type, if tag.unique { 1 } else { 0 })
+                })
+            }
+            None => fmod::ErrUnsupported
+        }
+    }
+}
+
+/// Trait to implement to decode a file format in Rust. One instance is created with `Default::default` for each file FMOD tries to open with it.
+/// Its methods are called from the thread opening or streaming the file, which is often a FMOD thread.
+pub trait Codec: Send {
+    /// Called when FMOD tries to open a file. Returns the format of the decoded data, or fmod::ErrFormat if the file isn't handled by this codec.
+    fn open(&mut self, file: &mut CodecFile) -> Result<CodecWaveFormat, fmod::Result>;
+    /// Called when the sound is released.
+    fn close(&mut self) {}
+    /// Decodes data into `buffer`, in the format returned by `open`. Returns the number of bytes written.
+    fn read(&mut self, file: &mut CodecFile, buffer: &mut [u8]) -> Result<u32, fmod::Result>;
+    /// Returns the length of the sound in `time_unit`.
+    #[allow(unused_variable)]
+    fn get_length(&mut self, time_unit: FmodTimeUnit) -> Result<u32, fmod::Result> {
+        Err(fmod::ErrUnsupported)
+    }
+    /// Seeks to `position` in `time_unit`, which is one of the time units given in the [`FmodCodecDescription`](struct.FmodCodecDescription.html).
+    fn set_position(&mut self, file: &mut CodecFile, sub_sound: i32, position: u32, time_unit: FmodTimeUnit) -> fmod::Result;
+    /// Returns the current position in `time_unit`.
+    #[allow(unused_variable)]
+    fn get_position(&mut self, time_unit: FmodTimeUnit) -> Result<u32, fmod::Result> {
+        Err(fmod::ErrUnsupported)
+    }
+    /// Returns the tags of the file. Called once, just after a successful `open`.
+    fn metadata(&mut self) -> Vec<CodecTag> {
+        Vec::new()
+    }
+}
+
+/// Registers the codec type `C`, a new one being created each time FMOD tries to open a file with it, and returns the FMOD codec handle.
+pub fn register_codec<C: Codec + Default + 'static>(system: *mut ffi::FMOD_SYSTEM, description: &FmodCodecDescription, priority: u32)
+    -> Result<u32, fmod::Result> {
+    // FMOD keeps a pointer to the name, so it is never freed
+    let name = description.name.to_c_str();
+    let mut handle = 0u32;
+    let mut t_description = ffi::FMOD_CODEC_DESCRIPTION {
+        name: unsafe { name.unwrap() as *mut c_char },
+        version: description.version,
+        defaultasstream: description.default_as_stream,
+        timeunits: match description.time_units {FmodTimeUnit(v) => v},
+        open: Some(open_callback::<C>),
+        close: Some(close_callback),
+        read: Some(read_callback),
+        getlength: Some(get_length_callback),
+        setposition: Some(set_position_callback),
+        getposition: Some(get_position_callback),
+        soundcreate: None,
+        getwaveformat: None
+    };
+
+    match unsafe { ffi::FMOD_System_RegisterCodec(system, &mut t_description, &mut handle, priority) } {
+        fmod::Ok => Ok(handle),
+        e => Err(e)
+    }
+}
@@ -0,0 +1,212 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use enums::*;
+use ffi;
+use error;
+use error::FmodError;
+use fmod_sys;
+use sound;
+use sound::Sound;
+use channel;
+use channel::Channel;
+use vector;
+use std::collections::hashmap::HashMap;
+use std::comm;
+
+/// Identifier of a sound given to [`FmodSys::register_sound`](struct.FmodSys.html#method.register_sound).
+#[deriving(Show, PartialEq, Eq, Hash, Clone)]
+pub struct SoundId(pub uint);
+
+/// Identifier of a channel started by [`CommandSender::play`](struct.CommandSender.html#method.play).
+#[deriving(Show, PartialEq, Eq, Hash, Clone)]
+pub struct ChannelId(pub uint);
+
+enum Command {
+    Play(SoundId, bool, Sender<Result<ChannelId, FmodError>>),
+    Stop(ChannelId, Sender<Result<(), FmodError>>),
+    SetVolume(ChannelId, f32, Sender<Result<(), FmodError>>),
+    SetPaused(ChannelId, bool, Sender<Result<(), FmodError>>),
+    Set3DAttributes(ChannelId, vector::FmodVector, vector::FmodVector, Sender<Result<(), FmodError>>)
+}
+
+/// Handle sending commands to a system from any thread. They are run by the next call to [`FmodSys::update`](struct.FmodSys.html#method.update),
+/// on the thread calling it, and each result is sent back through the returned receiver.
+///
+/// If the system has been released, the command is dropped and the receiver never gets a result.
+#[deriving(Clone)]
+pub struct CommandSender {
+    sender: Sender<Command>
+}
+
+impl CommandSender {
+    fn send<T: Send>(&self, command: Command, receiver: Receiver<Result<T, FmodError>>) -> Receiver<Result<T, FmodError>> {
+        let _ = self.sender.send_opt(command);
+        receiver
+    }
+
+    /// Plays a registered sound. The result holds the identifier of the new channel.
+    pub fn play(&self, sound: SoundId, paused: bool) -> Receiver<Result<ChannelId, FmodError>> {
+        let (tx, rx) = comm::channel();
+
+        self.send(Play(sound, paused, tx), rx)
+    }
+
+    pub fn stop(&self, channel: ChannelId) -> Receiver<Result<(), FmodError>> {
+        let (tx, rx) = comm::channel();
+
+        self.send(Stop(channel, tx), rx)
+    }
+
+    pub fn set_volume(&self, channel: ChannelId, volume: f32) -> Receiver<Result<(), FmodError>> {
+        let (tx, rx) = comm::channel();
+
+        self.send(SetVolume(channel, volume, tx), rx)
+    }
+
+    pub fn set_paused(&self, channel: ChannelId, paused: bool) -> Receiver<Result<(), FmodError>> {
+        let (tx, rx) = comm::channel();
+
+        self.send(SetPaused(channel, paused, tx), rx)
+    }
+
+    pub fn set_3D_attributes(&self, channel: ChannelId, position: vector::FmodVector, velocity: vector::FmodVector) -> Receiver<Result<(), FmodError>> {
+        let (tx, rx) = comm::channel();
+
+        self.send(Set3DAttributes(channel, position, velocity, tx), rx)
+    }
+}
+
+/// Receiving side of the commands, owned by the system.
+pub struct CommandQueue {
+    sender: Sender<Command>,
+    receiver: Receiver<Command>,
+    sounds: HashMap<uint, Sound>,
+    channels: HashMap<uint, Channel>,
+    next_id: uint
+}
+
+impl CommandQueue {
+    pub fn new() -> CommandQueue {
+        let (tx, rx) = comm::channel();
+
+        CommandQueue {
+            sender: tx,
+            receiver: rx,
+            sounds: HashMap::new(),
+            channels: HashMap::new(),
+            next_id: 0u
+        }
+    }
+
+    pub fn get_sender(&self) -> CommandSender {
+        CommandSender{sender: self.sender.clone()}
+    }
+
+    /// The sound mustn't keep a reference to the system, which owns the queue.
+    pub fn register_sound(&mut self, mut sound: Sound) -> SoundId {
+        sound::detach_system(&mut sound);
+        self.next_id += 1;
+        self.sounds.insert(self.next_id, sound);
+        SoundId(self.next_id)
+    }
+
+    pub fn release_sound(&mut self, SoundId(id): SoundId) -> Result<(), FmodError> {
+        match self.sounds.pop(&id) {
+            Some(mut sound) => sound.release(),
+            None => Err(FmodError::new(fmod::ErrInvalidHandle))
+        }
+    }
+
+    fn play(sound: &Sound, paused: bool, owner: &fmod_sys::SystemRef) -> Result<Channel, FmodError> {
+        let system = try!(sound.get_system_object());
+        let mut chan = ::std::ptr::mut_null();
+        let t_paused = if paused == true {
+            1
+        } else {
+            0
+        };
+
+        try!(error::check(unsafe { ffi::FMOD_System_PlaySound(fmod_sys::get_ffi(&system), fmod::ChannelFree, sound::get_ffi(sound), t_paused,
+            &mut chan) }));
+        Ok(channel::from_new_ptr(chan, owner.clone()))
+    }
+
+    fn with_channel(&self, ChannelId(id): ChannelId, f: |&Channel| -> Result<(), FmodError>) -> Result<(), FmodError> {
+        match self.channels.find(&id) {
+            Some(channel) => f(channel),
+            None => Err(FmodError::new(fmod::ErrInvalidHandle))
+        }
+    }
+
+    /// Runs the pending commands, then forgets the channels which stopped. `owner` mustn't be borrowed, it's given to the new channels.
+    pub fn run(&mut self, owner: &fmod_sys::SystemRef) {
+        loop {
+            let command = match self.receiver.try_recv() {
+                Ok(c) => c,
+                Err(_) => break
+            };
+
+            match command {
+                Play(SoundId(id), paused, tx) => {
+                    let result = match self.sounds.find(&id) {
+                        Some(sound) => match CommandQueue::play(sound, paused, owner) {
+                            Ok(chan) => {
+                                self.next_id += 1;
+                                self.channels.insert(self.next_id, chan);
+                                Ok(ChannelId(self.next_id))
+                            }
+                            Err(e) => Err(e)
+                        },
+                        None => Err(FmodError::new(fmod::ErrInvalidHandle))
+                    };
+                    let _ = tx.send_opt(result);
+                }
+                Stop(id, tx) => {
+                    let _ = tx.send_opt(self.with_channel(id, |c| c.stop()));
+                }
+                SetVolume(id, volume, tx) => {
+                    let _ = tx.send_opt(self.with_channel(id, |c| c.set_volume(volume)));
+                }
+                SetPaused(id, paused, tx) => {
+                    let _ = tx.send_opt(self.with_channel(id, |c| c.set_paused(paused)));
+                }
+                Set3DAttributes(id, position, velocity, tx) => {
+                    let _ = tx.send_opt(self.with_channel(id, |c| c.set_3D_attributes(&position, &velocity)));
+                }
+            }
+        }
+        let mut stopped = Vec::new();
+
+        for (id, chan) in self.channels.iter() {
+            match chan.state() {
+                Ok(channel::ChannelPlaying) | Ok(channel::ChannelPaused) | Ok(channel::ChannelVirtual) => {}
+                _ => stopped.push(*id)
+            }
+        }
+        for id in stopped.iter() {
+            self.channels.pop(id);
+        }
+    }
+}
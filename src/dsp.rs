@@ -29,6 +29,11 @@ use callbacks::*;
 use dsp_connection;
 use fmod_sys;
 use fmod_sys::{FmodMemoryUsageDetails, FmodSys};
+use error;
+use error::FmodError;
+use strings;
+use dsp_processor::ProcessorData;
+use std::any::Any;
 use std::mem::transmute;
 use channel;
 use libc::{c_char, c_void, c_uint, c_int, c_float, c_ushort};
@@ -223,15 +228,13 @@ extern "C" fn config_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, hwnd: *mut c_
 }
 
 struct UserData {
-    callbacks: DspCallbacks,
-    user_data: *mut c_void
+    callbacks: DspCallbacks
 }
 
 impl UserData {
     fn new() -> UserData {
         UserData {
-            callbacks: DspCallbacks::new(),
-            user_data: ::std::ptr::mut_null()
+            callbacks: DspCallbacks::new()
         }
     }
 }
@@ -501,9 +504,41 @@ pub fn get_state_ffi(state: &DspState) -> ffi::FMOD_DSP_STATE {
     }
 }
 
+/* Returns the addresses of the connections of `dsp` linked to `target`, or to any DSP if it's null. FMOD destroys them when they are
+ * disconnected, so their user data has to be dropped at the same time. */
+fn get_connections(dsp: *mut ffi::FMOD_DSP, inputs: bool, outputs: bool, target: *mut ffi::FMOD_DSP) -> Vec<uint> {
+    let mut connections = Vec::new();
+    let mut num_inputs = 0i32;
+    let mut num_outputs = 0i32;
+
+    unsafe {
+        if inputs && ffi::FMOD_DSP_GetNumInputs(dsp, &mut num_inputs) == fmod::Ok {
+            for index in range(0, num_inputs) {
+                let mut input = ::std::ptr::mut_null();
+                let mut connection = ::std::ptr::mut_null();
+
+                if ffi::FMOD_DSP_GetInput(dsp, index, &mut input, &mut connection) == fmod::Ok && (target.is_null() || input == target) {
+                    connections.push(connection as uint);
+                }
+            }
+        }
+        if outputs && ffi::FMOD_DSP_GetNumOutputs(dsp, &mut num_outputs) == fmod::Ok {
+            for index in range(0, num_outputs) {
+                let mut output = ::std::ptr::mut_null();
+                let mut connection = ::std::ptr::mut_null();
+
+                if ffi::FMOD_DSP_GetOutput(dsp, index, &mut output, &mut connection) == fmod::Ok && (target.is_null() || output == target) {
+                    connections.push(connection as uint);
+                }
+            }
+        }
+    }
+    connections
+}
+
 pub fn from_state_ptr(state: ffi::FMOD_DSP_STATE) -> DspState {
     DspState {
-        instance: from_ptr(state.instance),
+        instance: from_ptr(state.instance, fmod_sys::SystemRef::none()),
         plugin_data: state.plugin_data,
         speaker_mask: state.speaker_mask
     }
@@ -520,25 +555,21 @@ pub struct DspState
     pub speaker_mask: u16
 }
 
-pub fn from_ptr(dsp: *mut ffi::FMOD_DSP) -> Dsp {
+pub fn from_ptr(dsp: *mut ffi::FMOD_DSP, system: fmod_sys::SystemRef) -> Dsp {
     Dsp {
         dsp: dsp,
         can_be_deleted: false,
-        user_data: UserData {
-            callbacks: DspCallbacks::new(),
-            user_data: ::std::ptr::mut_null()
-        }
+        processor: None,
+        system: system
     }
 }
 
-pub fn from_ptr_first(dsp: *mut ffi::FMOD_DSP) -> Dsp {
+pub fn from_ptr_first(dsp: *mut ffi::FMOD_DSP, system: fmod_sys::SystemRef) -> Dsp {
     Dsp {
         dsp: dsp,
         can_be_deleted: true,
-        user_data: UserData {
-            callbacks: DspCallbacks::new(),
-            user_data: ::std::ptr::mut_null()
-        }
+        processor: None,
+        system: system
     }
 }
 
@@ -546,85 +577,100 @@ pub fn get_ffi(dsp: &Dsp) -> *mut ffi::FMOD_DSP {
     dsp.dsp
 }
 
+pub fn set_processor(dsp: &mut Dsp, processor: Box<ProcessorData>) {
+    dsp.processor = Some(processor);
+}
+
 /// Dsp object
 pub struct Dsp {
     dsp: *mut ffi::FMOD_DSP,
     can_be_deleted: bool,
-    user_data: UserData
+    /* pointed by the FMOD user data of the DSPs created by FmodSys::create_DSP_from_processor */
+    processor: Option<Box<ProcessorData>>,
+    system: fmod_sys::SystemRef
 }
 
 impl Drop for Dsp {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl Dsp {
-    pub fn get_system_object(&self) -> Result<FmodSys, fmod::Result> {
+    pub fn get_system_object(&self) -> Result<FmodSys, FmodError> {
         let mut system = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_DSP_GetSystemObject(self.dsp, &mut system) } {
-            fmod::Ok => Ok(fmod_sys::from_ptr(system)),
-            e => Err(e)
+            fmod::Ok => Ok(fmod_sys::from_ptr(system, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn release(&mut self) -> fmod::Result {
+    pub fn release(&mut self) -> Result<(), FmodError> {
         if self.can_be_deleted && self.dsp.is_not_null() {
+            let connections = get_connections(self.dsp, true, true, ::std::ptr::mut_null());
+
             match unsafe { ffi::FMOD_DSP_Release(self.dsp) } {
                 fmod::Ok => {
+                    self.remove_connections_data(connections);
+                    self.system.remove_user_data(self.dsp as uint);
                     self.dsp =::std::ptr::mut_null();
-                    fmod::Ok
+                    self.processor = None;
+                    Ok(())
                 }
-                e => e
+                e => Err(FmodError::new(e))
             }
         } else {
-            fmod::Ok
+            Ok(())
         }
     }
 
-    pub fn play(&self) -> Result<channel::Channel, fmod::Result> {
+    pub fn play(&self) -> Result<channel::Channel, FmodError> {
         let mut channel = ::std::ptr::mut_null();
 
         match match self.get_system_object() {
             Ok(s) => { 
                 unsafe { ffi::FMOD_System_PlayDSP(fmod_sys::get_ffi(&s), fmod::ChannelFree, self.dsp, 0, &mut channel) }
             }
-            Err(e) => e
+            Err(e) => return Err(e)
         } {
-            fmod::Ok => Ok(channel::from_ptr(channel)),
-            e => Err(e)
+            fmod::Ok => Ok(channel::from_new_ptr(channel, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn play_with_parameters(&self, channel_id: fmod::ChannelIndex) -> Result<channel::Channel, fmod::Result> {
+    pub fn play_with_parameters(&self, channel_id: fmod::ChannelIndex) -> Result<channel::Channel, FmodError> {
         let mut channel = ::std::ptr::mut_null();
         
         match match self.get_system_object() {
             Ok(s) => { 
                 unsafe { ffi::FMOD_System_PlayDSP(fmod_sys::get_ffi(&s), channel_id, self.dsp, 0, &mut channel) }
             }
-            Err(e) => e
+            Err(e) => return Err(e)
         } {
-            fmod::Ok => Ok(channel::from_ptr(channel)),
-            e => Err(e)
+            fmod::Ok => Ok(channel::from_ptr(channel, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn add_input(&self, target: Dsp) -> Result<dsp_connection::DspConnection, fmod::Result> {
+    pub fn add_input(&self, target: Dsp) -> Result<dsp_connection::DspConnection, FmodError> {
         let mut connection = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_DSP_AddInput(self.dsp, target.dsp, &mut connection) } {
-            fmod::Ok => Ok(dsp_connection::from_ptr(connection)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp_connection::from_ptr(connection, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn disconnect_from(&self, target: Dsp) -> fmod::Result {
-        unsafe { ffi::FMOD_DSP_DisconnectFrom(self.dsp, target.dsp) }
+    pub fn disconnect_from(&self, target: Dsp) -> Result<(), FmodError> {
+        let connections = get_connections(self.dsp, true, true, target.dsp);
+
+        try!(error::check(unsafe { ffi::FMOD_DSP_DisconnectFrom(self.dsp, target.dsp) }));
+        self.remove_connections_data(connections);
+        Ok(())
     }
 
-    pub fn disconnect_all(&self, inputs: bool, outputs: bool) -> fmod::Result {
+    pub fn disconnect_all(&self, inputs: bool, outputs: bool) -> Result<(), FmodError> {
         let t_inputs = if inputs == true {
             1
         } else {
@@ -636,110 +682,124 @@ impl Dsp {
             0
         };
 
-        unsafe { ffi::FMOD_DSP_DisconnectAll(self.dsp, t_inputs, t_outputs) }
+        let connections = get_connections(self.dsp, inputs, outputs, ::std::ptr::mut_null());
+
+        try!(error::check(unsafe { ffi::FMOD_DSP_DisconnectAll(self.dsp, t_inputs, t_outputs) }));
+        self.remove_connections_data(connections);
+        Ok(())
+    }
+
+    pub fn remove(&self) -> Result<(), FmodError> {
+        let connections = get_connections(self.dsp, true, true, ::std::ptr::mut_null());
+
+        try!(error::check(unsafe { ffi::FMOD_DSP_Remove(self.dsp) }));
+        self.remove_connections_data(connections);
+        Ok(())
     }
 
-    pub fn remove(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_DSP_Remove(self.dsp) }
+    fn remove_connections_data(&self, connections: Vec<uint>) {
+        for connection in connections.iter() {
+            self.system.remove_user_data(*connection);
+        }
     }
 
-    pub fn get_num_inputs(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_inputs(&self) -> Result<i32, FmodError> {
         let mut inputs = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetNumInputs(self.dsp, &mut inputs) } {
             fmod::Ok => Ok(inputs),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_outputs(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_outputs(&self) -> Result<i32, FmodError> {
         let mut outputs = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetNumOutputs(self.dsp, &mut outputs) } {
             fmod::Ok => Ok(outputs),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_input(&self, index: i32) -> Result<(Dsp, dsp_connection::DspConnection), fmod::Result> {
+    pub fn get_input(&self, index: i32) -> Result<(Dsp, dsp_connection::DspConnection), FmodError> {
         let mut input = ::std::ptr::mut_null();
         let mut input_connection = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_DSP_GetInput(self.dsp, index, &mut input, &mut input_connection) } {
-            fmod::Ok => Ok((from_ptr(input), dsp_connection::from_ptr(input_connection))),
-            e => Err(e)
+            fmod::Ok => Ok((from_ptr(input, self.system.clone()), dsp_connection::from_ptr(input_connection, self.system.clone()))),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_output(&self, index: i32) -> Result<(Dsp, dsp_connection::DspConnection), fmod::Result> {
+    pub fn get_output(&self, index: i32) -> Result<(Dsp, dsp_connection::DspConnection), FmodError> {
         let mut output = ::std::ptr::mut_null();
         let mut output_connection = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_DSP_GetOutput(self.dsp, index, &mut output, &mut output_connection) } {
-            fmod::Ok => Ok((from_ptr(output), dsp_connection::from_ptr(output_connection ))),
-            e => Err(e)
+            fmod::Ok => Ok((from_ptr(output, self.system.clone()), dsp_connection::from_ptr(output_connection , self.system.clone()))),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_active(&self, active: bool) -> fmod::Result {
+    pub fn set_active(&self, active: bool) -> Result<(), FmodError> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_DSP_SetActive(self.dsp, t_active) }
+        error::check(unsafe { ffi::FMOD_DSP_SetActive(self.dsp, t_active) })
     }
 
-    pub fn get_active(&self) -> Result<bool, fmod::Result> {
+    pub fn get_active(&self) -> Result<bool, FmodError> {
         let mut active = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetActive(self.dsp, &mut active) } {
             fmod::Ok => Ok(active != 0i32),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_bypass(&self, bypass: bool) -> fmod::Result {
+    pub fn set_bypass(&self, bypass: bool) -> Result<(), FmodError> {
         let t_bypass = if bypass == true {
             1i32
         } else {
             0i32
         };
 
-        unsafe { ffi::FMOD_DSP_SetBypass(self.dsp, t_bypass) }
+        error::check(unsafe { ffi::FMOD_DSP_SetBypass(self.dsp, t_bypass) })
     }
 
-    pub fn get_bypass(&self) -> Result<bool, fmod::Result> {
+    pub fn get_bypass(&self) -> Result<bool, FmodError> {
         let mut bypass = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetBypass(self.dsp, &mut bypass) } {
             fmod::Ok => Ok(bypass == 1i32),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_speaker_active(&self, speaker: fmod::Speaker, active: bool) -> fmod::Result {
+    pub fn set_speaker_active(&self, speaker: fmod::Speaker, active: bool) -> Result<(), FmodError> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_DSP_SetSpeakerActive(self.dsp, speaker, t_active) }
+        error::check(unsafe { ffi::FMOD_DSP_SetSpeakerActive(self.dsp, speaker, t_active) })
     }
 
-    pub fn get_speaker_active(&self, speaker: fmod::Speaker) -> Result<bool, fmod::Result> {
+    pub fn get_speaker_active(&self, speaker: fmod::Speaker) -> Result<bool, FmodError> {
         let mut active = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetSpeakerActive(self.dsp, speaker, &mut active) } {
             fmod::Ok => Ok(active == 1i32),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn reset(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_DSP_Reset(self.dsp) }
+    pub fn reset(&self) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_DSP_Reset(self.dsp) })
     }
 
     /// value argument depends directly on the index argument,
@@ -764,8 +824,8 @@ impl Dsp {
     /// * [`DspSfxReverb`](enums/fmod/type.DspSfxReverb.html)
     /// * [`DspLowPassSimple`](enums/fmod/type.DspLowPassSimple.html)
     /// * [`DspHighPassSimple`](enums/fmod/type.DspHighPassSimple.html)
-    pub fn set_parameter(&self, index: i32, value: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_DSP_SetParameter(self.dsp, index, value) }
+    pub fn set_parameter(&self, index: i32, value: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_DSP_SetParameter(self.dsp, index, value) })
     }
 
     /// value result depends directly on the index argument,
@@ -790,76 +850,74 @@ impl Dsp {
     /// * [`DspSfxReverb`](enums/fmod/type.DspSfxReverb.html)
     /// * [`DspLowPassSimple`](enums/fmod/type.DspLowPassSimple.html)
     /// * [`DspHighPassSimple`](enums/fmod/type.DspHighPassSimple.html)
-    pub fn get_parameter(&self, index: i32, value_str_len: u32) -> Result<(f32, String), fmod::Result> {
-        let tmp_v = String::with_capacity(value_str_len as uint);
+    pub fn get_parameter(&self, index: i32) -> Result<(f32, String), FmodError> {
         let mut value = 0f32;
 
-        tmp_v.with_c_str(|c_str|{
-            match unsafe { ffi::FMOD_DSP_GetParameter(self.dsp, index, &mut value, c_str as *mut c_char, value_str_len as i32) } {
-                fmod::Ok => Ok((value, unsafe {::std::str::raw::from_c_str(c_str).clone() })),
-                e => Err(e)
-            }
-        })
+        match strings::get_string(|value_str, value_str_len| unsafe { ffi::FMOD_DSP_GetParameter(self.dsp, index, &mut value, value_str,
+            value_str_len) }) {
+            Ok(value_str) => Ok((value, value_str)),
+            Err(e) => Err(FmodError::new(e))
+        }
     }
 
-    pub fn get_num_parameters(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_parameters(&self) -> Result<i32, FmodError> {
         let mut num_param = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetNumParameters(self.dsp, &mut num_param) } {
             fmod::Ok => Ok(num_param),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_parameter_info(&self, index: i32, name: &String, label: &String, description_len: u32) -> Result<(String, f32, f32), fmod::Result> {
+    /// Returns the name, the label, the description, the minimum and the maximum of the parameter.
+    pub fn get_parameter_info(&self, index: i32) -> Result<(String, String, String, f32, f32), FmodError> {
         let mut min = 0f32;
         let mut max = 0f32;
-        let tmp_d = String::with_capacity(description_len as uint);
-        let t_name = name.clone();
-        let t_label = label.clone();
-
-        tmp_d.with_c_str(|c_description| {
-            t_name.with_c_str(|c_name| {
-                t_label.with_c_str(|c_label|{
-                    match unsafe { ffi::FMOD_DSP_GetParameterInfo(self.dsp, index, c_name as *mut c_char, c_label as *mut c_char,
-                        c_description as *mut c_char, description_len as i32, &mut min, &mut max) } {
-                        fmod::Ok => Ok((unsafe {::std::str::raw::from_c_str(c_description).clone() }, min, max)),
-                        e => Err(e)
-                    }
-                })
-            })
-        })
-    }
-
-    pub fn get_info(&self, name: &String) -> Result<(u32, i32, i32, i32), fmod::Result> {
+
+        // FMOD writes up to 16 characters in the name and the label, only the description length is given
+        match strings::get_strings(3, |buffers, description_len| unsafe {
+            ffi::FMOD_DSP_GetParameterInfo(self.dsp, index, buffers[0].as_mut_ptr(), buffers[1].as_mut_ptr(), buffers[2].as_mut_ptr(),
+                description_len, &mut min, &mut max) }) {
+            Ok(mut strings) => {
+                let description = strings.pop().unwrap();
+                let label = strings.pop().unwrap();
+                let name = strings.pop().unwrap();
+
+                Ok((name, label, description, min, max))
+            }
+            Err(e) => Err(FmodError::new(e))
+        }
+    }
+
+    /// Returns the name, the version, the number of channels and the size of the configuration dialog.
+    pub fn get_info(&self) -> Result<(String, u32, i32, i32, i32), FmodError> {
+        // FMOD writes up to 32 characters in the name
+        let mut name = [0 as c_char, ..32];
         let mut version = 0u32;
         let mut channels = 0i32;
         let mut config_width = 0i32;
         let mut config_height = 0i32;
-        let tmp_n = name.clone();
 
-        tmp_n.with_c_str(|c_name| {
-            match unsafe { ffi::FMOD_DSP_GetInfo(self.dsp, c_name as *mut c_char, &mut version, &mut channels, &mut config_width, &mut config_height) } {
-                fmod::Ok => Ok((version, channels, config_width, config_height)),
-                e => Err(e)
-            }
-        })
+        match unsafe { ffi::FMOD_DSP_GetInfo(self.dsp, name.as_mut_ptr(), &mut version, &mut channels, &mut config_width, &mut config_height) } {
+            fmod::Ok => Ok((strings::from_buffer(name.as_slice()), version, channels, config_width, config_height)),
+            e => Err(FmodError::new(e))
+        }
     }
 
-    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_DSP_SetDefaults(self.dsp, frequency, volume, pan, priority) }
+    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_DSP_SetDefaults(self.dsp, frequency, volume, pan, priority) })
     }
 
-    pub fn get_type(&self) -> Result<fmod::DspType, fmod::Result> {
+    pub fn get_type(&self) -> Result<fmod::DspType, FmodError> {
         let mut _type = fmod::Unknown;
 
         match unsafe { ffi::FMOD_DSP_GetType(self.dsp, &mut _type) } {
             fmod::Ok => Ok(_type),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), fmod::Result> {
+    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), FmodError> {
         let mut frequency = 0f32;
         let mut volume = 0f32;
         let mut pan = 0f32;
@@ -867,63 +925,31 @@ impl Dsp {
 
         match unsafe { ffi::FMOD_DSP_GetDefaults(self.dsp, &mut frequency, &mut volume, &mut pan, &mut priority) } {
             fmod::Ok => Ok((frequency, volume, pan, priority)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
         match unsafe { ffi::FMOD_DSP_GetMemoryInfo(self.dsp, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
             fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_user_data<T>(&mut self, user_data: &mut T) -> fmod::Result {
-        let mut data : *mut c_void = ::std::ptr::mut_null();
-
-        unsafe {
-            match ffi::FMOD_DSP_GetUserData(self.dsp, &mut data) {
-                fmod::Ok => {
-                    if data.is_null() {
-                        self.user_data.user_data = ::std::ptr::mut_null();
-
-                        ffi::FMOD_DSP_SetUserData(self.dsp, transmute(&mut self.user_data))
-                    } else {
-                        let tmp : &mut UserData = transmute::<*mut c_void, &mut UserData>(data);
-
-                        tmp.user_data = transmute::<&mut T, *mut c_void>(user_data);
-                        ffi::FMOD_DSP_SetUserData(self.dsp, transmute(tmp))
-                    }
-                }
-                _ => {
-                    self.user_data.user_data = transmute::<&mut T, *mut c_void>(user_data);
-
-                    ffi::FMOD_DSP_SetUserData(self.dsp, transmute(&mut self.user_data))
-                }
-            }
-        }
+    /// Attaches `user_data` to the DSP, replacing and dropping the previous one. It's dropped when the DSP is released.
+    ///
+    /// Fails on the DSPs given by a [`BadDspConnection`](enum.SystemEvent.html) event and on the `instance` of a [`DspState`](struct.DspState.html)
+    /// given to a custom DSP callback, as they aren't linked to their system.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.system.set_user_data(self.dsp as uint, false, user_data)
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, fmod::Result> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::mut_null();
-
-            match ffi::FMOD_DSP_GetUserData(self.dsp, &mut user_data) {
-                fmod::Ok => {
-                    if user_data.is_not_null() {
-                        let tmp : &mut UserData = transmute::<*mut c_void, &mut UserData>(user_data);
-                        let tmp2 : &mut T = transmute::<*mut c_void, &mut T>(tmp.user_data);
-                        Ok(tmp2)
-                    } else {
-                        Err(fmod::Ok)
-                    }
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the data attached to the DSP and returns its result. Returns None if no data is attached or if it isn't a `T`.
+    pub fn with_user_data<T: 'static, R>(&self, f: |&T| -> R) -> Option<R> {
+        self.system.with_user_data(self.dsp as uint, f)
     }
 }
\ No newline at end of file
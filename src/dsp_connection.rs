@@ -26,14 +26,16 @@ use ffi;
 use types::*;
 use enums::*;
 use dsp;
-use libc::{c_int, c_void};
+use libc::c_int;
 use fmod_sys;
 use fmod_sys::FmodMemoryUsageDetails;
-use std::mem::transmute;
+use error;
+use error::FmodError;
+use std::any::Any;
 use std::default::Default;
 
-pub fn from_ptr(dsp_connection: *mut ffi::FMOD_DSPCONNECTION) -> DspConnection {
-    DspConnection{dsp_connection: dsp_connection}
+pub fn from_ptr(dsp_connection: *mut ffi::FMOD_DSPCONNECTION, system: fmod_sys::SystemRef) -> DspConnection {
+    DspConnection{dsp_connection: dsp_connection, system: system}
 }
 
 pub fn get_ffi(dsp_connection: DspConnection) -> *mut ffi::FMOD_DSPCONNECTION {
@@ -42,7 +44,8 @@ pub fn get_ffi(dsp_connection: DspConnection) -> *mut ffi::FMOD_DSPCONNECTION {
 
 /// DspConnection object
 pub struct DspConnection {
-    dsp_connection: *mut ffi::FMOD_DSPCONNECTION
+    dsp_connection: *mut ffi::FMOD_DSPCONNECTION,
+    system: fmod_sys::SystemRef
 }
 
 impl Drop for DspConnection {
@@ -56,76 +59,72 @@ impl DspConnection {
         self.dsp_connection = ::std::ptr::mut_null();
     }
 
-    pub fn get_input(&self) -> Result<dsp::Dsp, fmod::Result> {
+    pub fn get_input(&self) -> Result<dsp::Dsp, FmodError> {
         let mut input = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_DSPConnection_GetInput(self.dsp_connection, &mut input) } {
-            fmod::Ok => Ok(dsp::from_ptr(input)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr(input, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_output(&self) -> Result<dsp::Dsp, fmod::Result> {
+    pub fn get_output(&self) -> Result<dsp::Dsp, FmodError> {
         let mut output = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_DSPConnection_GetOutput(self.dsp_connection, &mut output) } {
-            fmod::Ok => Ok(dsp::from_ptr(output)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr(output, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_mix(&self, volume: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_DSPConnection_SetMix(self.dsp_connection, volume) }
+    pub fn set_mix(&self, volume: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_DSPConnection_SetMix(self.dsp_connection, volume) })
     }
 
-    pub fn get_mix(&self) -> Result<f32, fmod::Result> {
+    pub fn get_mix(&self) -> Result<f32, FmodError> {
         let mut volume = 0f32;
 
         match unsafe { ffi::FMOD_DSPConnection_GetMix(self.dsp_connection, &mut volume) } {
             fmod::Ok => Ok(volume),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_levels(&self, speaker: fmod::Speaker, levels: &mut Vec<f32>) -> fmod::Result {
-        unsafe { ffi::FMOD_DSPConnection_SetLevels(self.dsp_connection, speaker, levels.as_mut_ptr(), levels.len() as c_int) }
+    pub fn set_levels(&self, speaker: fmod::Speaker, levels: &mut Vec<f32>) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_DSPConnection_SetLevels(self.dsp_connection, speaker, levels.as_mut_ptr(), levels.len() as c_int) })
     }
 
-    pub fn get_levels(&self, speaker: fmod::Speaker, num_levels: uint) -> Result<Vec<f32>, fmod::Result> {
+    pub fn get_levels(&self, speaker: fmod::Speaker, num_levels: uint) -> Result<Vec<f32>, FmodError> {
         let mut levels = Vec::from_elem(num_levels, 0f32);
 
         match unsafe { ffi::FMOD_DSPConnection_GetLevels(self.dsp_connection, speaker, levels.as_mut_ptr(), levels.len() as c_int) } {
             fmod::Ok => Ok(levels),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
         match unsafe { ffi::FMOD_DSPConnection_GetMemoryInfo(self.dsp_connection, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
             fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_user_data<T>(&self, user_data: &mut T) -> fmod::Result {
-        unsafe { ffi::FMOD_DSPConnection_SetUserData(self.dsp_connection, transmute(user_data)) }
+    /// Attaches `user_data` to the connection, replacing and dropping the previous one. It's dropped when the connection is removed by
+    /// [`Dsp::disconnect_from`](struct.Dsp.html#method.disconnect_from), [`Dsp::disconnect_all`](struct.Dsp.html#method.disconnect_all),
+    /// [`Dsp::remove`](struct.Dsp.html#method.remove) or [`Dsp::release`](struct.Dsp.html#method.release).
+    ///
+    /// Fails on a connection obtained from a DSP given by a system event, as it isn't linked to its system.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.system.set_user_data(self.dsp_connection as uint, false, user_data)
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, fmod::Result> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::mut_null();
-
-            match ffi::FMOD_DSPConnection_GetUserData(self.dsp_connection, &mut user_data) {
-                fmod::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    Ok(tmp)
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the data attached to the connection and returns its result. Returns None if no data is attached or if it isn't a `T`.
+    pub fn with_user_data<T: 'static, R>(&self, f: |&T| -> R) -> Option<R> {
+        self.system.with_user_data(self.dsp_connection as uint, f)
     }
 }
\ No newline at end of file
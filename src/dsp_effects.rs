@@ -0,0 +1,378 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use enums::*;
+use dsp::Dsp;
+use fmod_sys::FmodSys;
+use error;
+use error::FmodError;
+
+fn set_parameter(dsp: &Dsp, name: &str, index: i32, value: f32, min: f32, max: f32) -> Result<(), FmodError> {
+    if value.is_nan() || value < min || value > max {
+        Err(error::out_of_range(format!("{}: {} is outside of [{}, {}]", name, value, min, max)))
+    } else {
+        dsp.set_parameter(index, value)
+    }
+}
+
+fn get_parameter(dsp: &Dsp, index: i32) -> Result<f32, FmodError> {
+    match dsp.get_parameter(index) {
+        Ok((value, _)) => Ok(value),
+        Err(e) => Err(e)
+    }
+}
+
+/* Declares the wrapper of a built-in DSP, with a setter and a getter per parameter, and the FmodSys method creating it. */
+macro_rules! builtin_dsp(
+    ($(#[$attr:meta])* $name:ident, $dsp_type:expr, $create:ident,
+        $($(#[$param_attr:meta])* $set:ident, $get:ident, $index:expr, $min:expr, $max:expr);+) => (
+        $(#[$attr])*
+        pub struct $name {
+            dsp: Dsp
+        }
+
+        impl $name {
+            /// Returns the underlying DSP, releasing it is then up to the caller.
+            pub fn into_dsp(self) -> Dsp {
+                self.dsp
+            }
+
+            $(
+                $(#[$param_attr])*
+                pub fn $set(&self, value: f32) -> Result<(), FmodError> {
+                    set_parameter(&self.dsp, concat!(stringify!($name), "::", stringify!($set)), $index as i32, value, $min, $max)
+                }
+
+                pub fn $get(&self) -> Result<f32, FmodError> {
+                    get_parameter(&self.dsp, $index as i32)
+                }
+            )+
+        }
+
+        impl Deref<Dsp> for $name {
+            fn deref<'a>(&'a self) -> &'a Dsp {
+                &self.dsp
+            }
+        }
+
+        impl FmodSys {
+            pub fn $create(&self) -> Result<$name, FmodError> {
+                match self.create_DSP_by_type($dsp_type) {
+                    Ok(dsp) => Ok($name{dsp: dsp}),
+                    Err(e) => Err(e)
+                }
+            }
+        }
+    )
+)
+
+/// Waveform generated by an [`OscillatorDsp`](struct.OscillatorDsp.html).
+#[deriving(Show, PartialEq, Clone)]
+pub enum OscillatorWaveform {
+    WaveformSine,
+    WaveformSquare,
+    WaveformSawUp,
+    WaveformSawDown,
+    WaveformTriangle,
+    WaveformNoise
+}
+
+builtin_dsp!(
+    /// Generates sine/square/saw/triangle or noise tones.
+    OscillatorDsp, fmod::Oscillator, create_oscillator_DSP,
+    /// Frequency of the wave in hz. 1.0 to 22000.0. Default = 220.0.
+    set_rate, get_rate, fmod::DspOscillatorRate, 1f32, 22000f32
+)
+
+impl OscillatorDsp {
+    /// Default = sine.
+    pub fn set_waveform(&self, waveform: OscillatorWaveform) -> Result<(), FmodError> {
+        self.dsp.set_parameter(fmod::DspOscillatorType as i32, waveform as int as f32)
+    }
+
+    pub fn get_waveform(&self) -> Result<OscillatorWaveform, FmodError> {
+        match try!(get_parameter(&self.dsp, fmod::DspOscillatorType as i32)) as int {
+            0 => Ok(WaveformSine),
+            1 => Ok(WaveformSquare),
+            2 => Ok(WaveformSawUp),
+            3 => Ok(WaveformSawDown),
+            4 => Ok(WaveformTriangle),
+            _ => Ok(WaveformNoise)
+        }
+    }
+}
+
+builtin_dsp!(
+    /// High quality, resonant lowpass filter, using more CPU time than [`LowPassSimpleDsp`](struct.LowPassSimpleDsp.html).
+    LowPassDsp, fmod::LowPass, create_low_pass_DSP,
+    /// Cutoff frequency in hz. 10.0 to 22000.0. Default = 5000.0.
+    set_cutoff, get_cutoff, fmod::DspLowPassCutoff, 10f32, 22000f32;
+    /// Resonance Q value. 1.0 to 10.0. Default = 1.0.
+    set_resonance, get_resonance, fmod::DspLowPassResonance, 1f32, 10f32
+)
+
+builtin_dsp!(
+    /// Resonant lowpass filter used in Impulse Tracker.
+    ITLowPassDsp, fmod::ITLowPass, create_IT_low_pass_DSP,
+    /// Cutoff frequency in hz. 1.0 to 22000.0. Default = 5000.0.
+    set_cutoff, get_cutoff, fmod::DspITLowPassCutoff, 1f32, 22000f32;
+    /// Resonance Q value. 0.0 to 127.0. Default = 1.0.
+    set_resonance, get_resonance, fmod::DspITLowPassResonance, 0f32, 127f32
+)
+
+builtin_dsp!(
+    /// Resonant highpass filter.
+    HighPassDsp, fmod::HighPass, create_high_pass_DSP,
+    /// Cutoff frequency in hz. 1.0 to 22000.0. Default = 5000.0.
+    set_cutoff, get_cutoff, fmod::DspHighPassCutoff, 1f32, 22000f32;
+    /// Resonance Q value. 1.0 to 10.0. Default = 1.0.
+    set_resonance, get_resonance, fmod::DspHighPassResonance, 1f32, 10f32
+)
+
+builtin_dsp!(
+    /// Echo fading out at the decay ratio.
+    EchoDsp, fmod::Echo, create_echo_DSP,
+    /// Delay in ms. 10.0 to 5000.0. Default = 500.0.
+    set_delay, get_delay, fmod::DspTypeEchoDelay, 10f32, 5000f32;
+    /// Decay per delay. 0.0 = total decay to 1.0 = no decay. Default = 0.5.
+    set_decay_ratio, get_decay_ratio, fmod::DspTypeEchoDecayRatio, 0f32, 1f32;
+    /// Maximum channels supported. 0 to 16, 0 being the default output polyphony. Default = 0.
+    set_max_channels, get_max_channels, fmod::DspTypeEchoMaxChannels, 0f32, 16f32;
+    /// Volume of the original signal. 0.0 to 1.0. Default = 1.0.
+    set_dry_mix, get_dry_mix, fmod::DspTypeEchoDryMix, 0f32, 1f32;
+    /// Volume of the echo signal. 0.0 to 1.0. Default = 1.0.
+    set_wet_mix, get_wet_mix, fmod::DspTypeEchoWetMix, 0f32, 1f32
+)
+
+builtin_dsp!(
+    /// Different delay on each channel.
+    DelayDsp, fmod::Delay, create_delay_DSP,
+    /// Maximum delay in ms. 0.0 to 10000.0. Default = 10.0.
+    set_max_delay, get_max_delay, fmod::DspDelayMaxDelay, 0f32, 10000f32
+)
+
+impl DelayDsp {
+    /// Delay of `channel` (0 to 15) in ms. 0.0 to 10000.0. Default = 0.0.
+    pub fn set_channel_delay(&self, channel: uint, delay: f32) -> Result<(), FmodError> {
+        if channel > 15 {
+            return Err(error::out_of_range(format!("DelayDsp::set_channel_delay: channel {} is outside of [0, 15]", channel)));
+        }
+        set_parameter(&self.dsp, "DelayDsp::set_channel_delay", fmod::DspDelayCH0 as i32 + channel as i32, delay, 0f32, 10000f32)
+    }
+
+    pub fn get_channel_delay(&self, channel: uint) -> Result<f32, FmodError> {
+        if channel > 15 {
+            return Err(error::out_of_range(format!("DelayDsp::get_channel_delay: channel {} is outside of [0, 15]", channel)));
+        }
+        get_parameter(&self.dsp, fmod::DspDelayCH0 as i32 + channel as i32)
+    }
+}
+
+builtin_dsp!(
+    FlangeDsp, fmod::Flange, create_flange_DSP,
+    /// Volume of the original signal. 0.0 to 1.0. Default = 0.45.
+    set_dry_mix, get_dry_mix, fmod::DspFlangeDryMix, 0f32, 1f32;
+    /// Volume of the flange signal. 0.0 to 1.0. Default = 0.55.
+    set_wet_mix, get_wet_mix, fmod::DspFlangeWetMix, 0f32, 1f32;
+    /// Percentage of 40ms delay. 0.01 to 1.0. Default = 1.0.
+    set_depth, get_depth, fmod::DspFlangeDepth, 0.01f32, 1f32;
+    /// Speed in hz. 0.0 to 20.0. Default = 0.1.
+    set_rate, get_rate, fmod::DspFlangeRate, 0f32, 20f32
+)
+
+builtin_dsp!(
+    /// Tremolo / chopper effect.
+    TremoloDsp, fmod::Tremolo, create_tremolo_DSP,
+    /// LFO frequency in hz. 0.1 to 20.0. Default = 4.0.
+    set_frequency, get_frequency, fmod::DspTremoloFrequency, 0.1f32, 20f32;
+    /// 0.0 to 1.0. Default = 0.0.
+    set_depth, get_depth, fmod::DspTremoloDepth, 0f32, 1f32;
+    /// LFO shape morph between triangle and sine. 0.0 to 1.0. Default = 0.0.
+    set_shape, get_shape, fmod::DspTremoloShape, 0f32, 1f32;
+    /// Time-skewing of LFO cycle. -1.0 to 1.0. Default = 0.0.
+    set_skew, get_skew, fmod::DspTremoloSkew, -1f32, 1f32;
+    /// LFO on-time. 0.0 to 1.0. Default = 0.5.
+    set_duty, get_duty, fmod::DspTremoloDuty, 0f32, 1f32;
+    /// Flatness of the LFO shape. 0.0 to 1.0. Default = 0.0.
+    set_square, get_square, fmod::DspTremoloSquare, 0f32, 1f32;
+    /// Instantaneous LFO phase. 0.0 to 1.0. Default = 0.0.
+    set_phase, get_phase, fmod::DspTremoloPhase, 0f32, 1f32;
+    /// Rotation / auto-pan effect. -1.0 to 1.0. Default = 0.0.
+    set_spread, get_spread, fmod::DspTremoloSpread, -1f32, 1f32
+)
+
+builtin_dsp!(
+    DistortionDsp, fmod::Distortion, create_distortion_DSP,
+    /// 0.0 to 1.0. Default = 0.5.
+    set_level, get_level, fmod::DspDistortionLevel, 0f32, 1f32
+)
+
+builtin_dsp!(
+    /// Normalizes or amplifies the sound to a certain level.
+    NormalizeDsp, fmod::Normalize, create_normalize_DSP,
+    /// Time to ramp the silence to full in ms. 0.0 to 20000.0. Default = 5000.0.
+    set_fade_time, get_fade_time, fmod::DspNormalizeFadeTime, 0f32, 20000f32;
+    /// Lower volume range threshold to ignore. 0.0 to 1.0. Default = 0.1.
+    set_threshold, get_threshold, fmod::DspNormalizeThreshold, 0f32, 1f32;
+    /// Maximum amplification allowed. 1.0 to 100000.0. Default = 20.0.
+    set_max_amp, get_max_amp, fmod::DspNormalizeMaxAmp, 1f32, 100000f32
+)
+
+builtin_dsp!(
+    /// Attenuates or amplifies a frequency range.
+    ParameqDsp, fmod::Parameq, create_parameq_DSP,
+    /// Center frequency in hz. 20.0 to 22000.0. Default = 8000.0.
+    set_center, get_center, fmod::DspTypeParameqCenter, 20f32, 22000f32;
+    /// Octave range around the center frequency. 0.2 to 5.0. Default = 1.0.
+    set_bandwidth, get_bandwidth, fmod::DspTypeParameqBandwidth, 0.2f32, 5f32;
+    /// 0.05 to 3.0. Default = 1.0.
+    set_gain, get_gain, fmod::DspTypeParameqGain, 0.05f32, 3f32
+)
+
+/// FFT window size of a [`PitchShiftDsp`](struct.PitchShiftDsp.html). Larger windows give a better quality but use more CPU time.
+#[deriving(Show, PartialEq, Clone)]
+pub enum PitchShiftFFTSize {
+    FFTSize256 = 256,
+    FFTSize512 = 512,
+    FFTSize1024 = 1024,
+    FFTSize2048 = 2048,
+    FFTSize4096 = 4096
+}
+
+builtin_dsp!(
+    /// Bends the pitch without changing the speed of playback.
+    PitchShiftDsp, fmod::PitchShift, create_pitch_shift_DSP,
+    /// 0.5 = one octave down to 2.0 = one octave up. Default = 1.0.
+    set_pitch, get_pitch, fmod::DspPitchShiftPitch, 0.5f32, 2f32;
+    /// Maximum channels supported. 0 to 16, 0 being the default output polyphony. Default = 0.
+    set_max_channels, get_max_channels, fmod::DspPitchShiftMaxChannels, 0f32, 16f32
+)
+
+impl PitchShiftDsp {
+    /// Default = 1024.
+    pub fn set_FFT_size(&self, size: PitchShiftFFTSize) -> Result<(), FmodError> {
+        self.dsp.set_parameter(fmod::DspPitchShiftFFTSize as i32, size as int as f32)
+    }
+
+    pub fn get_FFT_size(&self) -> Result<PitchShiftFFTSize, FmodError> {
+        match try!(get_parameter(&self.dsp, fmod::DspPitchShiftFFTSize as i32)) as int {
+            256 => Ok(FFTSize256),
+            512 => Ok(FFTSize512),
+            1024 => Ok(FFTSize1024),
+            2048 => Ok(FFTSize2048),
+            _ => Ok(FFTSize4096)
+        }
+    }
+}
+
+builtin_dsp!(
+    ChorusDsp, fmod::Chorus, create_chorus_DSP,
+    /// Volume of the original signal. 0.0 to 1.0. Default = 0.5.
+    set_dry_mix, get_dry_mix, fmod::DspChorusDryMix, 0f32, 1f32;
+    /// Volume of the 1st tap. 0.0 to 1.0. Default = 0.5.
+    set_wet_mix1, get_wet_mix1, fmod::DspChorusWetMix1, 0f32, 1f32;
+    /// Volume of the 2nd tap, 90 degrees out of phase of the first one. 0.0 to 1.0. Default = 0.5.
+    set_wet_mix2, get_wet_mix2, fmod::DspChorusWetMix2, 0f32, 1f32;
+    /// Volume of the 3rd tap, 90 degrees out of phase of the second one. 0.0 to 1.0. Default = 0.5.
+    set_wet_mix3, get_wet_mix3, fmod::DspChorusWetMix3, 0f32, 1f32;
+    /// Delay in ms. 0.1 to 100.0. Default = 40.0.
+    set_delay, get_delay, fmod::DspChorusDelay, 0.1f32, 100f32;
+    /// Modulation rate in hz. 0.0 to 20.0. Default = 0.8.
+    set_rate, get_rate, fmod::DspChorusRate, 0f32, 20f32;
+    /// Modulation depth. 0.0 to 1.0. Default = 0.03.
+    set_depth, get_depth, fmod::DspChorusDepth, 0f32, 1f32
+)
+
+builtin_dsp!(
+    /// Echo used in Impulse Tracker, emulating the DirectX DMO echo.
+    ITEchoDsp, fmod::ITEcho, create_IT_echo_DSP,
+    /// Percentage of wet signal. 0.0 to 100.0. Default = 50.0.
+    set_wet_dry_mix, get_wet_dry_mix, fmod::DspITEchoWetDryMix, 0f32, 100f32;
+    /// Percentage of output fed back into input. 0.0 to 100.0. Default = 50.0.
+    set_feedback, get_feedback, fmod::DspITEchoFeedBack, 0f32, 100f32;
+    /// Delay of the left channel in ms. 1.0 to 2000.0. Default = 500.0.
+    set_left_delay, get_left_delay, fmod::DspITEchoLeftDelay, 1f32, 2000f32;
+    /// Delay of the right channel in ms. 1.0 to 2000.0. Default = 500.0.
+    set_right_delay, get_right_delay, fmod::DspITEchoRightDelay, 1f32, 2000f32
+)
+
+builtin_dsp!(
+    /// Linked multichannel limiter, uniform across the whole spectrum.
+    CompressorDsp, fmod::Compressor, create_compressor_DSP,
+    /// Threshold level in dB. -60.0 to 0.0. Default = 0.0.
+    set_threshold, get_threshold, fmod::DspCompressorThreshold, -60f32, 0f32;
+    /// Gain reduction attack time in ms. 10.0 to 200.0. Default = 50.0.
+    set_attack, get_attack, fmod::DspCompressorAttack, 10f32, 200f32;
+    /// Gain reduction release time in ms. 20.0 to 1000.0. Default = 50.0.
+    set_release, get_release, fmod::DspCompressorRelease, 20f32, 1000f32;
+    /// Make-up gain in dB applied after limiting. 0.0 to 30.0. Default = 0.0.
+    set_gain_makeup, get_gain_makeup, fmod::DspCompressorGainMakeup, 0f32, 30f32
+)
+
+builtin_dsp!(
+    /// I3DL2 reverb.
+    SfxReverbDsp, fmod::SFXReverb, create_SFX_reverb_DSP,
+    /// Mix level of dry signal in mB. -10000.0 to 0.0. Default = 0.0.
+    set_dry_level, get_dry_level, fmod::DspSfxReverbDryLevel, -10000f32, 0f32;
+    /// Room effect level at low frequencies in mB. -10000.0 to 0.0. Default = -10000.0.
+    set_room, get_room, fmod::DspSfxReverbRoom, -10000f32, 0f32;
+    /// Room effect high-frequency level re. low frequency level in mB. -10000.0 to 0.0. Default = 0.0.
+    set_room_HF, get_room_HF, fmod::DspSfxReverbRoomHF, -10000f32, 0f32;
+    /// Decay time at low frequencies in seconds. 0.1 to 20.0. Default = 1.0.
+    set_decay_time, get_decay_time, fmod::DspSfxReverbDecayTime, 0.1f32, 20f32;
+    /// High-frequency to low-frequency decay time ratio. 0.1 to 2.0. Default = 0.5.
+    set_decay_HF_ratio, get_decay_HF_ratio, fmod::DspSfxReverbDecayHFRatio, 0.1f32, 2f32;
+    /// Early reflections level relative to room effect in mB. -10000.0 to 1000.0. Default = -10000.0.
+    set_reflections_level, get_reflections_level, fmod::DspSfxReverbReflectionsLevel, -10000f32, 1000f32;
+    /// Delay of the first reflection in seconds. 0.0 to 0.3. Default = 0.02.
+    set_reflections_delay, get_reflections_delay, fmod::DspSfxReverbReflectionsDelay, 0f32, 0.3f32;
+    /// Late reverberation level relative to room effect in mB. -10000.0 to 2000.0. Default = 0.0.
+    set_reverb_level, get_reverb_level, fmod::DspSfxReverbReverbLevel, -10000f32, 2000f32;
+    /// Late reverberation delay relative to the first reflection in seconds. 0.0 to 0.1. Default = 0.04.
+    set_reverb_delay, get_reverb_delay, fmod::DspSfxReverbReverbDelay, 0f32, 0.1f32;
+    /// Echo density in percent. 0.0 to 100.0. Default = 100.0.
+    set_diffusion, get_diffusion, fmod::DspSfxReverbDiffusion, 0f32, 100f32;
+    /// Modal density in percent. 0.0 to 100.0. Default = 100.0.
+    set_density, get_density, fmod::DspSfxReverbDensity, 0f32, 100f32;
+    /// Reference high frequency in hz. 20.0 to 20000.0. Default = 5000.0.
+    set_HF_reference, get_HF_reference, fmod::DspSfxReverbHFReference, 20f32, 20000f32;
+    /// Room effect low-frequency level in mB. -10000.0 to 0.0. Default = 0.0.
+    set_room_LF, get_room_LF, fmod::DspSfxReverbRoomLF, -10000f32, 0f32;
+    /// Reference low frequency in hz. 20.0 to 1000.0. Default = 250.0.
+    set_LF_reference, get_LF_reference, fmod::DspSfxReverbLFReference, 20f32, 1000f32
+)
+
+builtin_dsp!(
+    /// Fast lowpass filter with no resonance.
+    LowPassSimpleDsp, fmod::LowPassSimple, create_low_pass_simple_DSP,
+    /// Cutoff frequency in hz. 10.0 to 22000.0. Default = 5000.0.
+    set_cutoff, get_cutoff, fmod::DspLowPassSimpleCutoff, 10f32, 22000f32
+)
+
+builtin_dsp!(
+    /// Fast highpass filter with no resonance.
+    HighPassSimpleDsp, fmod::HighPassSimple, create_high_pass_simple_DSP,
+    /// Cutoff frequency in hz. 10.0 to 22000.0. Default = 1000.0.
+    set_cutoff, get_cutoff, fmod::DspHighPassSimpleCutoff, 10f32, 22000f32
+)
@@ -0,0 +1,254 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use enums::*;
+use dsp;
+use dsp::Dsp;
+use fmod_sys;
+use fmod_sys::FmodSys;
+use error::FmodError;
+use std::fmt;
+use std::cmp::min;
+use std::collections::hashmap::HashMap;
+
+static SPEAKERS : [fmod::Speaker, ..8] = [fmod::SpeakerFrontLeft, fmod::SpeakerFrontRight, fmod::SpeakerFrontCenter, fmod::SpeakerLowFrequency,
+    fmod::SpeakerBackLeft, fmod::SpeakerBackRight, fmod::SpeakerSideLeft, fmod::SpeakerSideRight];
+
+/// DSP unit of a [`DspGraph`](struct.DspGraph.html).
+#[deriving(Show, Clone)]
+pub struct DspNode {
+    pub dsp_type: fmod::DspType,
+    pub name: String,
+    pub active: bool,
+    pub bypass: bool
+}
+
+/// Connection of a [`DspGraph`](struct.DspGraph.html), the signal going from `input` to `output`.
+#[deriving(Show, Clone)]
+pub struct DspEdge {
+    /// Index of the unit reading the signal in [`DspGraph::nodes`](struct.DspGraph.html#structfield.nodes).
+    pub output: uint,
+    /// Index of the unit producing the signal.
+    pub input: uint,
+    pub mix: f32,
+    /// Levels of each input channel for the speakers of the output, empty if FMOD didn't give them.
+    pub levels: Vec<(fmod::Speaker, Vec<f32>)>
+}
+
+/// Snapshot of a DSP network, taken with [`DspGraph::new`](struct.DspGraph.html#method.new) or [`FmodSys::get_DSP_graph`](struct.FmodSys.html#method.get_DSP_graph).
+///
+/// A unit with several outputs only appears once. Printing the graph shows it as a tree, its DOT export can be rendered with Graphviz.
+#[deriving(Clone)]
+pub struct DspGraph {
+    /// The units, the first one being the root.
+    pub nodes: Vec<DspNode>,
+    pub edges: Vec<DspEdge>
+}
+
+fn get_node(dsp: &Dsp) -> Result<DspNode, FmodError> {
+    let (name, _, _, _, _) = try!(dsp.get_info());
+
+    Ok(DspNode {
+        dsp_type: try!(dsp.get_type()),
+        name: name,
+        active: try!(dsp.get_active()),
+        bypass: try!(dsp.get_bypass())
+    })
+}
+
+fn escape(s: &str) -> String {
+    s.replace("\\", "\\\\").replace("\"", "\\\"")
+}
+
+impl DspGraph {
+    /// Walks the network feeding `root`.
+    pub fn new(root: &Dsp) -> Result<DspGraph, FmodError> {
+        let format = try!(try!(root.get_system_object()).get_software_format());
+        let speakers = SPEAKERS.slice_to(min(format.num_output_channels as uint, SPEAKERS.len()));
+        let mut graph = DspGraph{nodes: vec!(try!(get_node(root))), edges: Vec::new()};
+        let mut indexes = HashMap::new();
+        let mut to_visit = vec!((dsp::from_ptr(dsp::get_ffi(root), fmod_sys::SystemRef::none()), 0u));
+
+        indexes.insert(dsp::get_ffi(root) as uint, 0u);
+        loop {
+            let (output, output_index) = match to_visit.pop() {
+                Some(v) => v,
+                None => break
+            };
+
+            for i in range(0, try!(output.get_num_inputs())) {
+                let (input, connection) = try!(output.get_input(i));
+                let input_index = match indexes.find_copy(&(dsp::get_ffi(&input) as uint)) {
+                    Some(index) => index,
+                    None => {
+                        let index = graph.nodes.len();
+
+                        graph.nodes.push(try!(get_node(&input)));
+                        indexes.insert(dsp::get_ffi(&input) as uint, index);
+                        to_visit.push((input, index));
+                        index
+                    }
+                };
+                let mut levels = Vec::new();
+
+                for speaker in speakers.iter() {
+                    match connection.get_levels(*speaker, format.max_input_channels as uint) {
+                        Ok(l) => levels.push((*speaker, l)),
+                        Err(_) => {}
+                    }
+                }
+                graph.edges.push(DspEdge {
+                    output: output_index,
+                    input: input_index,
+                    mix: try!(connection.get_mix()),
+                    levels: levels
+                });
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Exports the graph in the Graphviz DOT format, the signal flowing from the inputs to the root.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from_str("digraph dsp {\n");
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let style = if !node.active {
+                ", style=dashed"
+            } else if node.bypass {
+                ", style=dotted"
+            } else {
+                ""
+            };
+
+            dot.push_str(format!("    n{} [label=\"{}\\n{}{}\"{}];\n", index, escape(node.name.as_slice()), node.dsp_type,
+                if node.bypass { " (bypassed)" } else { "" }, style).as_slice());
+        }
+        for edge in self.edges.iter() {
+            dot.push_str(format!("    n{} -> n{} [label=\"{:.2}\"];\n", edge.input, edge.output, edge.mix).as_slice());
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn fmt_node(&self, f: &mut fmt::Formatter, index: uint, depth: uint, mix: Option<f32>, printed: &mut Vec<bool>) -> fmt::Result {
+        let node = &self.nodes[index];
+
+        for _ in range(0, depth) {
+            try!(write!(f, "  "));
+        }
+        match mix {
+            Some(m) => try!(write!(f, "<- {:.2} ", m)),
+            None => {}
+        }
+        try!(write!(f, "{} ({}){}{}", node.name, node.dsp_type, if node.active { "" } else { " inactive" },
+            if node.bypass { " bypassed" } else { "" }));
+        if printed[index] {
+            return writeln!(f, " ...");
+        }
+        try!(writeln!(f, ""));
+        *printed.get_mut(index) = true;
+        for edge in self.edges.iter().filter(|e| e.output == index) {
+            try!(self.fmt_node(f, edge.input, depth + 1, Some(edge.mix), printed));
+        }
+        Ok(())
+    }
+}
+
+/* One unit per line, indented under the unit it feeds after the mix of the connection. Units already printed are followed by "...". */
+impl fmt::Show for DspGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.nodes.is_empty() {
+            return Ok(());
+        }
+        let mut printed = Vec::from_elem(self.nodes.len(), false);
+
+        self.fmt_node(f, 0, 0, None, &mut printed)
+    }
+}
+
+impl FmodSys {
+    /// Snapshot of the whole DSP network, from [`FmodSys::get_DSP_head`](struct.FmodSys.html#method.get_DSP_head).
+    pub fn get_DSP_graph(&self) -> Result<DspGraph, FmodError> {
+        DspGraph::new(&try!(self.get_DSP_head()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use enums::*;
+    use super::{DspGraph, DspNode, DspEdge};
+
+    fn node(dsp_type: fmod::DspType, name: &str) -> DspNode {
+        DspNode{dsp_type: dsp_type, name: name.to_string(), active: true, bypass: false}
+    }
+
+    fn edge(input: uint, output: uint, mix: f32) -> DspEdge {
+        DspEdge{output: output, input: input, mix: mix, levels: Vec::new()}
+    }
+
+    /* The tone feeds both the head and the mix. */
+    fn shared_input_graph() -> DspGraph {
+        DspGraph {
+            nodes: vec!(node(fmod::Mixer, "Head"), node(fmod::Mixer, "Mix"), node(fmod::Oscillator, "Tone")),
+            edges: vec!(edge(1, 0, 1f32), edge(2, 1, 0.5f32), edge(2, 0, 0.25f32))
+        }
+    }
+
+    #[test]
+    fn to_dot_escapes_names() {
+        let graph = DspGraph{nodes: vec!(node(fmod::Echo, "say \"hi\" \\o/")), edges: Vec::new()};
+
+        assert_eq!(graph.to_dot(), "digraph dsp {\n    n0 [label=\"say \\\"hi\\\" \\\\o/\\nEcho\"];\n}\n".to_string());
+    }
+
+    #[test]
+    fn to_dot_lists_nodes_and_edges() {
+        let mut graph = shared_input_graph();
+
+        graph.nodes.get_mut(1).bypass = true;
+        graph.nodes.get_mut(2).active = false;
+        assert_eq!(graph.to_dot(), "digraph dsp {\n\
+            \x20   n0 [label=\"Head\\nMixer\"];\n\
+            \x20   n1 [label=\"Mix\\nMixer (bypassed)\", style=dotted];\n\
+            \x20   n2 [label=\"Tone\\nOscillator\", style=dashed];\n\
+            \x20   n1 -> n0 [label=\"1.00\"];\n\
+            \x20   n2 -> n1 [label=\"0.50\"];\n\
+            \x20   n2 -> n0 [label=\"0.25\"];\n\
+            }\n".to_string());
+    }
+
+    #[test]
+    fn show_prints_shared_inputs_once() {
+        assert_eq!(format!("{}", shared_input_graph()), "Head (Mixer)\n\
+            \x20 <- 1.00 Mix (Mixer)\n\
+            \x20   <- 0.50 Tone (Oscillator)\n\
+            \x20 <- 0.25 Tone (Oscillator) ...\n".to_string());
+    }
+
+    #[test]
+    fn show_prints_nothing_for_an_empty_graph() {
+        assert_eq!(format!("{}", DspGraph{nodes: Vec::new(), edges: Vec::new()}), String::new());
+    }
+}
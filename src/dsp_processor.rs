@@ -0,0 +1,431 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use enums::*;
+use dsp::DspParameterDesc;
+use libc::{c_char, c_void, c_uint, c_int, c_float};
+use std::mem::transmute;
+use std::raw;
+use std::c_str::CString;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUint, AtomicBool, SeqCst};
+
+/// Custom effect added to the DSP network with [`FmodSys::create_DSP_from_processor`](struct.FmodSys.html#method.create_DSP_from_processor).
+///
+/// `process`, `reset` and `set_param` are called from the FMOD mixer thread. The values given to [`Dsp::set_parameter`](struct.Dsp.html#method.set_parameter)
+/// are stored without waiting for the mixer, and given to `set_param` just before the next `process`.
+pub trait DspProcessor: Send {
+    /// Name of the unit displayed in the network, truncated to 31 bytes.
+    fn get_name(&self) -> String {
+        "DspProcessor".to_string()
+    }
+
+    /// Parameters of the unit, read once when the DSP is created. FMOD checks the indexes and ranges given to
+    /// [`Dsp::set_parameter`](struct.Dsp.html#method.set_parameter) against them.
+    fn get_parameters(&self) -> Vec<DspParameterDesc> {
+        Vec::new()
+    }
+
+    /// Writes `frames` frames of `out_channels` samples in `output` from `frames` frames of `in_channels` samples in `input`. Samples are interleaved.
+    fn process(&mut self, input: &[f32], output: &mut [f32], frames: uint, in_channels: uint, out_channels: uint);
+
+    /// Clears the history buffers of the effect, before the next `process`. Does nothing by default.
+    fn reset(&mut self) {
+    }
+
+    /// Applies a parameter change. `index` and `value` have already been checked against the parameters given by `get_parameters`,
+    /// and [`Dsp::set_parameter`](struct.Dsp.html#method.set_parameter) has already succeeded, so the change can't be refused.
+    #[allow(unused_variable)]
+    fn set_param(&mut self, index: i32, value: f32) {
+    }
+
+    /// Initial value of a parameter, read once when the DSP is created. The default value of the parameter is used if it fails.
+    #[allow(unused_variable)]
+    fn get_param(&self, index: i32) -> Result<f32, fmod::Result> {
+        Err(fmod::ErrInvalidParam)
+    }
+
+    /// Text given with the value of a parameter by [`Dsp::get_parameter`](struct.Dsp.html#method.get_parameter), truncated to 15 bytes.
+    ///
+    /// It isn't called while the mixer runs `process`, the value is then shown with two decimals instead. While it runs, the mixer
+    /// lets the signal through the unit unchanged.
+    #[allow(unused_variable)]
+    fn format_param(&self, index: i32, value: f32) -> String {
+        format_value(value)
+    }
+}
+
+fn format_value(value: f32) -> String {
+    format!("{:.2}", value)
+}
+
+/// Parameter of a [`DspEffect`](trait.DspEffect.html), read and written through the `get` and `set` functions.
+pub struct DspParam<T> {
+    name: String,
+    label: String,
+    description: String,
+    min: f32,
+    max: f32,
+    default_val: f32,
+    get: fn(&T) -> f32,
+    set: fn(&mut T, f32),
+    format: fn(f32) -> String
+}
+
+impl<T> DspParam<T> {
+    pub fn new(name: &str, min: f32, max: f32, default_val: f32, get: fn(&T) -> f32, set: fn(&mut T, f32)) -> DspParam<T> {
+        DspParam {
+            name: name.to_string(),
+            label: String::new(),
+            description: String::new(),
+            min: min,
+            max: max,
+            default_val: default_val,
+            get: get,
+            set: set,
+            format: format_value
+        }
+    }
+
+    /// Unit displayed next to the value, like "hz".
+    pub fn label(mut self, label: &str) -> DspParam<T> {
+        self.label = label.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> DspParam<T> {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Replaces the default formatting of the value, which keeps two decimals.
+    pub fn format(mut self, format: fn(f32) -> String) -> DspParam<T> {
+        self.format = format;
+        self
+    }
+
+    fn get_desc(&self) -> DspParameterDesc {
+        DspParameterDesc {
+            min: self.min,
+            max: self.max,
+            default_val: self.default_val,
+            name: self.name.clone(),
+            label: self.label.clone(),
+            description: self.description.clone()
+        }
+    }
+}
+
+/// Custom effect declaring its parameters once, created with [`FmodSys::create_DSP_from_effect`](struct.FmodSys.html#method.create_DSP_from_effect).
+///
+/// The calls to [`Dsp::set_parameter`](struct.Dsp.html#method.set_parameter) and [`Dsp::get_parameter`](struct.Dsp.html#method.get_parameter)
+/// are checked against the schema and dispatched to the matching parameter, and each parameter is set to its default value when the DSP is created.
+pub trait DspEffect: Send {
+    fn get_name(&self) -> String {
+        "DspEffect".to_string()
+    }
+
+    /// Parameters of the effect, their index being their position in the returned vector.
+    fn get_schema(&self) -> Vec<DspParam<Self>>;
+
+    /// Same as [`DspProcessor::process`](trait.DspProcessor.html#tymethod.process).
+    fn process(&mut self, input: &[f32], output: &mut [f32], frames: uint, in_channels: uint, out_channels: uint);
+
+    fn reset(&mut self) {
+    }
+}
+
+/* Runs a DspEffect through its schema. */
+struct EffectProcessor<T> {
+    effect: T,
+    schema: Vec<DspParam<T>>
+}
+
+pub fn from_effect<T: DspEffect>(mut effect: T) -> Box<DspProcessor + Send> {
+    let schema = effect.get_schema();
+
+    for param in schema.iter() {
+        (param.set)(&mut effect, param.default_val);
+    }
+    box EffectProcessor{effect: effect, schema: schema} as Box<DspProcessor + Send>
+}
+
+impl<T: DspEffect> DspProcessor for EffectProcessor<T> {
+    fn get_name(&self) -> String {
+        self.effect.get_name()
+    }
+
+    fn get_parameters(&self) -> Vec<DspParameterDesc> {
+        self.schema.iter().map(|p| p.get_desc()).collect()
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut [f32], frames: uint, in_channels: uint, out_channels: uint) {
+        self.effect.process(input, output, frames, in_channels, out_channels)
+    }
+
+    fn reset(&mut self) {
+        self.effect.reset()
+    }
+
+    fn set_param(&mut self, index: i32, value: f32) {
+        if index >= 0 && (index as uint) < self.schema.len() {
+            (self.schema[index as uint].set)(&mut self.effect, value);
+        }
+    }
+
+    fn get_param(&self, index: i32) -> Result<f32, fmod::Result> {
+        if index < 0 || index as uint >= self.schema.len() {
+            Err(fmod::ErrInvalidParam)
+        } else {
+            Ok((self.schema[index as uint].get)(&self.effect))
+        }
+    }
+
+    fn format_param(&self, index: i32, value: f32) -> String {
+        if index < 0 || index as uint >= self.schema.len() {
+            format_value(value)
+        } else {
+            (self.schema[index as uint].format)(value)
+        }
+    }
+}
+
+fn to_bits(value: f32) -> uint {
+    unsafe { transmute::<f32, u32>(value) as uint }
+}
+
+fn from_bits(bits: uint) -> f32 {
+    unsafe { transmute::<u32, f32>(bits as u32) }
+}
+
+/* Pointed by the user data of the DSPs created by FmodSys::create_DSP_from_processor.
+ *
+ * Nobody waits for the processor: the parameters and the reset requests are passed through atomics, the mixer thread lets
+ * the signal through if another thread is formatting a value, and the other threads only try to lock it to format one. */
+pub struct ProcessorData {
+    processor: Mutex<Box<DspProcessor + Send>>,
+    /* last value given to each parameter, as f32 bits, and whether the processor got it */
+    values: Vec<AtomicUint>,
+    changed: Vec<AtomicBool>,
+    reset: AtomicBool,
+    name: String,
+    params: Vec<ffi::FMOD_DSP_PARAMETERDESC>,
+    /* the descriptions pointed by params */
+    descriptions: Vec<CString>
+}
+
+impl ProcessorData {
+    pub fn new(processor: Box<DspProcessor + Send>) -> ProcessorData {
+        let name = processor.get_name();
+        let parameters = processor.get_parameters();
+        let descriptions : Vec<CString> = parameters.iter().map(|p| p.description.to_c_str()).collect();
+        let params = parameters.iter().zip(descriptions.iter()).map(|(p, description)| {
+            let mut param = ffi::FMOD_DSP_PARAMETERDESC {
+                min: p.min,
+                max: p.max,
+                default_val: p.default_val,
+                name: [0, ..16],
+                label: [0, ..16],
+                description: description.as_ptr()
+            };
+
+            copy_str(&mut param.name, p.name.as_slice());
+            copy_str(&mut param.label, p.label.as_slice());
+            param
+        }).collect();
+        let values = parameters.iter().enumerate().map(|(index, p)| {
+            AtomicUint::new(to_bits(match processor.get_param(index as i32) {
+                Ok(v) => v,
+                Err(_) => p.default_val
+            }))
+        }).collect();
+
+        ProcessorData {
+            processor: Mutex::new(processor),
+            values: values,
+            changed: parameters.iter().map(|_| AtomicBool::new(false)).collect(),
+            reset: AtomicBool::new(false),
+            name: name,
+            params: params,
+            descriptions: descriptions
+        }
+    }
+}
+
+/* Copies as much of `s` as possible in `dst` and fills the rest with nul bytes, keeping at least one. */
+fn copy_str(dst: &mut [c_char], s: &str) {
+    let bytes = s.as_bytes();
+    let len = dst.len();
+
+    for (i, d) in dst.mut_iter().enumerate() {
+        *d = if i + 1 < len && i < bytes.len() {
+            bytes[i] as c_char
+        } else {
+            0
+        };
+    }
+}
+
+/// Builds the description given to FMOD, `data` has to live as long as the DSP.
+pub fn get_description_ffi(data: &mut ProcessorData) -> ffi::FMOD_DSP_DESCRIPTION {
+    let mut description = ffi::FMOD_DSP_DESCRIPTION {
+        name: [0, ..32],
+        version: 0,
+        channels: 0,
+        create: None,
+        release: None,
+        reset: Some(reset_callback),
+        read: Some(read_callback),
+        set_position: None,
+        num_parameters: data.params.len() as c_int,
+        param_desc: data.params.as_mut_ptr(),
+        set_parameter: Some(set_parameter_callback),
+        get_parameter: Some(get_parameter_callback),
+        config: None,
+        config_width: 0,
+        config_height: 0,
+        user_data: data as *mut ProcessorData as *mut c_void
+    };
+
+    copy_str(&mut description.name, data.name.as_slice());
+    description
+}
+
+unsafe fn get_data<'r>(dsp_state: *mut ffi::FMOD_DSP_STATE) -> Option<&'r ProcessorData> {
+    if dsp_state.is_null() || (*dsp_state).instance.is_null() {
+        return None;
+    }
+    let mut tmp = ::std::ptr::mut_null();
+
+    ffi::FMOD_DSP_GetUserData((*dsp_state).instance, &mut tmp);
+    if tmp.is_null() {
+        None
+    } else {
+        Some(transmute(tmp as *const c_void))
+    }
+}
+
+extern "C" fn reset_callback(dsp_state: *mut ffi::FMOD_DSP_STATE) -> fmod::Result {
+    unsafe {
+        match get_data(dsp_state) {
+            Some(data) => {
+                data.reset.store(true, SeqCst);
+                fmod::Ok
+            }
+            None => fmod::Ok
+        }
+    }
+}
+
+fn pass_through(input: &[f32], output: &mut [f32], frames: uint, in_channels: uint, out_channels: uint) {
+    for frame in range(0u, frames) {
+        for channel in range(0u, out_channels) {
+            output[frame * out_channels + channel] = if channel < in_channels {
+                input[frame * in_channels + channel]
+            } else {
+                0f32
+            };
+        }
+    }
+}
+
+extern "C" fn read_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, in_buffer: *mut c_float, out_buffer: *mut c_float, length: c_uint,
+    in_channels: c_int, out_channels: c_int) -> fmod::Result {
+    unsafe {
+        match get_data(dsp_state) {
+            Some(data) => {
+                let frames = length as uint;
+                let input : &[f32] = transmute(raw::Slice{data: in_buffer as *const f32, len: frames * in_channels as uint});
+                let output : &mut [f32] = transmute(raw::Slice{data: out_buffer as *const f32, len: frames * out_channels as uint});
+                let mut processor = match data.processor.try_lock() {
+                    Some(p) => p,
+                    None => {
+                        // the changes are applied on the next block
+                        pass_through(input, output, frames, in_channels as uint, out_channels as uint);
+                        return fmod::Ok;
+                    }
+                };
+
+                if data.reset.swap(false, SeqCst) {
+                    processor.reset();
+                }
+                for (index, changed) in data.changed.iter().enumerate() {
+                    if changed.swap(false, SeqCst) {
+                        processor.set_param(index as i32, from_bits(data.values[index].load(SeqCst)));
+                    }
+                }
+                processor.process(input, output, frames, in_channels as uint, out_channels as uint);
+                fmod::Ok
+            }
+            None => fmod::Ok
+        }
+    }
+}
+
+extern "C" fn set_parameter_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, index: c_int, value: c_float) -> fmod::Result {
+    unsafe {
+        match get_data(dsp_state) {
+            Some(data) if (index as uint) < data.values.len() => {
+                let param = &data.params[index as uint];
+
+                // checked here as the processor only gets the value on the mixer thread, and can't refuse it
+                if value.is_nan() || value < param.min || value > param.max {
+                    return fmod::ErrInvalidParam;
+                }
+                data.values[index as uint].store(to_bits(value), SeqCst);
+                data.changed[index as uint].store(true, SeqCst);
+                fmod::Ok
+            }
+            Some(_) => fmod::ErrInvalidParam,
+            None => fmod::Ok
+        }
+    }
+}
+
+extern "C" fn get_parameter_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, index: c_int, value: *mut c_float, value_str: *mut c_char) -> fmod::Result {
+    unsafe {
+        match get_data(dsp_state) {
+            Some(data) if (index as uint) < data.values.len() => {
+                let v = from_bits(data.values[index as uint].load(SeqCst));
+
+                if value.is_not_null() {
+                    *value = v;
+                }
+                if value_str.is_not_null() {
+                    let text = match data.processor.try_lock() {
+                        Some(processor) => processor.format_param(index as i32, v),
+                        None => format_value(v)
+                    };
+                    let buffer : &mut [c_char] = transmute(raw::Slice{data: value_str as *const c_char, len: 16u});
+
+                    copy_str(buffer, text.as_slice());
+                }
+                fmod::Ok
+            }
+            Some(_) => fmod::ErrInvalidParam,
+            None => fmod::Ok
+        }
+    }
+}
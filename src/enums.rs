@@ -23,10 +23,10 @@
 */
 
 use libc::{c_void, c_uint, c_int, c_char, c_float};
-use types::{FmodTimeUnit, FmodMemoryBits};
+use types::{FmodMode, FmodInitFlag, FmodTimeUnit, FmodCaps, FmodMemoryBits};
 
 pub mod fmod {
-    #[deriving(PartialEq, PartialOrd, Show)]
+    #[deriving(PartialEq, PartialOrd, Show, Clone)]
     #[repr(C)]
     /// Error codes. Returned from every function.
     pub enum Result
@@ -684,6 +684,25 @@ pub mod fmod {
         SystemCallbackTypeForceInt = 65536
     }
 
+    #[deriving(PartialEq, PartialOrd, Show)]
+    #[repr(C)]
+    pub enum ChannelCallbackType
+    {
+        /// Called when a sound ends.
+        ChannelCallbackTypeEnd,
+        /// Called when a voice is swapped out or swapped in.
+        ChannelCallbackTypeVirtualVoice,
+        /// Called when a syncpoint is encountered. Can be from wav file markers.
+        ChannelCallbackTypeSyncPoint,
+        /// Called when the channel has its geometry occlusion value calculated. Can be used to clamp or change the value.
+        ChannelCallbackTypeOcclusion,
+
+        /// Maximum number of callback types supported.
+        ChannelCallbackTypeMax,
+        /// Makes sure this enum is signed 32bit.
+        ChannelCallbackTypeForceInt = 65536
+    }
+
     #[deriving(PartialEq, PartialOrd, Show)]
     #[repr(C)]
     /// These flags are used with [`SoundGroup::set_max_audible_behavior`](../../struct.SoundGroup.html#method.set_max_audible_behavior) to determine what happens when more sounds are played than are specified with [`SoundGroup::set_max_audible`](../../struct.SoundGroup.html#method.set_max_audible).
@@ -1091,113 +1110,135 @@ pub mod fmod {
 }
 
 /// Default for all modes listed below. FMOD_LOOP_OFF, FMOD_2D, FMOD_HARDWARE
-pub static FMOD_DEFAULT                : c_uint = 0x00000000;
+pub static FMOD_DEFAULT                : FmodMode = FmodMode(0x00000000);
 /// For non looping sounds. (DEFAULT). Overrides FMOD_LOOP_NORMAL / FMOD_LOOP_BIDI.
-pub static FMOD_LOOP_OFF               : c_uint = 0x00000001;
+pub static FMOD_LOOP_OFF               : FmodMode = FmodMode(0x00000001);
 /// For forward looping sounds.
-pub static FMOD_LOOP_NORMAL            : c_uint = 0x00000002;
+pub static FMOD_LOOP_NORMAL            : FmodMode = FmodMode(0x00000002);
 /// For bidirectional looping sounds. (only works on software mixed static sounds).
-pub static FMOD_LOOP_BIDI              : c_uint = 0x00000004;
+pub static FMOD_LOOP_BIDI              : FmodMode = FmodMode(0x00000004);
 /// Ignores any 3d processing. (DEFAULT).
-pub static FMOD_2D                     : c_uint = 0x00000008;
+pub static FMOD_2D                     : FmodMode = FmodMode(0x00000008);
 /// Makes the sound positionable in 3D. Overrides FMOD_2D
-pub static FMOD_3D                     : c_uint = 0x00000010;
+pub static FMOD_3D                     : FmodMode = FmodMode(0x00000010);
 /// Attempts to make sounds use hardware acceleration. (DEFAULT). Note on platforms that don't support FMOD_HARDWARE (only 3DS, PS Vita, PSP, Wii and Wii U support FMOD_HARDWARE), this will be internally treated as FMOD_SOFTWARE.
-pub static FMOD_HARDWARE               : c_uint = 0x00000020;
+pub static FMOD_HARDWARE               : FmodMode = FmodMode(0x00000020);
 /// Makes the sound be mixed by the FMOD CPU based software mixer. Overrides FMOD_HARDWARE. Use this for FFT, DSP, compressed sample support, 2D multi-speaker support and other software related features.
-pub static FMOD_SOFTWARE               : c_uint = 0x00000040;
+pub static FMOD_SOFTWARE               : FmodMode = FmodMode(0x00000040);
 /// Decompress at runtime, streaming from the source provided (ie from disk). Overrides FMOD_CREATESAMPLE and FMOD_CREATECOMPRESSEDSAMPLE. Note a stream can only be played once at a time due to a stream only having 1 stream buffer and file handle. Open multiple streams to have them play concurrently.
-pub static FMOD_CREATESTREAM           : c_uint = 0x00000080;
+pub static FMOD_CREATESTREAM           : FmodMode = FmodMode(0x00000080);
 /// Decompress at loadtime, decompressing or decoding whole file into memory as the target sample format (ie PCM). Fastest for FMOD_SOFTWARE based playback and most flexible.
-pub static FMOD_CREATESAMPLE           : c_uint = 0x00000100;
+pub static FMOD_CREATESAMPLE           : FmodMode = FmodMode(0x00000100);
 /// Load MP2/MP3/IMAADPCM/CELT/Vorbis/AT9 or XMA into memory and leave it compressed. CELT/Vorbis/AT9 encoding only supported in the FSB file format. During playback the FMOD software mixer will decode it in realtime as a 'compressed sample'. Can only be used in combination with FMOD_SOFTWARE. Overrides FMOD_CREATESAMPLE. If the sound data is not one of the supported formats, it will behave as if it was created with FMOD_CREATESAMPLE and decode the sound into PCM.
-pub static FMOD_CREATECOMPRESSEDSAMPLE : c_uint = 0x00000200;
+pub static FMOD_CREATECOMPRESSEDSAMPLE : FmodMode = FmodMode(0x00000200);
 /// Opens a user created static sample or stream. Use FMOD_CREATESOUNDEXINFO to specify format and/or read callbacks. If a user created 'sample' is created with no read callback, the sample will be empty. Use [`Sound::lock`](../struct.Sound.html#method.lock) and [`Sound::unlock`](../struct.Sound.html#method.unlock) to place sound data into the sound if this is the case.
-pub static FMOD_OPENUSER               : c_uint = 0x00000400;
+pub static FMOD_OPENUSER               : FmodMode = FmodMode(0x00000400);
 /// "name_or_data" will be interpreted as a pointer to memory instead of filename for creating sounds. Use FMOD_CREATESOUNDEXINFO to specify length. If used with FMOD_CREATESAMPLE or FMOD_CREATECOMPRESSEDSAMPLE, FMOD duplicates the memory into its own buffers. Your own buffer can be freed after open. If used with FMOD_CREATESTREAM, FMOD will stream out of the buffer whose pointer you passed in. In this case, your own buffer should not be freed until you have finished with and released the stream.
-pub static FMOD_OPENMEMORY             : c_uint = 0x00000800;
+pub static FMOD_OPENMEMORY             : FmodMode = FmodMode(0x00000800);
 /// "name_or_data" will be interpreted as a pointer to memory instead of filename for creating sounds. Use FMOD_CREATESOUNDEXINFO to specify length. This differs to FMOD_OPENMEMORY in that it uses the memory as is, without duplicating the memory into its own buffers. For Wii/PSP FMOD_HARDWARE supports this flag for the GCADPCM/VAG formats. On other platforms FMOD_SOFTWARE must be used, as sound hardware on the other platforms (ie PC) cannot access main ram. Cannot be freed after open, only after [`Sound::release`](../struct.Sound.html#method.release). Will not work if the data is compressed and FMOD_CREATECOMPRESSEDSAMPLE is not used.
-pub static FMOD_OPENMEMORY_POINT       : c_uint = 0x10000000;
+pub static FMOD_OPENMEMORY_POINT       : FmodMode = FmodMode(0x10000000);
 /// Will ignore file format and treat as raw pcm. Use FMOD_CREATESOUNDEXINFO to specify format. Requires at least defaultfrequency, numchannels and format to be specified before it will open. Must be little endian data.
-pub static FMOD_OPENRAW                : c_uint = 0x00001000;
+pub static FMOD_OPENRAW                : FmodMode = FmodMode(0x00001000);
 /// Just open the file, dont prebuffer or read. Good for fast opens for info, or when sound::readData is to be used.
-pub static FMOD_OPENONLY               : c_uint = 0x00002000;
+pub static FMOD_OPENONLY               : FmodMode = FmodMode(0x00002000);
 /// For [`FmodSys::create_sound`](../struct.FmodSys.html#method.create_sound) - for accurate [`Sound::get_length`](../struct.Sound.html#method.get_length) / [`Channel::set_position`](../struct.Channel.html#method.set_position) on VBR MP3, and MOD/S3M/XM/IT/MIDI files. Scans file first, so takes longer to open. FMOD_OPENONLY does not affect this.
-pub static FMOD_ACCURATETIME           : c_uint = 0x00004000;
+pub static FMOD_ACCURATETIME           : FmodMode = FmodMode(0x00004000);
 /// For corrupted / bad MP3 files. This will search all the way through the file until it hits a valid MPEG header. Normally only searches for 4k.
-pub static FMOD_MPEGSEARCH             : c_uint = 0x00008000;
+pub static FMOD_MPEGSEARCH             : FmodMode = FmodMode(0x00008000);
 /// For opening sounds and getting streamed subsounds (seeking) asyncronously. Use [`Sound::get_open_state`](../struct.Sound.html#method.get_open_state) to poll the state of the sound as it opens or retrieves the subsound in the background.
-pub static FMOD_NONBLOCKING            : c_uint = 0x00010000;
+pub static FMOD_NONBLOCKING            : FmodMode = FmodMode(0x00010000);
 /// Unique sound, can only be played one at a time
-pub static FMOD_UNIQUE                 : c_uint = 0x00020000;
+pub static FMOD_UNIQUE                 : FmodMode = FmodMode(0x00020000);
 /// Make the sound's position, velocity and orientation relative to the listener.
-pub static FMOD_3D_HEADRELATIVE        : c_uint = 0x00040000;
+pub static FMOD_3D_HEADRELATIVE        : FmodMode = FmodMode(0x00040000);
 /// Make the sound's position, velocity and orientation absolute (relative to the world). (DEFAULT)
-pub static FMOD_3D_WORLDRELATIVE       : c_uint = 0x00080000;
+pub static FMOD_3D_WORLDRELATIVE       : FmodMode = FmodMode(0x00080000);
 /// This sound will follow the inverse rolloff model where mindistance = full volume, maxdistance = where sound stops attenuating, and rolloff is fixed according to the global rolloff factor. (DEFAULT)
-pub static FMOD_3D_INVERSEROLLOFF      : c_uint = 0x00100000;
+pub static FMOD_3D_INVERSEROLLOFF      : FmodMode = FmodMode(0x00100000);
 /// This sound will follow a linear rolloff model where mindistance = full volume, maxdistance = silence. Rolloffscale is ignored.
-pub static FMOD_3D_LINEARROLLOFF       : c_uint = 0x00200000;
+pub static FMOD_3D_LINEARROLLOFF       : FmodMode = FmodMode(0x00200000);
 /// This sound will follow a linear-square rolloff model where mindistance = full volume, maxdistance = silence. Rolloffscale is ignored.
-pub static FMOD_3D_LINEARSQUAREROLLOFF : c_uint = 0x00400000;
+pub static FMOD_3D_LINEARSQUAREROLLOFF : FmodMode = FmodMode(0x00400000);
 /// This sound will follow a rolloff model defined by [`Sound::set_3D_custom_rolloff`](../struct.Sound.html#method.set_3D_custom_rolloff) / [`Channel::set_3D_custom_rolloff`](../struct.Channel.html#method.set_3D_custom_rolloff).
-pub static FMOD_3D_CUSTOMROLLOFF       : c_uint = 0x04000000;
+pub static FMOD_3D_CUSTOMROLLOFF       : FmodMode = FmodMode(0x04000000);
 /// Is not affect by geometry occlusion. If not specified in [`Sound::set_mode`](../struct.Sound.html#method.set_mode), or [`Channel::set_mode`](../struct.Channel.html#method.set_mode), the flag is cleared and it is affected by geometry again.
-pub static FMOD_3D_IGNOREGEOMETRY      : c_uint = 0x40000000;
+pub static FMOD_3D_IGNOREGEOMETRY      : FmodMode = FmodMode(0x40000000);
 /// Filename is double-byte unicode.
-pub static FMOD_UNICODE                : c_uint = 0x01000000;
+pub static FMOD_UNICODE                : FmodMode = FmodMode(0x01000000);
 /// Skips id3v2/asf/etc tag checks when opening a sound, to reduce seek/read overhead when opening files (helps with CD performance).
-pub static FMOD_IGNORETAGS             : c_uint = 0x02000000;
+pub static FMOD_IGNORETAGS             : FmodMode = FmodMode(0x02000000);
 /// Removes some features from samples to give a lower memory overhead, like [`Sound::get_name`](../struct.Sound.html#method.get_name). See remarks.
-pub static FMOD_LOWMEM                 : c_uint = 0x08000000;
+pub static FMOD_LOWMEM                 : FmodMode = FmodMode(0x08000000);
 /// Load sound into the secondary RAM of supported platform. On PS3, sounds will be loaded into RSX/VRAM.
-pub static FMOD_LOADSECONDARYRAM       : c_uint = 0x20000000;
+pub static FMOD_LOADSECONDARYRAM       : FmodMode = FmodMode(0x20000000);
 /// For sounds that start virtual (due to being quiet or low importance), instead of swapping back to audible, and playing at the correct offset according to time, this flag makes the sound play from the start.
-pub static FMOD_VIRTUAL_PLAYFROMSTART  : c_uint = 0x80000000;
+pub static FMOD_VIRTUAL_PLAYFROMSTART  : FmodMode = FmodMode(0x80000000);
 
 /// All platforms - Initialize normally
-pub static FMOD_INIT_NORMAL                    : c_uint = 0x00000000;
+pub static FMOD_INIT_NORMAL                    : FmodInitFlag = FmodInitFlag(0x00000000);
 /// All platforms - No stream thread is created internally. Streams are driven from [`FmodSys::update`](../struct.FmodSys.html#method.update). Mainly used with non-realtime outputs.
-pub static FMOD_INIT_STREAM_FROM_UPDATE        : c_uint = 0x00000001;
+pub static FMOD_INIT_STREAM_FROM_UPDATE        : FmodInitFlag = FmodInitFlag(0x00000001);
 /// All platforms - FMOD will treat +X as right, +Y as up and +Z as backwards (towards you).
-pub static FMOD_INIT_3D_RIGHTHANDED            : c_uint = 0x00000002;
+pub static FMOD_INIT_3D_RIGHTHANDED            : FmodInitFlag = FmodInitFlag(0x00000002);
 /// All platforms - Disable software mixer to save memory. Anything created with FMOD_SOFTWARE will fail and DSP will not work.
-pub static FMOD_INIT_SOFTWARE_DISABLE          : c_uint = 0x00000004;
+pub static FMOD_INIT_SOFTWARE_DISABLE          : FmodInitFlag = FmodInitFlag(0x00000004);
 /// All platforms - All FMOD_SOFTWARE (and FMOD_HARDWARE on 3DS and NGP) with FMOD_3D based voices will add a software lowpass filter effect into the DSP chain which is automatically used when [`Channel::set_3D_occlusion`](../struct.Channel.html#method.set_3D_occlusion) is used or the geometry API.
-pub static FMOD_INIT_OCCLUSION_LOWPASS         : c_uint = 0x00000008;
+pub static FMOD_INIT_OCCLUSION_LOWPASS         : FmodInitFlag = FmodInitFlag(0x00000008);
 /// All platforms - All FMOD_SOFTWARE (and FMOD_HARDWARE on 3DS and NGP) with FMOD_3D based voices will add a software lowpass filter effect into the DSP chain which causes sounds to sound duller when the sound goes behind the listener. Use [`FmodSys::set_advanced_settings`](../struct.FmodSys.html#method.set_advanced_settings) to adjust Cutoff frequency.
-pub static FMOD_INIT_HRTF_LOWPASS              : c_uint = 0x00000010;
+pub static FMOD_INIT_HRTF_LOWPASS              : FmodInitFlag = FmodInitFlag(0x00000010);
 /// All platforms - All FMOD_SOFTWARE with FMOD_3D based voices will add a software lowpass and highpass filter effect into the DSP chain which will act as a distance-automated bandpass filter. Use [`FmodSys::set_advanced_settings`](../struct.FmodSys.html#method.set_advanced_settings) to adjust the center frequency.
-pub static FMOD_INIT_DISTANCE_FILTERING        : c_uint = 0x00000200;
+pub static FMOD_INIT_DISTANCE_FILTERING        : FmodInitFlag = FmodInitFlag(0x00000200);
 /// All platforms - FMOD Software reverb will preallocate enough buffers for reverb per channel, rather than allocating them and freeing them at runtime.
-pub static FMOD_INIT_REVERB_PREALLOCBUFFERS    : c_uint = 0x00000040;
+pub static FMOD_INIT_REVERB_PREALLOCBUFFERS    : FmodInitFlag = FmodInitFlag(0x00000040);
 /// All platforms - Enable TCP/IP based host which allows FMOD Designer or FMOD Profiler to connect to it, and view memory, CPU and the DSP network graph in real-time.
-pub static FMOD_INIT_ENABLE_PROFILE            : c_uint = 0x00000020;
+pub static FMOD_INIT_ENABLE_PROFILE            : FmodInitFlag = FmodInitFlag(0x00000020);
 /// All platforms - Any sounds that are 0 volume will go virtual and not be processed except for having their positions updated virtually. Use [`FmodSys::set_advanced_settings`](../struct.FmodSys.html#method.set_advanced_settings) to adjust what volume besides zero to switch to virtual at.
-pub static FMOD_INIT_VOL0_BECOMES_VIRTUAL      : c_uint = 0x00000080;
+pub static FMOD_INIT_VOL0_BECOMES_VIRTUAL      : FmodInitFlag = FmodInitFlag(0x00000080);
 /// Win32 Vista only - for WASAPI output - Enable exclusive access to hardware, lower latency at the expense of excluding other applications from accessing the audio hardware.
-pub static FMOD_INIT_WASAPI_EXCLUSIVE          : c_uint = 0x00000100;
+pub static FMOD_INIT_WASAPI_EXCLUSIVE          : FmodInitFlag = FmodInitFlag(0x00000100);
 /// PS3 only - Prefer DTS over Dolby Digital if both are supported. Note: 8 and 6 channel LPCM is always preferred over both DTS and Dolby Digital.
-pub static FMOD_INIT_PS3_PREFERDTS             : c_uint = 0x00800000;
+pub static FMOD_INIT_PS3_PREFERDTS             : FmodInitFlag = FmodInitFlag(0x00800000);
 /// PS3 only - Force PS3 system output mode to 2 channel LPCM.
-pub static FMOD_INIT_PS3_FORCE2CHLPCM          : c_uint = 0x01000000;
+pub static FMOD_INIT_PS3_FORCE2CHLPCM          : FmodInitFlag = FmodInitFlag(0x01000000);
 /// Wii / 3DS - Disable Dolby Pro Logic surround. Speakermode will be set to STEREO even if user has selected surround in the system settings.
-pub static FMOD_INIT_DISABLEDOLBY              : c_uint = 0x00100000;
+pub static FMOD_INIT_DISABLEDOLBY              : FmodInitFlag = FmodInitFlag(0x00100000);
 /// Xbox 360 / PS3 - The "music" channelgroup which by default pauses when custom 360 dashboard / PS3 BGM music is played, can be changed to mute (therefore continues playing) instead of pausing, by using this flag.
-pub static FMOD_INIT_SYSTEM_MUSICMUTENOTPAUSE  : c_uint = 0x00200000;
+pub static FMOD_INIT_SYSTEM_MUSICMUTENOTPAUSE  : FmodInitFlag = FmodInitFlag(0x00200000);
 /// Win32/Wii/PS3/Xbox/Xbox 360 - FMOD Mixer thread is woken up to do a mix when [`FmodSys::update`](../struct.FmodSys.html#method.update) is called rather than waking periodically on its own timer.
-pub static FMOD_INIT_SYNCMIXERWITHUPDATE       : c_uint = 0x00400000;
+pub static FMOD_INIT_SYNCMIXERWITHUPDATE       : FmodInitFlag = FmodInitFlag(0x00400000);
 /// All platforms - With the geometry engine, only process the closest polygon rather than accumulating all polygons the sound to listener line intersects.
-pub static FMOD_INIT_GEOMETRY_USECLOSEST       : c_uint = 0x04000000;
+pub static FMOD_INIT_GEOMETRY_USECLOSEST       : FmodInitFlag = FmodInitFlag(0x04000000);
 /// Win32 - Disables automatic setting of of FMOD_SPEAKERMODE_STEREO to FMOD_SPEAKERMODE_MYEARS if the MyEars profile exists on the PC. MyEars is HRTF 7.1 downmixing through headphones.
-pub static FMOD_INIT_DISABLE_MYEARS_AUTODETECT : c_uint = 0x08000000;
+pub static FMOD_INIT_DISABLE_MYEARS_AUTODETECT : FmodInitFlag = FmodInitFlag(0x08000000);
 /// PS3 only - Disable DTS output mode selection
-pub static FMOD_INIT_PS3_DISABLEDTS            : c_uint = 0x10000000;
+pub static FMOD_INIT_PS3_DISABLEDTS            : FmodInitFlag = FmodInitFlag(0x10000000);
 /// PS3 only - Disable Dolby Digital output mode selection
-pub static FMOD_INIT_PS3_DISABLEDOLBYDIGITAL   : c_uint = 0x20000000;
+pub static FMOD_INIT_PS3_DISABLEDOLBYDIGITAL   : FmodInitFlag = FmodInitFlag(0x20000000);
 /// PS3/PS4 only - FMOD uses the WAVEFORMATEX Microsoft 7.1 speaker mapping where the last 2 pairs of speakers are 'rears' then 'sides', but on PS3/PS4 these are mapped to 'surrounds' and 'backs'. Use this flag to swap fmod's last 2 pair of speakers on PS3/PS4 to avoid needing to do a special case for these platforms.
-pub static FMOD_INIT_7POINT1_DOLBYMAPPING      : c_uint = 0x40000000;
+pub static FMOD_INIT_7POINT1_DOLBYMAPPING      : FmodInitFlag = FmodInitFlag(0x40000000);
 
+/// Device has no special capabilities.
+pub static FMOD_CAPS_NONE                   : FmodCaps = FmodCaps(0x00000000);
+/// Device supports hardware mixing.
+pub static FMOD_CAPS_HARDWARE               : FmodCaps = FmodCaps(0x00000001);
+/// User has device set to 'Hardware acceleration = off' in control panel, and now extra 200ms latency is incurred.
+pub static FMOD_CAPS_HARDWARE_EMULATED      : FmodCaps = FmodCaps(0x00000002);
+/// Device can do multichannel output, ie greater than 2 channels.
+pub static FMOD_CAPS_OUTPUT_MULTICHANNEL    : FmodCaps = FmodCaps(0x00000004);
+/// Device can output to 8bit integer PCM.
+pub static FMOD_CAPS_OUTPUT_FORMAT_PCM8     : FmodCaps = FmodCaps(0x00000008);
+/// Device can output to 16bit integer PCM.
+pub static FMOD_CAPS_OUTPUT_FORMAT_PCM16    : FmodCaps = FmodCaps(0x00000010);
+/// Device can output to 24bit integer PCM.
+pub static FMOD_CAPS_OUTPUT_FORMAT_PCM24    : FmodCaps = FmodCaps(0x00000020);
+/// Device can output to 32bit integer PCM.
+pub static FMOD_CAPS_OUTPUT_FORMAT_PCM32    : FmodCaps = FmodCaps(0x00000040);
+/// Device can output to 32bit floating point PCM.
+pub static FMOD_CAPS_OUTPUT_FORMAT_PCMFLOAT : FmodCaps = FmodCaps(0x00000080);
+/// Device supports some form of limited hardware reverb, maybe parameterless and only selectable by environment.
+pub static FMOD_CAPS_REVERB_LIMITED         : FmodCaps = FmodCaps(0x00002000);
+/// Device is a loopback recording device.
+pub static FMOD_CAPS_LOOPBACK               : FmodCaps = FmodCaps(0x00004000);
 /// Milliseconds.
 pub static FMOD_TIMEUNIT_MS               : FmodTimeUnit = FmodTimeUnit(0x00000001);
 /// PCM samples, related to milliseconds * samplerate / 1000.
@@ -0,0 +1,225 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use enums::*;
+use std::fmt;
+use std::error::Error;
+use std::io;
+use std::io::IoError;
+
+/// Converts the code returned by an FMOD function into a Result.
+pub fn check(code: fmod::Result) -> Result<(), FmodError> {
+    match code {
+        fmod::Ok => Ok(()),
+        e => Err(FmodError::new(e))
+    }
+}
+
+/// Category of an [`FmodError`](struct.FmodError.html).
+#[deriving(Show, PartialEq, Clone)]
+pub enum ErrorKind {
+    /// FMOD failed to do what was asked.
+    FmodFailure,
+    /// The object behind the handle doesn't exist anymore, like a channel which stopped or whose voice was stolen.
+    DeadHandle,
+    /// The given flags contradict each other. Nothing was sent to FMOD.
+    InvalidFlags,
+    /// The given value is outside of the range accepted by FMOD. Nothing was sent to FMOD.
+    OutOfRange,
+    /// The system was not closed because objects created from it are still alive. It's closed once they are dropped.
+    StillInUse
+}
+
+/* Arguments rejected before calling FMOD get the code FMOD would have returned, the kind tells them apart. */
+fn rejected(kind: ErrorKind, detail: String) -> FmodError {
+    FmodError{code: fmod::ErrInvalidParam, kind: kind, detail: Some(detail)}
+}
+
+/// Returned when the flags given to a function are rejected before calling FMOD.
+pub fn invalid_flags(detail: String) -> FmodError {
+    rejected(InvalidFlags, detail)
+}
+
+/// Returned when a value given to a function is rejected before calling FMOD.
+pub fn out_of_range(detail: String) -> FmodError {
+    rejected(OutOfRange, detail)
+}
+
+/// Returned when reading or writing a file outside of FMOD fails.
+pub fn io_error(e: IoError) -> FmodError {
+    let code = match e.kind {
+        io::FileNotFound => fmod::ErrFileNotFound,
+        _ => fmod::ErrFileBad
+    };
+
+    FmodError{code: code, kind: FmodFailure, detail: Some(format!("{}", e))}
+}
+
+/// Returned when releasing a system which other objects keep alive.
+pub fn still_in_use(detail: String) -> FmodError {
+    FmodError{code: fmod::ErrNotReady, kind: StillInUse, detail: Some(detail)}
+}
+
+/// Error returned by the wrappers, holding the FMOD error code.
+#[deriving(PartialEq, Clone)]
+pub struct FmodError {
+    code: fmod::Result,
+    kind: ErrorKind,
+    detail: Option<String>
+}
+
+impl FmodError {
+    pub fn new(code: fmod::Result) -> FmodError {
+        let kind = match code {
+            fmod::ErrInvalidHandle | fmod::ErrChannelStolen => DeadHandle,
+            _ => FmodFailure
+        };
+
+        FmodError{code: code, kind: kind, detail: None}
+    }
+
+    /// FMOD error code.
+    pub fn get_code(&self) -> fmod::Result {
+        self.code
+    }
+
+    /// Category of the error, chosen when it was created.
+    pub fn get_kind(&self) -> ErrorKind {
+        self.kind.clone()
+    }
+}
+
+impl Error for FmodError {
+    fn description(&self) -> &str {
+        match self.detail {
+            Some(ref d) => return d.as_slice(),
+            None => {}
+        }
+        match self.code {
+            fmod::Ok => "No errors.",
+            fmod::Err_AlreadyLocked => "Tried to call lock a second time before unlock was called.",
+            fmod::ErrBadCommand => "Tried to call a function on a data type that does not allow this type of functionality (ie calling Sound::lock on a streaming sound).",
+            fmod::ErrCDDADrivers => "Neither NTSCSI nor ASPI could be initialised.",
+            fmod::ErrCDDAInit => "An error occurred while initialising the CDDA subsystem.",
+            fmod::ErrCDDAInvalidDevice => "Couldn't find the specified device.",
+            fmod::ErrCDDANoAudio => "No audio tracks on the specified disc.",
+            fmod::ErrCDDANoDevices => "No CD/DVD devices were found.",
+            fmod::ErrCDDANoDisc => "No disc present in the specified drive.",
+            fmod::ErrCDDARead => "A CDDA read error occurred.",
+            fmod::ErrChannelAlloc => "Error trying to allocate a channel.",
+            fmod::ErrChannelStolen => "The specified channel has been reused to play another sound.",
+            fmod::ErrCOM => "A Win32 COM related error occured. COM failed to initialize or a QueryInterface failed meaning a Windows codec or driver was not installed properly.",
+            fmod::ErrDMA => "DMA Failure. See debug output for more information.",
+            fmod::ErrDSPConnection => "DSP connection error. Connection possibly caused a cyclic dependancy. Or tried to connect a tree too many units deep (more than 128).",
+            fmod::ErrDSPFormat => "DSP Format error. A DSP unit may have attempted to connect to this network with the wrong format.",
+            fmod::ErrDSPNotFound => "DSP connection error. Couldn't find the DSP unit specified.",
+            fmod::ErrDSPRunning => "DSP error. Cannot perform this operation while the network is in the middle of running. This will most likely happen if a connection or disconnection is attempted in a DSP callback.",
+            fmod::ErrDSPTooManyConnections => "DSP connection error. The unit being connected to or disconnected should only have 1 input or output.",
+            fmod::ErrFileBad => "Error loading file.",
+            fmod::ErrFileCouldNotSeek => "Couldn't perform seek operation. This is a limitation of the medium (ie netstreams) or the file format.",
+            fmod::ErrFileDiskEjected => "Media was ejected while reading.",
+            fmod::ErrFileEOF => "End of file unexpectedly reached while trying to read essential data (truncated data ?).",
+            fmod::ErrFileNotFound => "File not found.",
+            fmod::ErrFileUnwanted => "Unwanted file access occured.",
+            fmod::ErrFormat => "Unsupported file or audio format.",
+            fmod::ErrHTTP => "A HTTP error occurred. This is a catch-all for HTTP errors not listed elsewhere.",
+            fmod::ErrHTTPAccess => "The specified resource requires authentication or is forbidden.",
+            fmod::ErrHTTPProxyAuth => "Proxy authentication is required to access the specified resource.",
+            fmod::ErrHTTPServerError => "A HTTP server error occurred.",
+            fmod::ErrHTTPTimeout => "The HTTP request timed out.",
+            fmod::ErrInitialization => "FMOD was not initialized correctly to support this function.",
+            fmod::ErrInitialized => "Cannot call this command after System::init.",
+            fmod::ErrInternal => "An error occured that wasn't supposed to. Contact support.",
+            fmod::ErrInvalidAddress => "On Xbox 360, this memory address passed to FMOD must be physical, (ie allocated with XPhysicalAlloc.)",
+            fmod::ErrInvalidFloat => "Value passed in was a NaN, Inf or denormalized float.",
+            fmod::ErrInvalidHandle => "An invalid object handle was used.",
+            fmod::ErrInvalidParam => "An invalid parameter was passed to this function.",
+            fmod::ErrInvalidPosition => "An invalid seek position was passed to this function.",
+            fmod::ErrInvalidSpeaker => "An invalid speaker was passed to this function based on the current speaker mode.",
+            fmod::ErrInvalidSyncPoint => "The syncpoint did not come from this sound handle.",
+            fmod::ErrInvalidVector => "The vectors passed in are not unit length, or perpendicular.",
+            fmod::ErrMaxAudible => "Reached maximum audible playback count for this sound's soundgroup.",
+            fmod::ErrMemory => "Not enough memory or resources.",
+            fmod::ErrMemoryCantPoint => "Can't use FMOD_OPENMEMORY_POINT on non PCM source data, or non mp3/xma/adpcm data if FMOD_CREATECOMPRESSEDSAMPLE was used.",
+            fmod::ErrMemorySRam => "Not enough memory or resources on console sound ram.",
+            fmod::ErrNeeds2D => "Tried to call a command on a 3d sound when the command was meant for 2d sound.",
+            fmod::ErrNeeds3D => "Tried to call a command on a 2d sound when the command was meant for 3d sound.",
+            fmod::ErrNeedsHardware => "Tried to use a feature that requires hardware support. (ie trying to play a GCADPCM compressed sound in software on Wii).",
+            fmod::ErrNeedsSoftware => "Tried to use a feature that requires the software engine. Software engine has either been turned off, or command was executed on a hardware channel which does not support this feature.",
+            fmod::ErrNetConnect => "Couldn't connect to the specified host.",
+            fmod::ErrNetSocketError => "A socket error occurred. This is a catch-all for socket-related errors not listed elsewhere.",
+            fmod::ErrNetURL => "The specified URL couldn't be resolved.",
+            fmod::ErrNetWouldBlock => "Operation on a non-blocking socket could not complete immediately.",
+            fmod::ErrNotReady => "Operation could not be performed because specified sound/DSP connection is not ready.",
+            fmod::ErrOutputAllocated => "Error initializing output device, but more specifically, the output device is already in use and cannot be reused.",
+            fmod::ErrOutputCreateBuffer => "Error creating hardware sound buffer.",
+            fmod::ErrOutputDriverCall => "A call to a standard soundcard driver failed, which could possibly mean a bug in the driver or resources were missing or exhausted.",
+            fmod::ErrOutputEnumeration => "Error enumerating the available driver list. List may be inconsistent due to a recent device addition or removal.",
+            fmod::ErrOutputFormat => "Soundcard does not support the minimum features needed for this soundsystem (16bit stereo output).",
+            fmod::ErrOutputInit => "Error initializing output device.",
+            fmod::ErrOutputNoHardware => "FMOD_HARDWARE was specified but the sound card does not have the resources necessary to play it.",
+            fmod::ErrOutputNoSoftware => "Attempted to create a software sound but no software channels were specified in System::init.",
+            fmod::ErrPan => "Panning only works with mono or stereo sound sources.",
+            fmod::ErrPlugin => "An unspecified error has been returned from a 3rd party plugin.",
+            fmod::ErrPluginInstances => "The number of allowed instances of a plugin has been exceeded.",
+            fmod::ErrPluginMissing => "A requested output, dsp unit type or codec was not available.",
+            fmod::ErrPluginResource => "A resource that the plugin requires cannot be found. (ie the DLS file for MIDI playback or other DLLs that it needs to load)",
+            fmod::ErrPreloaded => "The specified sound is still in use by the event system, call EventSystem::unloadFSB before trying to release it.",
+            fmod::ErrProgrammerSound => "The specified sound is still in use by the event system, wait for the event which is using it finish with it.",
+            fmod::ErrRecord => "An error occured trying to initialize the recording device.",
+            fmod::ErrReverbInstance => "Specified instance in FMOD_REVERB_PROPERTIES couldn't be set. Most likely because it is an invalid instance number or the reverb doesnt exist.",
+            fmod::ErrSubsoundAllocated => "This subsound is already being used by another sound, you cannot have more than one parent to a sound. Null out the other parent's entry first.",
+            fmod::ErrSubsoundCantMove => "Shared subsounds cannot be replaced or moved from their parent stream, such as when the parent stream is an FSB file.",
+            fmod::ErrSubsoundMode => "The subsound's mode bits do not match with the parent sound's mode bits. See documentation for function that it was called with.",
+            fmod::ErrSubsounds => "The error occured because the sound referenced contains subsounds when it shouldn't have, or it doesn't contain subsounds when it should have. The operation may also not be able to be performed on a parent sound, or a parent sound was played without setting up a sentence first.",
+            fmod::ErrTagNotFound => "The specified tag could not be found or there are no tags.",
+            fmod::ErrTooManyChannels => "The sound created exceeds the allowable input channel count. This can be increased using the maxinputchannels parameter in System::setSoftwareFormat.",
+            fmod::ErrUnimplemented => "Something in FMOD hasn't been implemented when it should be ! contact support !",
+            fmod::ErrUnintialized => "This command failed because System::init or System::setDriver was not called.",
+            fmod::ErrUnsupported => "A command issued was not supported by this object. Possibly a plugin without certain callbacks specified.",
+            fmod::ErrUpdate => "An error caused by System::update occured.",
+            fmod::ErrVersion => "The version number of this file format is not supported.",
+            fmod::ErrEventFailed => "An Event failed to be retrieved, most likely due to 'just fail' being specified as the max playbacks behavior.",
+            fmod::ErrEventINFOONLY => "Can't execute this command on an EVENT_INFOONLY event.",
+            fmod::ErrEventInternal => "An error occured that wasn't supposed to. See debug log for reason.",
+            fmod::ErrEventMaxStreams => "Event failed because 'Max streams' was hit when FMOD_EVENT_INIT_FAIL_ON_MAXSTREAMS was specified.",
+            fmod::ErrEventMismatch => "FSB mismatches the FEV it was compiled with, the stream/sample mode it was meant to be created with was different, or the FEV was built for a different platform.",
+            fmod::ErrEventNameConflict => "A category with the same name already exists.",
+            fmod::ErrEventNotFound => "The requested event, event group, event category or event property could not be found.",
+            fmod::ErrEventNeedSimple => "Tried to call a function on a complex event that's only supported by simple events.",
+            fmod::ErrEventGuidConflict => "An event with the same GUID already exists.",
+            fmod::ErrEventAlreadyLoaded => "The specified project or bank has already been loaded. Having multiple copies of the same project loaded simultaneously is forbidden.",
+            fmod::ErrMusicUnintialized => "Music system is not initialized probably because no music data is loaded.",
+            fmod::ErrMusicNotFound => "The requested music entity could not be found.",
+            fmod::ErrMusicNoCallback => "The music callback is required, but it has not been set.",
+            fmod::ResultForceInt => "Unknown error."
+        }
+    }
+}
+
+impl fmt::Show for FmodError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.description(), self.code)
+    }
+}
@@ -48,6 +48,8 @@ pub struct FMOD_SYNCPOINT;
 
 pub type FMOD_SYSTEM_CALLBACK = Option<extern "C" fn(system: *mut FMOD_SYSTEM, _type: fmod::SystemCallbackType, command_data1: *mut c_void,
     command_data2: *mut c_void) -> fmod::Result>;
+pub type FMOD_CHANNEL_CALLBACK = Option<extern "C" fn(channel: *mut FMOD_CHANNEL, _type: fmod::ChannelCallbackType, command_data1: *mut c_void,
+    command_data2: *mut c_void) -> fmod::Result>;
 
 /* file callbacks */
 pub type FMOD_FILE_OPENCALLBACK = Option<extern "C" fn(name: *mut c_char, unicode: int, file_size: *mut c_uint, handle: *mut *mut c_void,
@@ -76,7 +78,7 @@ pub type FMOD_CODEC_SOUNDCREATECALLBACK = Option<extern "C" fn(codec_state: *mut
 pub type FMOD_CODEC_METADATACALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, tag_type: fmod::TagType, name: *mut c_char, data: *mut c_void,
     data_len: c_uint, data_type: fmod::TagDataType, unique: c_int) -> fmod::Result>;
 pub type FMOD_CODEC_GETWAVEFORMAT = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, index: c_int, wave_format: *mut FMOD_CODEC_WAVEFORMAT) -> fmod::Result>;
-pub type FMOD_3D_ROLLOFFCALLBACK = Option<extern "C" fn(channel: *mut FMOD_CHANNEL, distance: c_float) -> fmod::Result>;
+pub type FMOD_3D_ROLLOFFCALLBACK = Option<extern "C" fn(channel: *mut FMOD_CHANNEL, distance: c_float) -> c_float>;
 
 /*  DSP callbacks */
 pub type FMOD_DSP_CREATECALLBACK = Option<extern "C" fn(dsp_state: *mut FMOD_DSP_STATE) -> fmod::Result>;
@@ -112,18 +114,15 @@ extern "C" {
         num_output_channels: *mut c_int, max_input_channels: *mut c_int, resample_method: *mut fmod::DSPResampler, bits: *mut c_int) -> fmod::Result;
     pub fn FMOD_System_SetDSPBufferSize(system: *mut FMOD_SYSTEM, buffer_length: c_uint, num_buffers: c_int) -> fmod::Result;
     pub fn FMOD_System_GetDSPBufferSize(system: *mut FMOD_SYSTEM, buffer_length: *mut c_uint, num_buffers: *mut c_int) -> fmod::Result;
-    // I'll bind it a little later
     pub fn FMOD_System_SetFileSystem(system: *mut FMOD_SYSTEM, user_open: FMOD_FILE_OPENCALLBACK, user_close: FMOD_FILE_CLOSECALLBACK,
         user_read: FMOD_FILE_READCALLBACK, user_seek: FMOD_FILE_SEEKCALLBACK, user_async_read: FMOD_FILE_ASYNCREADCALLBACK,
         user_async_cancel: FMOD_FILE_ASYNCCANCELCALLBACK, block_align: c_int) -> fmod::Result;
-    // I'll bind it a little later
     pub fn FMOD_System_AttachFileSystem(system: *mut FMOD_SYSTEM, user_open: FMOD_FILE_OPENCALLBACK, user_close: FMOD_FILE_CLOSECALLBACK,
         user_read: FMOD_FILE_READCALLBACK, user_seek: FMOD_FILE_SEEKCALLBACK) -> fmod::Result;
     pub fn FMOD_System_SetAdvancedSettings(system: *mut FMOD_SYSTEM, settings: *mut FMOD_ADVANCEDSETTINGS) -> fmod::Result;
     pub fn FMOD_System_GetAdvancedSettings(system: *mut FMOD_SYSTEM, settings: *mut FMOD_ADVANCEDSETTINGS) -> fmod::Result;
     pub fn FMOD_System_SetSpeakerMode(system: *mut FMOD_SYSTEM, speaker_mode: fmod::SpeakerMode) -> fmod::Result;
     pub fn FMOD_System_GetSpeakerMode(system: *mut FMOD_SYSTEM, speaker_mode: *mut fmod::SpeakerMode) -> fmod::Result;
-    // I'll bind it a little later
     pub fn FMOD_System_SetCallback(system: *mut FMOD_SYSTEM, call_back: FMOD_SYSTEM_CALLBACK) -> fmod::Result;
     /* plug-in part functions */
     pub fn FMOD_System_SetPluginPath(system: *mut FMOD_SYSTEM, path: *const c_char) -> fmod::Result;
@@ -156,8 +155,9 @@ extern "C" {
         up: *mut FMOD_VECTOR) -> fmod::Result;
     pub fn FMOD_System_GetMemoryInfo(system: *mut FMOD_SYSTEM, memory_bits: c_uint, event_memory_bits: c_uint, memory_used: *mut c_uint,
         memoryused_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> fmod::Result;
-    /* I'll bind it later */
-    pub fn FMOD_System_SetFMOD_3D_ROLLOFFCALLBACK(system: *mut FMOD_SYSTEM, callback: FMOD_3D_ROLLOFFCALLBACK) -> fmod::Result;
+    pub fn FMOD_System_SetUserData(system: *mut FMOD_SYSTEM, user_data: *mut c_void) -> fmod::Result;
+    pub fn FMOD_System_GetUserData(system: *mut FMOD_SYSTEM, user_data: *mut *mut c_void) -> fmod::Result;
+    pub fn FMOD_System_Set3DRolloffCallback(system: *mut FMOD_SYSTEM, callback: FMOD_3D_ROLLOFFCALLBACK) -> fmod::Result;
     pub fn FMOD_System_Set3DSpeakerPosition(system: *mut FMOD_SYSTEM, speaker: fmod::Speaker, x: c_float, y: c_float, active: FMOD_BOOL) -> fmod::Result;
     pub fn FMOD_System_Get3DSpeakerPosition(system: *mut FMOD_SYSTEM, speaker: fmod::Speaker, x: *mut c_float, y: *mut c_float, active: *mut FMOD_BOOL) -> fmod::Result;
     pub fn FMOD_System_Set3DSettings(system: *mut FMOD_SYSTEM, doppler_scale: c_float, distance_factor: c_float, roll_off_scale: c_float) -> fmod::Result;
@@ -209,7 +209,6 @@ extern "C" {
     pub fn FMOD_System_CreateGeometry(system: *mut FMOD_SYSTEM, max_polygons: c_int, max_vertices: c_int, geometry: *mut *mut FMOD_GEOMETRY) -> fmod::Result;
     pub fn FMOD_System_SetGeometrySettings(system: *mut FMOD_SYSTEM, max_world_size: c_float) -> fmod::Result;
     pub fn FMOD_System_GetGeometrySettings(system: *mut FMOD_SYSTEM, max_world_size: *mut c_float) -> fmod::Result;
-    /* I'll bind it later */
     pub fn FMOD_System_LoadGeometry(system: *mut FMOD_SYSTEM, data: *mut c_void, data_size: c_int, geometry: *mut *mut FMOD_GEOMETRY) -> fmod::Result;
     pub fn FMOD_System_GetGeometryOcclusion(system: *mut FMOD_SYSTEM, listener: *const FMOD_VECTOR, source: *const FMOD_VECTOR, direct: *mut c_float,
         reverb: *mut c_float) -> fmod::Result;
@@ -247,7 +246,6 @@ extern "C" {
     pub fn FMOD_Sound_GetTag(sound: *mut FMOD_SOUND, name: *const c_char, index: c_int, tag: *mut FMOD_TAG) -> fmod::Result;
     pub fn FMOD_Sound_GetOpenState(sound: *mut FMOD_SOUND, open_state: *mut fmod::OpenState, percent_buffered: *mut c_uint, starving: *mut FMOD_BOOL,
         disk_busy: *mut FMOD_BOOL) -> fmod::Result;
-    /* I'll bind it later */
     pub fn FMOD_Sound_ReadData(sound: *mut FMOD_SOUND, buffer: *mut c_void, len_bytes: c_uint, read: *mut c_uint) -> fmod::Result;
     pub fn FMOD_Sound_SeekData(sound: *mut FMOD_SOUND, pcm: c_uint) -> fmod::Result;
     pub fn FMOD_Sound_SetSoundGroup(sound: *mut FMOD_SOUND, sound_group: *mut FMOD_SOUNDGROUP) -> fmod::Result;
@@ -314,8 +312,7 @@ extern "C" {
     pub fn FMOD_Channel_GetLowPassGain(channel: *mut FMOD_CHANNEL, gain: *mut c_float) -> fmod::Result;
     pub fn FMOD_Channel_SetChannelGroup(channel: *mut FMOD_CHANNEL, channelgroup: *mut FMOD_CHANNELGROUP) -> fmod::Result;
     pub fn FMOD_Channel_GetChannelGroup(channel: *mut FMOD_CHANNEL, channelgroup: *mut *mut FMOD_CHANNELGROUP) -> fmod::Result;
-    /* I'll bint it later */
-    //pub fn FMOD_Channel_SetCallback(channel: *mut FMOD_CHANNEL, callback: *mut FMOD_Channel_CALLBACK) -> fmod::Result;
+    pub fn FMOD_Channel_SetCallback(channel: *mut FMOD_CHANNEL, callback: FMOD_CHANNEL_CALLBACK) -> fmod::Result;
     /* 3D functionality */
     pub fn FMOD_Channel_Set3DAttributes(channel: *mut FMOD_CHANNEL, position: *mut FMOD_VECTOR, velociy: *mut FMOD_VECTOR) -> fmod::Result;
     pub fn FMOD_Channel_Get3DAttributes(channel: *mut FMOD_CHANNEL, position: *mut FMOD_VECTOR, velociy: *mut FMOD_VECTOR) -> fmod::Result;
@@ -514,7 +511,6 @@ extern "C" {
     pub fn FMOD_Geometry_GetPosition(geometry: *mut FMOD_GEOMETRY, position: *mut FMOD_VECTOR) -> fmod::Result;
     pub fn FMOD_Geometry_SetScale(geometry: *mut FMOD_GEOMETRY, scale: *const FMOD_VECTOR) -> fmod::Result;
     pub fn FMOD_Geometry_GetScale(geometry: *mut FMOD_GEOMETRY, scale: *mut FMOD_VECTOR) -> fmod::Result;
-    /* I'll bind it later */
     pub fn FMOD_Geometry_Save(geometry: *mut FMOD_GEOMETRY, data: *mut c_void, data_size: *mut c_int) -> fmod::Result;
     /* Userdata set/get. */
     pub fn FMOD_Geometry_SetUserData(geometry: *mut FMOD_GEOMETRY, user_data: *mut c_void) -> fmod::Result;
@@ -537,8 +533,6 @@ extern "C" {
     pub fn FMOD_Reverb_GetMemoryInfo(reverb: *mut FMOD_REVERB, memory_bits: c_uint, event_memory_bits: c_uint, memory_used: *mut c_uint,
         memory_used_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> fmod::Result;
 
-
-    /* need to add FMOD_ErrorString */
 }
 
 pub struct FMOD_ASYNCREADINFO
@@ -673,7 +667,7 @@ pub struct FMOD_CODEC_WAVEFORMAT
 pub struct FMOD_CODEC_STATE
 {
     pub numsubsounds: c_int,                      /* [in] Number of 'subsounds' in this sound. Anything other than 0 makes it a 'container' format (ie CDDA/DLS/FSB etc which contain 1 or more su bsounds). For most normal, single sound codec such as WAV/AIFF/MP3, this should be 0 as they are not a container for subsounds, they are the sound by itself. */
-    pub waveformat  : FMOD_CODEC_WAVEFORMAT,      /* [in] Pointer to an array of format structures containing information about each sample. Can be 0 or NULL if fmod::FMOD_CODEC_GETWAVEFORMAT callback is preferred. The number of entries here must equal the number of subsounds defined in the subsound parameter. If numsubsounds = 0 then there should be 1 instance of this structure. */
+    pub waveformat  : *mut FMOD_CODEC_WAVEFORMAT, /* [in] Pointer to an array of format structures containing information about each sample. Can be 0 or NULL if fmod::FMOD_CODEC_GETWAVEFORMAT callback is preferred. The number of entries here must equal the number of subsounds defined in the subsound parameter. If numsubsounds = 0 then there should be 1 instance of this structure. */
     pub plugindata  : *mut c_void,                /* [in] Plugin writer created data the codec author wants to attach to this object. */
                                                
     pub filehandle  : *mut c_void,                /* [out] This will return an internal FMOD file handle to use with the callbacks provided. */
@@ -816,8 +810,7 @@ pub struct FMOD_DSP_STATE
 pub struct SoundData {
     pub non_block: SoundNonBlockCallback,
     pub pcm_read: SoundPcmReadCallback,
-    pub pcm_set_pos: SoundPcmSetPosCallback,
-    pub user_data: *mut c_void
+    pub pcm_set_pos: SoundPcmSetPosCallback
 }
 
 impl SoundData {
@@ -825,8 +818,7 @@ impl SoundData {
         SoundData {
             non_block: None,
             pcm_read: None,
-            pcm_set_pos: None,
-            user_data: ::std::ptr::mut_null()
+            pcm_set_pos: None
         }
     }
 }
\ No newline at end of file
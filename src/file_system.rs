@@ -0,0 +1,491 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use enums::*;
+use libc::{c_void, c_uint, c_char, c_ushort};
+use std::mem::transmute;
+use std::c_vec::CVec;
+use std::collections::hashmap::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUint, INIT_ATOMIC_UINT, SeqCst};
+
+/* FMOD doesn't give any user data to the open callback, so every system with a custom file system gets its own slot, read
+ * by its own open callback. 0 is a free slot, 1 a slot being claimed, anything else the address of a FileSystemSlot. */
+static FILE_SYSTEMS : [AtomicUint, ..4] = [INIT_ATOMIC_UINT, INIT_ATOMIC_UINT, INIT_ATOMIC_UINT, INIT_ATOMIC_UINT];
+/* Only one file observer can be attached at a time. */
+static mut FILE_OBSERVER : *mut c_void = 0 as *mut c_void;
+
+unsafe fn get_name(name: *mut c_char, unicode: int) -> String {
+    if unicode != 0 {
+        let mut v = Vec::new();
+        let mut it = 0;
+
+        while *(name as *const c_ushort).offset(it) != 0 {
+            v.push(*(name as *const c_ushort).offset(it) as u16);
+            it += 1;
+        }
+        String::from_utf16_lossy(v.as_slice())
+    } else {
+        ::std::str::raw::from_c_str(name as *const c_char)
+    }
+}
+
+unsafe fn open(slot: &AtomicUint, name: *mut c_char, unicode: int, file_size: *mut c_uint, handle: *mut *mut c_void,
+    user_data: *mut *mut c_void) -> fmod::Result {
+    let slot = slot.load(SeqCst);
+
+    if name.is_null() || slot <= 1 {
+        return fmod::ErrFileNotFound;
+    }
+    let slot : &FileSystemSlot = transmute(slot);
+    // the opened file keeps its own reference: it has to be closed by the file system which opened it
+    let data = match *slot.current.lock() {
+        Some(ref data) => data.clone(),
+        None => return fmod::ErrFileNotFound
+    };
+    let t_name = get_name(name, unicode);
+    let ret = data.file_system.lock().open(t_name.as_slice());
+
+    match ret {
+        Ok((t_handle, size)) => {
+            *handle = transmute::<uint, *mut c_void>(t_handle);
+            *file_size = size as c_uint;
+            *user_data = transmute::<Box<Arc<FileSystemData>>, *mut c_void>(box data);
+            fmod::Ok
+        }
+        Err(e) => e
+    }
+}
+
+macro_rules! open_callbacks(
+    ($($index:expr => $callback:ident),+) => (
+        $(extern "C" fn $callback(name: *mut c_char, unicode: int, file_size: *mut c_uint, handle: *mut *mut c_void,
+            user_data: *mut *mut c_void) -> fmod::Result {
+            unsafe { open(&FILE_SYSTEMS[$index], name, unicode, file_size, handle, user_data) }
+        })+
+
+        fn open_callback(index: uint) -> ffi::FMOD_FILE_OPENCALLBACK {
+            [$(Some($callback)),+][index]
+        }
+    )
+)
+
+open_callbacks!(0 => open_callback0, 1 => open_callback1, 2 => open_callback2, 3 => open_callback3)
+
+extern "C" fn close_callback(handle: *mut c_void, user_data: *mut c_void) -> fmod::Result {
+    unsafe {
+        if user_data.is_not_null() {
+            let data : Box<Arc<FileSystemData>> = transmute(user_data);
+            let ret = data.file_system.lock().close(transmute::<*mut c_void, uint>(handle));
+
+            ret
+        } else {
+            fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn read_callback(handle: *mut c_void, buffer: *mut c_void, size_bytes: c_uint, bytes_read: *mut c_uint,
+    user_data: *mut c_void) -> fmod::Result {
+    unsafe {
+        if user_data.is_not_null() && buffer.is_not_null() {
+            let data : &Arc<FileSystemData> = transmute(user_data);
+            let mut t_buffer = CVec::new(buffer as *mut u8, size_bytes as uint);
+            let ret = data.file_system.lock().read(transmute::<*mut c_void, uint>(handle), t_buffer.as_mut_slice());
+
+            match ret {
+                Ok(read) => {
+                    *bytes_read = read as c_uint;
+                    if read < size_bytes as u32 {
+                        fmod::ErrFileEOF
+                    } else {
+                        fmod::Ok
+                    }
+                }
+                Err(e) => e
+            }
+        } else {
+            fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn seek_callback(handle: *mut c_void, pos: c_uint, user_data: *mut c_void) -> fmod::Result {
+    unsafe {
+        if user_data.is_not_null() {
+            let data : &Arc<FileSystemData> = transmute(user_data);
+            let ret = data.file_system.lock().seek(transmute::<*mut c_void, uint>(handle), pos as u32);
+
+            ret
+        } else {
+            fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn async_read_callback(info: *mut ffi::FMOD_ASYNCREADINFO, user_data: *mut c_void) -> fmod::Result {
+    unsafe {
+        if info.is_not_null() && user_data.is_not_null() {
+            let data : &Arc<FileSystemData> = transmute(user_data);
+            let ret = data.file_system.lock().async_read(AsyncReadInfo{info: info});
+
+            ret
+        } else {
+            fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn async_cancel_callback(handle: *mut c_void, user_data: *mut c_void, size_bytes: c_uint) -> fmod::Result {
+    unsafe {
+        if user_data.is_not_null() {
+            let data : &Arc<FileSystemData> = transmute(user_data);
+            let ret = data.file_system.lock().async_cancel(transmute::<*mut c_void, uint>(handle), size_bytes as u32);
+
+            ret
+        } else {
+            fmod::ErrInvalidHandle
+        }
+    }
+}
+
+extern "C" fn observer_open_callback(name: *mut c_char, unicode: int, file_size: *mut c_uint, _handle: *mut *mut c_void,
+    user_data: *mut *mut c_void) -> fmod::Result {
+    unsafe {
+        if name.is_not_null() && FILE_OBSERVER.is_not_null() {
+            let data : &FileObserverData = transmute(FILE_OBSERVER);
+            let file = box ObservedFile{name: get_name(name, unicode)};
+
+            data.observer.on_open(file.name.as_slice(), if file_size.is_not_null() { *file_size as u32 } else { 0u32 });
+            *user_data = transmute::<Box<ObservedFile>, *mut c_void>(file);
+        }
+        fmod::Ok
+    }
+}
+
+extern "C" fn observer_close_callback(_handle: *mut c_void, user_data: *mut c_void) -> fmod::Result {
+    unsafe {
+        if user_data.is_not_null() {
+            let file : Box<ObservedFile> = transmute(user_data);
+
+            if FILE_OBSERVER.is_not_null() {
+                let data : &FileObserverData = transmute(FILE_OBSERVER);
+
+                data.observer.on_close(file.name.as_slice());
+            }
+        }
+        fmod::Ok
+    }
+}
+
+extern "C" fn observer_read_callback(_handle: *mut c_void, _buffer: *mut c_void, size_bytes: c_uint, bytes_read: *mut c_uint,
+    user_data: *mut c_void) -> fmod::Result {
+    unsafe {
+        if user_data.is_not_null() && FILE_OBSERVER.is_not_null() {
+            let file : &ObservedFile = transmute(user_data);
+            let data : &FileObserverData = transmute(FILE_OBSERVER);
+
+            data.observer.on_read(file.name.as_slice(), size_bytes as u32, if bytes_read.is_not_null() { *bytes_read as u32 } else { 0u32 });
+        }
+        fmod::Ok
+    }
+}
+
+extern "C" fn observer_seek_callback(_handle: *mut c_void, pos: c_uint, user_data: *mut c_void) -> fmod::Result {
+    unsafe {
+        if user_data.is_not_null() && FILE_OBSERVER.is_not_null() {
+            let file : &ObservedFile = transmute(user_data);
+            let data : &FileObserverData = transmute(FILE_OBSERVER);
+
+            data.observer.on_seek(file.name.as_slice(), pos as u32);
+        }
+        fmod::Ok
+    }
+}
+
+/// Trait to implement to let FMOD read its files through your own code (a custom asset store, an archive, ...).
+///
+/// Handles are chosen by the implementation in `open` and are given back to every other method. The methods are called from
+/// FMOD's loading threads, one at a time.
+pub trait FileSystem: Send {
+    /// Opens the file `name`. Returns the handle of the opened file and its size in bytes.
+    fn open(&mut self, name: &str) -> Result<(uint, u32), fmod::Result>;
+    /// Closes the file corresponding to `handle`.
+    fn close(&mut self, handle: uint) -> fmod::Result;
+    /// Fills `buffer` and returns the number of bytes read. Returning less than `buffer.len()` means the end of the file has been reached.
+    fn read(&mut self, handle: uint, buffer: &mut [u8]) -> Result<u32, fmod::Result>;
+    /// Seeks to `pos` bytes from the beginning of the file.
+    fn seek(&mut self, handle: uint, pos: u32) -> fmod::Result;
+    /// Returns true if `async_read` and `async_cancel` are implemented. If not, FMOD only uses the synchronous methods.
+    fn has_async(&self) -> bool {
+        false
+    }
+    /// Queues an asynchronous read. The request has to be completed later with [`AsyncReadInfo::done`](struct.AsyncReadInfo.html#method.done).
+    #[allow(unused_variable)]
+    fn async_read(&mut self, info: AsyncReadInfo) -> fmod::Result {
+        fmod::ErrUnsupported
+    }
+    /// Cancels the pending asynchronous reads of `handle`.
+    #[allow(unused_variable)]
+    fn async_cancel(&mut self, handle: uint, size_bytes: u32) -> fmod::Result {
+        fmod::ErrUnsupported
+    }
+}
+
+/// Asynchronous read request sent by FMOD to [`FileSystem::async_read`](trait.FileSystem.html#tymethod.async_read).
+pub struct AsyncReadInfo {
+    info: *mut ffi::FMOD_ASYNCREADINFO
+}
+
+impl AsyncReadInfo {
+    /// Handle of the file to read from.
+    pub fn get_handle(&self) -> uint {
+        unsafe { transmute::<*mut c_void, uint>((*self.info).handle) }
+    }
+
+    /// Position in the file to read from.
+    pub fn get_offset(&self) -> u32 {
+        unsafe { (*self.info).offset as u32 }
+    }
+
+    /// Number of bytes requested.
+    pub fn get_size(&self) -> u32 {
+        unsafe { (*self.info).sizebytes as u32 }
+    }
+
+    /// 0 = low importance, 100 = must be read now or stuttering may occur.
+    pub fn get_priority(&self) -> i32 {
+        unsafe { (*self.info).priority as i32 }
+    }
+
+    /// Copies `data` into the FMOD buffer and tells FMOD the read is over. `data` can be shorter than the requested size at the end of the file.
+    pub fn done(self, data: &[u8]) {
+        unsafe {
+            let len = ::std::cmp::min(data.len(), (*self.info).sizebytes as uint);
+
+            ::std::ptr::copy_nonoverlapping_memory((*self.info).buffer as *mut u8, data.as_ptr(), len);
+            (*self.info).bytesread = len as c_uint;
+            (*self.info).result = if len < (*self.info).sizebytes as uint {
+                fmod::ErrFileEOF
+            } else {
+                fmod::Ok
+            };
+        }
+    }
+
+    /// Tells FMOD the read failed.
+    pub fn fail(self, error: fmod::Result) {
+        unsafe {
+            (*self.info).bytesread = 0;
+            (*self.info).result = error;
+        }
+    }
+}
+
+/// Trait to implement to be notified of every file access made by FMOD, without replacing its file system.
+///
+/// Methods are called from the thread doing the access, which is often a FMOD stream thread, and can run at the same time
+/// on several threads.
+#[allow(unused_variable)]
+pub trait FileObserver: Send + Sync {
+    /// Called when FMOD opens `name`.
+    fn on_open(&self, name: &str, file_size: u32) {}
+    /// Called when FMOD closes `name`.
+    fn on_close(&self, name: &str) {}
+    /// Called when FMOD reads `name`. `bytes_read` can be lower than `size_bytes` at the end of the file.
+    fn on_read(&self, name: &str, size_bytes: u32, bytes_read: u32) {}
+    /// Called when FMOD seeks in `name`.
+    fn on_seek(&self, name: &str, pos: u32) {}
+}
+
+#[deriving(Show, PartialEq, Clone)]
+/// I/O statistics of one file, returned by [`FileStatsObserver::get_report`](struct.FileStatsObserver.html#method.get_report).
+pub struct FileStats {
+    /// [r] Name of the file.
+    pub name       : String,
+    /// [r] Number of times the file has been opened. More than 1 often means the asset is loaded twice.
+    pub opens      : u32,
+    /// [r] Number of read calls.
+    pub reads      : u32,
+    /// [r] Total number of bytes read.
+    pub bytes_read : u64,
+    /// [r] Number of seeks.
+    pub seeks      : u32
+}
+
+impl FileStats {
+    fn new(name: &str) -> FileStats {
+        FileStats {
+            name: name.to_string(),
+            opens: 0u32,
+            reads: 0u32,
+            bytes_read: 0u64,
+            seeks: 0u32
+        }
+    }
+}
+
+#[deriving(Clone)]
+/// Ready-made [`FileObserver`](trait.FileObserver.html) which counts opens, bytes read and seeks per file name.
+///
+/// Clones share the same statistics, so give a clone to [`FmodSys::attach_file_observer`](struct.FmodSys.html#method.attach_file_observer)
+/// and keep the original to get the report.
+pub struct FileStatsObserver {
+    stats: Arc<Mutex<HashMap<String, FileStats>>>
+}
+
+impl FileStatsObserver {
+    pub fn new() -> FileStatsObserver {
+        FileStatsObserver {
+            stats: Arc::new(Mutex::new(HashMap::new()))
+        }
+    }
+
+    /// Returns the statistics of every file accessed so far, sorted by name.
+    pub fn get_report(&self) -> Vec<FileStats> {
+        let stats = self.stats.lock();
+        let mut report : Vec<FileStats> = stats.values().map(|s| s.clone()).collect();
+
+        report.sort_by(|a, b| a.name.cmp(&b.name));
+        report
+    }
+
+    /// Clears the statistics.
+    pub fn reset(&self) {
+        self.stats.lock().clear();
+    }
+}
+
+impl FileObserver for FileStatsObserver {
+    fn on_open(&self, name: &str, _file_size: u32) {
+        let mut stats = self.stats.lock();
+
+        stats.find_or_insert_with(name.to_string(), |n| FileStats::new(n.as_slice())).opens += 1;
+    }
+
+    fn on_read(&self, name: &str, _size_bytes: u32, bytes_read: u32) {
+        let mut stats = self.stats.lock();
+        let file = stats.find_or_insert_with(name.to_string(), |n| FileStats::new(n.as_slice()));
+
+        file.reads += 1;
+        file.bytes_read += bytes_read as u64;
+    }
+
+    fn on_seek(&self, name: &str, _pos: u32) {
+        let mut stats = self.stats.lock();
+
+        stats.find_or_insert_with(name.to_string(), |n| FileStats::new(n.as_slice())).seeks += 1;
+    }
+}
+
+struct ObservedFile {
+    name: String
+}
+
+pub struct FileObserverData {
+    observer: Box<FileObserver>
+}
+
+pub struct FileSystemData {
+    file_system: Mutex<Box<FileSystem>>
+}
+
+/// File system of one system. The files opened through a replaced file system keep it alive until they are closed.
+pub struct FileSystemSlot {
+    index: uint,
+    current: Mutex<Option<Arc<FileSystemData>>>
+}
+
+impl Drop for FileSystemSlot {
+    fn drop(&mut self) {
+        FILE_SYSTEMS[self.index].store(0, SeqCst);
+    }
+}
+
+fn claim_slot() -> Result<Box<FileSystemSlot>, fmod::Result> {
+    for (index, slot) in FILE_SYSTEMS.iter().enumerate() {
+        if slot.compare_and_swap(0, 1, SeqCst) == 0 {
+            let data = box FileSystemSlot{index: index, current: Mutex::new(None)};
+
+            slot.store(unsafe { transmute::<&FileSystemSlot, uint>(&*data) }, SeqCst);
+            return Ok(data);
+        }
+    }
+    Err(fmod::ErrMemory)
+}
+
+/// Makes the FMOD callbacks of `system` use `file_system`. `slot` is the one of `system`, it is claimed the first time.
+pub fn set_file_system(system: *mut ffi::FMOD_SYSTEM, slot: &mut Option<Box<FileSystemSlot>>, file_system: Box<FileSystem>,
+    block_align: i32) -> Result<(), fmod::Result> {
+    if slot.is_none() {
+        *slot = Some(try!(claim_slot()));
+    }
+    let slot = slot.as_ref().unwrap();
+    let has_async = file_system.has_async();
+    let old = ::std::mem::replace(&mut *slot.current.lock(), Some(Arc::new(FileSystemData{file_system: Mutex::new(file_system)})));
+
+    match unsafe { ffi::FMOD_System_SetFileSystem(system,
+        open_callback(slot.index),
+        Some(close_callback),
+        Some(read_callback),
+        Some(seek_callback),
+        if has_async { Some(async_read_callback) } else { None },
+        if has_async { Some(async_cancel_callback) } else { None },
+        block_align) } {
+        fmod::Ok => Ok(()),
+        e => {
+            *slot.current.lock() = old;
+            Err(e)
+        }
+    }
+}
+
+/// Installs `observer` as the one used by the FMOD attached file callbacks. The returned data has to be kept alive as long as FMOD uses it.
+pub fn attach_file_observer(system: *mut ffi::FMOD_SYSTEM, observer: Box<FileObserver>) -> Result<Box<FileObserverData>, fmod::Result> {
+    let mut data = box FileObserverData{observer: observer};
+    let old = unsafe { FILE_OBSERVER };
+
+    unsafe { FILE_OBSERVER = transmute::<&mut FileObserverData, *mut c_void>(&mut *data) };
+    match unsafe { ffi::FMOD_System_AttachFileSystem(system,
+        Some(observer_open_callback),
+        Some(observer_close_callback),
+        Some(observer_read_callback),
+        Some(observer_seek_callback)) } {
+        fmod::Ok => Ok(data),
+        e => {
+            unsafe { FILE_OBSERVER = old };
+            Err(e)
+        }
+    }
+}
+
+/// Forgets `data` if it is the attached file observer.
+pub fn remove_file_observer(data: &mut FileObserverData) {
+    unsafe {
+        if FILE_OBSERVER == transmute::<&mut FileObserverData, *mut c_void>(data) {
+            FILE_OBSERVER = ::std::ptr::mut_null();
+        }
+    }
+}
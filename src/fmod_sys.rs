@@ -27,7 +27,7 @@ use ffi;
 use types::*;
 use enums::*;
 use sound;
-use sound::Sound;
+use sound::{Sound, BorrowedSound};
 use sound_group;
 use std::mem;
 use channel_group;
@@ -42,6 +42,26 @@ use dsp_connection;
 use std::default::Default;
 use callbacks::*;
 use std::c_vec::CVec;
+use std::collections::hashmap::HashMap;
+use std::io::File;
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex};
+use std::cell::{RefCell, RefMut};
+use std::any::{Any, AnyRefExt};
+use file_system;
+use file_system::{FileSystem, FileObserver};
+use codec;
+use codec::Codec;
+use error;
+use error::FmodError;
+use strings;
+use dsp_processor;
+use dsp_processor::{DspProcessor, DspEffect, ProcessorData};
+use pcm_source;
+use pcm_source::{PcmSource, PcmSourceData};
+use load_handle;
+use load_handle::{LoadHandle, LoadState};
+use command_queue::{CommandQueue, CommandSender, SoundId};
 
 extern "C" fn pcm_read_callback(sound: *mut ffi::FMOD_SOUND, data: *mut c_void, data_len: c_uint) -> fmod::Result {
     unsafe {
@@ -57,7 +77,7 @@ extern "C" fn pcm_read_callback(sound: *mut ffi::FMOD_SOUND, data: *mut c_void,
                         let max = data_len as int >> 2;
                         let mut data_vec = CVec::new(data as *mut c_short, max as uint * 2);
 
-                        let ret = p(&sound::from_ptr(sound), data_vec.as_mut_slice());
+                        let ret = p(&sound::from_ptr(sound, SystemRef::none()), data_vec.as_mut_slice());
                         ret
                     },
                     None => fmod::Ok
@@ -81,7 +101,7 @@ extern "C" fn non_block_callback(sound: *mut ffi::FMOD_SOUND, result: fmod::Resu
                 let callbacks : &mut ffi::SoundData = ::std::mem::transmute(tmp);
 
                 match callbacks.non_block {
-                    Some(p) => p(&sound::from_ptr(sound), result),
+                    Some(p) => p(&sound::from_ptr(sound, SystemRef::none()), result),
                     None => fmod::Ok
                 }
             } else {
@@ -103,7 +123,7 @@ extern "C" fn pcm_set_pos_callback(sound: *mut ffi::FMOD_SOUND, sub_sound: c_int
                 let callbacks : &mut ffi::SoundData = ::std::mem::transmute(tmp);
 
                 match callbacks.pcm_set_pos {
-                    Some(p) => p(&sound::from_ptr(sound), sub_sound, position, FmodTimeUnit(postype)),
+                    Some(p) => p(&sound::from_ptr(sound, SystemRef::none()), sub_sound, position, FmodTimeUnit(postype)),
                     None => fmod::Ok
                 }
             } else {
@@ -115,6 +135,183 @@ extern "C" fn pcm_set_pos_callback(sound: *mut ffi::FMOD_SOUND, sub_sound: c_int
     }
 }
 
+/* FMOD gives a null system to the memory allocation failure callback, this one is used instead. */
+static mut LAST_SYSTEM_DATA : *mut c_void = 0 as *mut c_void;
+
+extern "C" fn system_callback(system: *mut ffi::FMOD_SYSTEM, _type: fmod::SystemCallbackType, command_data1: *mut c_void,
+    command_data2: *mut c_void) -> fmod::Result {
+    unsafe {
+        let mut tmp = ::std::ptr::mut_null();
+
+        if system.is_not_null() {
+            ffi::FMOD_System_GetUserData(system, &mut tmp);
+        } else {
+            tmp = LAST_SYSTEM_DATA;
+        }
+        if tmp.is_not_null() {
+            let user_data : &SystemUserData = ::std::mem::transmute(tmp);
+            let event = match _type {
+                fmod::SystemCallbackTypeDeviceListChanged => DeviceListChanged,
+                fmod::SystemCallbackTypeDeviceLost => DeviceLost,
+                fmod::SystemCallbackTypeMemoryAllocationFailed => MemoryAllocationFailed(
+                    if command_data1.is_not_null() {
+                        ::std::str::raw::from_c_str(command_data1 as *const c_char)
+                    } else {
+                        String::new()
+                    },
+                    command_data2 as int as i32),
+                fmod::SystemCallbackTypeThreadCreated => ThreadCreated(command_data1 as uint,
+                    if command_data2.is_not_null() {
+                        ::std::str::raw::from_c_str(command_data2 as *const c_char)
+                    } else {
+                        String::new()
+                    }),
+                fmod::SystemCallbackTypeThreadDestroyed => ThreadDestroyed(command_data1 as uint,
+                    if command_data2.is_not_null() {
+                        ::std::str::raw::from_c_str(command_data2 as *const c_char)
+                    } else {
+                        String::new()
+                    }),
+                // usually called from the mixer thread, where the reference count can't be touched: the DSPs are only lent to the closure
+                fmod::SystemCallbackTypeBadDSPConnection => BadDspConnection(dsp::from_ptr(command_data1 as *mut ffi::FMOD_DSP, SystemRef::none()),
+                    dsp::from_ptr(command_data2 as *mut ffi::FMOD_DSP, SystemRef::none())),
+                fmod::SystemCallbackTypeBadDSPLevel => BadDspLevel,
+                _ => return fmod::Ok
+            };
+
+            // the slot is unlocked before the call, so the closure can replace itself
+            let callback = user_data.callback.lock().clone();
+
+            match callback {
+                Some(c) => (*c.lock())(&event),
+                None => {}
+            }
+        }
+        fmod::Ok
+    }
+}
+
+extern "C" fn rolloff_callback(channel: *mut ffi::FMOD_CHANNEL, distance: c_float) -> c_float {
+    unsafe {
+        let mut system = ::std::ptr::mut_null();
+        let mut tmp = ::std::ptr::mut_null();
+
+        if channel.is_not_null() && ffi::FMOD_Channel_GetSystemObject(channel, &mut system) == fmod::Ok && system.is_not_null() {
+            ffi::FMOD_System_GetUserData(system, &mut tmp);
+        }
+        if tmp.is_not_null() {
+            let user_data : &SystemUserData = ::std::mem::transmute(tmp);
+            let callback = user_data.rolloff_callback.lock().clone();
+
+            match callback {
+                Some(c) => (*c.lock())(&channel::from_ptr(channel, user_data.get_system_ref()), distance as f32) as c_float,
+                None => 1f32 as c_float
+            }
+        } else {
+            1f32 as c_float
+        }
+    }
+}
+
+extern "C" fn channel_callback(channel: *mut ffi::FMOD_CHANNEL, _type: fmod::ChannelCallbackType, command_data1: *mut c_void,
+    command_data2: *mut c_void) -> fmod::Result {
+    unsafe {
+        let mut system = ::std::ptr::mut_null();
+        let mut tmp = ::std::ptr::mut_null();
+
+        if channel.is_not_null() && ffi::FMOD_Channel_GetSystemObject(channel, &mut system) == fmod::Ok && system.is_not_null() {
+            ffi::FMOD_System_GetUserData(system, &mut tmp);
+        }
+        if tmp.is_not_null() {
+            let user_data : &mut SystemUserData = ::std::mem::transmute(tmp);
+            let event = match _type {
+                fmod::ChannelCallbackTypeEnd => channel::End,
+                fmod::ChannelCallbackTypeVirtualVoice => channel::VirtualVoice(command_data1 as int == 1),
+                fmod::ChannelCallbackTypeSyncPoint => {
+                    let index = command_data1 as int as i32;
+                    let mut sound = ::std::ptr::mut_null();
+                    let mut sync_point = ::std::ptr::mut_null();
+                    let mut name = [0 as c_char, ..256];
+                    let mut offset = 0u32;
+
+                    if ffi::FMOD_Channel_GetCurrentSound(channel, &mut sound) == fmod::Ok
+                        && ffi::FMOD_Sound_GetSyncPoint(sound, index, &mut sync_point) == fmod::Ok
+                        && ffi::FMOD_Sound_GetSyncPointInfo(sound, sync_point, name.as_mut_ptr(), 256, &mut offset, 1) == fmod::Ok {
+                        channel::SyncPoint(index, ::std::str::raw::from_c_str(name.as_ptr()))
+                    } else {
+                        channel::SyncPoint(index, String::new())
+                    }
+                }
+                fmod::ChannelCallbackTypeOcclusion => channel::Occlusion(*(command_data1 as *mut c_float) as f32,
+                    *(command_data2 as *mut c_float) as f32),
+                _ => return fmod::Ok
+            };
+            let is_end = match event {
+                channel::End => true,
+                _ => false
+            };
+
+            match user_data.channel_callbacks.pop(&(channel as uint)) {
+                Some(mut c) => {
+                    (*c)(&channel::from_ptr(channel, user_data.get_system_ref()), event);
+                    // the closure may have set a new callback on its channel, which replaces it
+                    if !is_end && !user_data.channel_callbacks.contains_key(&(channel as uint)) {
+                        user_data.channel_callbacks.insert(channel as uint, c);
+                    }
+                }
+                None => {}
+            }
+        }
+        fmod::Ok
+    }
+}
+
+/// Events sent to the closure given to [`FmodSys::set_callback`](struct.FmodSys.html#method.set_callback).
+pub enum SystemEvent {
+    /// Called from [`FmodSys::update`](struct.FmodSys.html#method.update) when the enumerated list of devices has changed.
+    DeviceListChanged,
+    /// Called from [`FmodSys::update`](struct.FmodSys.html#method.update) when an output device has been lost and FMOD cannot automatically recover.
+    DeviceLost,
+    /// Called directly when a memory allocation fails somewhere in FMOD. Contains the file and line of the allocation inside FMOD and the requested size.
+    MemoryAllocationFailed(String, i32),
+    /// Called directly when a thread is created. Contains the thread handle and the thread name.
+    ThreadCreated(uint, String),
+    /// Called directly when a thread is destroyed. Contains the thread handle and the thread name.
+    ThreadDestroyed(uint, String),
+    /// Called when a bad connection was made with [`Dsp::add_input`](struct.Dsp.html#method.add_input). Contains the target and the source of the connection.
+    BadDspConnection(Dsp, Dsp),
+    /// Called when too many effects were added exceeding the maximum tree depth of 128.
+    BadDspLevel
+}
+
+/* The system and rolloff closures are locked as FMOD threads can call them while they are replaced. The callbacks only lock
+ * the slot to clone the closure out of it, then call it: a replaced closure is dropped once its running calls are over. */
+struct SystemUserData {
+    owner: Option<Weak<RefCell<SystemOwner>>>,
+    callback: Mutex<Option<Arc<Mutex<Box<FnMut(&SystemEvent) + Send>>>>>,
+    rolloff_callback: Mutex<Option<Arc<Mutex<Box<FnMut(&channel::Channel, f32) -> f32 + Send>>>>>,
+    channel_callbacks: HashMap<uint, Box<FnMut(&channel::Channel, channel::ChannelEvent)>>
+}
+
+impl SystemUserData {
+    fn new() -> SystemUserData {
+        SystemUserData {
+            owner: None,
+            callback: Mutex::new(None),
+            rolloff_callback: Mutex::new(None),
+            channel_callbacks: HashMap::new()
+        }
+    }
+
+    /* Only for the callbacks called from FmodSys::update, the reference count can't be touched from FMOD threads. */
+    fn get_system_ref(&self) -> SystemRef {
+        SystemRef{owner: match self.owner {
+            Some(ref o) => o.upgrade(),
+            None => None
+        }}
+    }
+}
+
 /// Structure describing a globally unique identifier.
 pub struct FmodGuid
 {
@@ -297,7 +494,7 @@ pub struct FmodCreateSoundexInfo
     pub encryption_key         : String,
     /// [w] Optional. Specify 0 to ignore. For sequenced formats with dynamic channel allocation such as .MID and .IT, this specifies the maximum voice count allowed while playing. .IT defaults to 64. .MID defaults to 32.
     pub max_polyphony          : i32,
-    /// [w] Optional. Specify 0 to ignore. This is user data to be attached to the sound during creation. Access via [`Sound::get_user_data`](doc/rfmod/struct.Sound.html#method.get_user_data). Note: This is not passed to FMOD_FILE_OPENCALLBACK, that is a different userdata that is file specific.
+    /// Holds the callbacks above while the sound is created. Use [`Sound::set_user_data`](doc/rfmod/struct.Sound.html#method.set_user_data) to attach data to the sound.
     user_data                  : Box<ffi::SoundData>,
     /// [w] Optional. Specify 0 or fmod::SoundTypeUnknown to ignore. Instead of scanning all codec types, use this to speed up loading by making it jump straight to this codec.
     pub suggested_sound_type   : fmod::SoundType,
@@ -360,7 +557,7 @@ impl Default for FmodCreateSoundexInfo {
             user_async_read: None,
             user_async_cancel: None,
             speaker_map: fmod::SpeakerMapTypeDefault,
-            initial_sound_group: sound_group::from_ptr(::std::ptr::mut_null()),
+            initial_sound_group: sound_group::from_ptr(::std::ptr::mut_null(), SystemRef::none()),
             initial_seek_position: 0u32,
             initial_seek_pos_type: FmodTimeUnit(0u32),
             ignore_set_file_system: 0i32,
@@ -427,7 +624,7 @@ impl FmodCreateSoundexInfo {
     }
 }
 
-/// When creating a codec, declare one of these and provide the relevant callbacks and name for FMOD to use when it opens and reads a file.
+/// When registering a codec with [`FmodSys::register_codec`](struct.FmodSys.html#method.register_codec), declare one of these to give its name and the time units it handles.
 pub struct FmodCodecDescription {
     /// [in] Name of the codec.
     pub name             : String,
@@ -437,23 +634,7 @@ pub struct FmodCodecDescription {
     /// Usually sequenced formats such as mod/s3m/xm/it/midi fall into this category. It is mainly to stop users that don't know what they're doing from getting FMOD_ERR_MEMORY returned from createSound when they should have in fact called System::createStream or used FMOD_CREATESTREAM in [`FmodSys::create_sound`](doc/rfmod/struct.FmodSys.html#method.create_sound).
     pub default_as_stream: i32,
     /// [in] When setposition codec is called, only these time formats will be passed to the codec. Use bitwise OR to accumulate different types.
-    pub time_units       : FmodTimeUnit,
-    /// [in] Open callback for the codec for when FMOD tries to open a sound using this codec.
-    open                 : ffi::FMOD_CODEC_OPENCALLBACK,
-    /// [in] Close callback for the codec for when FMOD tries to close a sound using this codec.
-    close                : ffi::FMOD_CODEC_CLOSECALLBACK,
-    /// [in] Read callback for the codec for when FMOD tries to read some data from the file to the destination format (specified in the open callback).
-    read                 : ffi::FMOD_CODEC_READCALLBACK,
-    /// [in] Callback to return the length of the song in whatever format required when [`Sound::get_length`](doc/rfmod/struct.Sound.html#method.get_length) is called.
-    get_length           : ffi::FMOD_CODEC_GETLENGTHCALLBACK,
-    /// [in] Seek callback for the codec for when FMOD tries to seek within the file with [`Channel::set_position`](doc/rfmod/struct.Channel.html#method.set_position).
-    set_position         : ffi::FMOD_CODEC_SETPOSITIONCALLBACK,
-    /// [in] Tell callback for the codec for when FMOD tries to get the current position within the with [`Channel::get_position`](doc/rfmod/struct.Channel.html#method.get_position).
-    get_position         : ffi::FMOD_CODEC_GETPOSITIONCALLBACK,
-    /// [in] Sound creation callback for the codec when FMOD finishes creating the sound. (So the codec can set more parameters for the related created sound, ie loop points/mode or 3D attributes etc).
-    sound_create         : ffi::FMOD_CODEC_SOUNDCREATECALLBACK,
-    /// [in] Callback to tell FMOD about the waveformat of a particular subsound. This is to save memory, rather than saving 1000 FMOD_CODEC_WAVEFORMAT structures in the codec, the codec might have a more optimal way of storing this information.
-    get_wave_format      : ffi::FMOD_CODEC_GETWAVEFORMAT
+    pub time_units       : FmodTimeUnit
 }
 
 impl Default for FmodCodecDescription {
@@ -462,15 +643,7 @@ impl Default for FmodCodecDescription {
             name: String::new(),
             version: 0u32,
             default_as_stream: 0i32,
-            time_units: FmodTimeUnit(0u32),
-            open: None,
-            close: None,
-            read: None,
-            get_length: None,
-            set_position: None,
-            get_position: None,
-            sound_create: None,
-            get_wave_format: None
+            time_units: FmodTimeUnit(0u32)
         }
     }
 }
@@ -742,8 +915,175 @@ pub fn from_memory_usage_details_ptr(details: ffi::FMOD_MEMORY_USAGE_DETAILS) ->
     }
 }
 
-pub fn from_ptr(system: *mut ffi::FMOD_SYSTEM) -> FmodSys {
-    FmodSys{system: system, is_first: false}
+pub fn set_channel_callback(channel: *mut ffi::FMOD_CHANNEL, callback: Box<FnMut(&channel::Channel, channel::ChannelEvent)>) -> fmod::Result {
+    let mut system = ::std::ptr::mut_null();
+    let mut tmp = ::std::ptr::mut_null();
+
+    match unsafe { ffi::FMOD_Channel_GetSystemObject(channel, &mut system) } {
+        fmod::Ok => {}
+        e => return e
+    }
+    match unsafe { ffi::FMOD_System_GetUserData(system, &mut tmp) } {
+        fmod::Ok => {}
+        e => return e
+    }
+    if tmp.is_null() {
+        return fmod::ErrInvalidHandle;
+    }
+    let user_data : &mut SystemUserData = unsafe { ::std::mem::transmute(tmp) };
+
+    user_data.channel_callbacks.insert(channel as uint, callback);
+    unsafe { ffi::FMOD_Channel_SetCallback(channel, Some(channel_callback)) }
+}
+
+/// Data given to the `set_user_data` methods of the objects, indexed by the address of the FMOD object.
+///
+/// It's shared so `with_user_data` can keep it alive while its closure runs, even if the closure replaces or removes it.
+struct ObjectData {
+    is_channel: bool,
+    data: Rc<Box<Any>>
+}
+
+/// Owns the FMOD system and the data its callbacks use. It's released when the last [`SystemRef`](struct.SystemRef.html) pointing to it is dropped.
+struct SystemOwner {
+    system: *mut ffi::FMOD_SYSTEM,
+    file_system: Option<Box<file_system::FileSystemSlot>>,
+    file_observer: Option<Box<file_system::FileObserverData>>,
+    user_data: Box<SystemUserData>,
+    commands: Option<CommandQueue>,
+    objects_data: HashMap<uint, ObjectData>
+}
+
+impl SystemOwner {
+    /* The closures and the registered sounds can hold objects of this system, which would keep it alive forever. They are taken
+     * out when the FmodSys returned by FmodSys::new is released, and dropped by the caller once the owner isn't borrowed anymore. */
+    fn take_callbacks(&mut self) -> (SystemUserData, Option<CommandQueue>) {
+        unsafe {
+            ffi::FMOD_System_SetCallback(self.system, None);
+            ffi::FMOD_System_Set3DRolloffCallback(self.system, None);
+        }
+
+        let callbacks = SystemUserData {
+            owner: None,
+            callback: Mutex::new(self.user_data.callback.lock().take()),
+            rolloff_callback: Mutex::new(self.user_data.rolloff_callback.lock().take()),
+            channel_callbacks: mem::replace(&mut self.user_data.channel_callbacks, HashMap::new())
+        };
+
+        (callbacks, self.commands.take())
+    }
+
+    fn take_stopped_channels_data(&mut self) -> Vec<ObjectData> {
+        let mut stopped = Vec::new();
+
+        for (key, object) in self.objects_data.iter() {
+            let mut playing = 0;
+
+            if object.is_channel && (unsafe { ffi::FMOD_Channel_IsPlaying(*key as *mut ffi::FMOD_CHANNEL, &mut playing) } != fmod::Ok || playing == 0) {
+                stopped.push(*key);
+            }
+        }
+        stopped.iter().filter_map(|key| self.objects_data.pop(key)).collect()
+    }
+}
+
+impl Drop for SystemOwner {
+    fn drop(&mut self) {
+        // closing the system can call them, and the channels they would get couldn't be linked to the system anymore
+        drop(self.take_callbacks());
+        unsafe {
+            ffi::FMOD_System_Close(self.system);
+            ffi::FMOD_System_Release(self.system);
+            if LAST_SYSTEM_DATA == ::std::mem::transmute::<&mut SystemUserData, *mut c_void>(&mut *self.user_data) {
+                LAST_SYSTEM_DATA = ::std::ptr::mut_null();
+            }
+        }
+        match self.file_observer {
+            Some(ref mut f) => file_system::remove_file_observer(&mut **f),
+            None => {}
+        }
+    }
+}
+
+/// Reference to the system an object was created from. Every object keeps one so the system can't be released before it.
+///
+/// Objects only lent to a callback hold an empty reference: they are created on FMOD threads, where the count can't be touched.
+#[deriving(Clone)]
+pub struct SystemRef {
+    owner: Option<Rc<RefCell<SystemOwner>>>
+}
+
+impl SystemRef {
+    pub fn none() -> SystemRef {
+        SystemRef{owner: None}
+    }
+
+    fn borrow_owner<'r>(&'r self) -> Result<RefMut<'r, SystemOwner>, FmodError> {
+        match self.owner {
+            Some(ref o) => Ok(o.borrow_mut()),
+            None => Err(FmodError::new(fmod::ErrInvalidHandle))
+        }
+    }
+
+    pub fn set_user_data(&self, object: uint, is_channel: bool, data: Box<Any>) -> Result<(), FmodError> {
+        let old = {
+            let mut owner = try!(self.borrow_owner());
+
+            owner.objects_data.swap(object, ObjectData{is_channel: is_channel, data: Rc::new(data)})
+        };
+
+        // the previous data is dropped here, once the owner isn't borrowed anymore
+        drop(old);
+        Ok(())
+    }
+
+    /// Calls `f` with the data attached to `object`. The owner isn't borrowed during the call, so `f` can use the system freely.
+    pub fn with_user_data<T: 'static, R>(&self, object: uint, f: |&T| -> R) -> Option<R> {
+        let data = match self.owner {
+            Some(ref o) => match o.borrow().objects_data.find(&object) {
+                Some(o) => o.data.clone(),
+                None => return None
+            },
+            None => return None
+        };
+
+        match (**data).downcast_ref::<T>() {
+            Some(d) => Some(f(d)),
+            None => None
+        }
+    }
+
+    pub fn remove_user_data(&self, object: uint) {
+        let old = match self.owner {
+            Some(ref o) => o.borrow_mut().objects_data.pop(&object),
+            None => None
+        };
+
+        drop(old);
+    }
+}
+
+fn memory_exinfo(data: &[u8]) -> ffi::FMOD_CREATESOUNDEXINFO {
+    let mut exinfo : ffi::FMOD_CREATESOUNDEXINFO = unsafe { mem::zeroed() };
+
+    exinfo.cbsize = mem::size_of::<ffi::FMOD_CREATESOUNDEXINFO>() as i32;
+    exinfo.length = data.len() as u32;
+    exinfo
+}
+
+/// Creates a sound from raw PCM data in memory, used by the [`SoundBuilder`](struct.SoundBuilder.html).
+pub fn create_raw_sound(system: &FmodSys, data: &[u8], mode: FmodMode, channels: i32, frequency: i32,
+    format: fmod::SoundFormat) -> Result<Sound, FmodError> {
+    let mut exinfo = memory_exinfo(data);
+
+    exinfo.numchannels = channels;
+    exinfo.defaultfrequency = frequency;
+    exinfo.format = format;
+    system.create_sound_from_ptr(data, mode | FMOD_OPENMEMORY | FMOD_OPENRAW, &mut exinfo)
+}
+
+pub fn from_ptr(system: *mut ffi::FMOD_SYSTEM, owner: SystemRef) -> FmodSys {
+    FmodSys{system: system, owner: owner, owns_callbacks: false}
 }
 
 pub fn get_ffi(system: &FmodSys) -> *mut ffi::FMOD_SYSTEM {
@@ -751,66 +1091,130 @@ pub fn get_ffi(system: &FmodSys) -> *mut ffi::FMOD_SYSTEM {
 }
 
 /// FMOD System Object
+///
+/// Every object created from it keeps the system alive: it's closed and released once the FmodSys, the copies returned by
+/// the `get_system_object` methods and all these objects have been dropped or released.
 pub struct FmodSys {
     system: *mut ffi::FMOD_SYSTEM,
-    is_first: bool
+    owner: SystemRef,
+    owns_callbacks: bool
 }
 
 impl Drop for FmodSys {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl FmodSys {
-    /* the first one created has to be the last one released */
-    pub fn new() -> Result<FmodSys, fmod::Result> {
+    pub fn new() -> Result<FmodSys, FmodError> {
         let mut tmp = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_Create(&mut tmp) } {
-            fmod::Ok => Ok(FmodSys{system: tmp, is_first: true}),
-            err => Err(err)
+            fmod::Ok => {
+                let mut owner = SystemOwner{system: tmp, file_system: None, file_observer: None, user_data: box SystemUserData::new(),
+                    commands: None, objects_data: HashMap::new()};
+
+                match unsafe { ffi::FMOD_System_SetUserData(tmp, ::std::mem::transmute::<&mut SystemUserData, *mut c_void>(&mut *owner.user_data)) } {
+                    fmod::Ok => {
+                        let owner = Rc::new(RefCell::new(owner));
+
+                        owner.borrow_mut().user_data.owner = Some(owner.downgrade());
+                        Ok(FmodSys{system: tmp, owner: SystemRef{owner: Some(owner)}, owns_callbacks: true})
+                    }
+                    err => Err(FmodError::new(err))
+                }
+            }
+            err => Err(FmodError::new(err))
         }
     }
 
-    pub fn init(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_System_Init(self.system, 1, FMOD_INIT_NORMAL, ::std::ptr::mut_null()) }
+    pub fn init(&self) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_Init(self.system, 1, FMOD_INIT_NORMAL, ::std::ptr::mut_null()) })
     }
 
-    pub fn init_with_parameters(&self, max_channels: i32, FmodInitFlag(flag): FmodInitFlag) -> fmod::Result {
-        unsafe { ffi::FMOD_System_Init(self.system, max_channels, flag, ::std::ptr::mut_null()) }
+    pub fn init_with_parameters(&self, max_channels: i32, FmodInitFlag(flag): FmodInitFlag) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_Init(self.system, max_channels, flag, ::std::ptr::mut_null()) })
     }
 
-    pub fn update(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_System_Update(self.system) }
+    /// Same as [`FmodSys::init_with_parameters`](struct.FmodSys.html#method.init_with_parameters) but passes `file_name` as extra driver data.
+    /// Used by the OutputTypeWAVWriter and OutputTypeWAVWriterNRT outputs to choose the file they write to.
+    pub fn init_with_output_file(&self, max_channels: i32, FmodInitFlag(flag): FmodInitFlag, file_name: &str) -> Result<(), FmodError> {
+        file_name.with_c_str(|c_file_name|{
+            error::check(unsafe { ffi::FMOD_System_Init(self.system, max_channels, flag, c_file_name as *mut c_void) })
+        })
     }
 
-    pub fn release(&mut self) -> fmod::Result {
-        if self.is_first && self.system.is_not_null() {
-            unsafe {
-                match match ffi::FMOD_System_Close(self.system) {
-                    fmod::Ok => ffi::FMOD_System_Release(self.system),
-                    e => e
-                } {
-                    fmod::Ok => {
-                        self.system = ::std::ptr::mut_null();
-                        fmod::Ok
-                    }
-                    e => e
+    /// Also runs the commands sent through the [`CommandSender`](struct.CommandSender.html)s of this system and drops the
+    /// user data of the channels which stopped.
+    pub fn update(&self) -> Result<(), FmodError> {
+        // the queue is taken out while it runs, as the channels it creates remove the stale user data of their handle
+        let commands = match self.owner.owner {
+            Some(ref o) => o.borrow_mut().commands.take(),
+            None => None
+        };
+
+        match commands {
+            Some(mut commands) => {
+                commands.run(&self.owner);
+                match self.owner.owner {
+                    Some(ref o) => o.borrow_mut().commands = Some(commands),
+                    None => {}
                 }
-                
             }
+            None => {}
+        }
+        let stopped = match self.owner.owner {
+            Some(ref o) => o.borrow_mut().take_stopped_channels_data(),
+            None => Vec::new()
+        };
+
+        drop(stopped);
+        error::check(unsafe { ffi::FMOD_System_Update(self.system) })
+    }
+
+    /// Drops this reference to the system. The system itself is only closed and released when no object created from it is alive anymore.
+    ///
+    /// Releasing the FmodSys returned by [`FmodSys::new`](struct.FmodSys.html#method.new) also drops the closures given to the
+    /// `set_*callback` methods, the sounds given to [`FmodSys::register_sound`](struct.FmodSys.html#method.register_sound) and the
+    /// channels started by [`CommandSender::play`](struct.CommandSender.html#method.play), so the objects they hold don't keep the
+    /// system alive. If other objects (sounds, channels, DSPs, ...) still keep it
+    /// alive, it returns a [`StillInUse`](enum.ErrorKind.html) error: the system, and the file written by the WAV writer
+    /// outputs, are only closed once they are dropped.
+    pub fn release(&mut self) -> Result<(), FmodError> {
+        let callbacks = match self.owner.owner {
+            Some(ref o) if self.owns_callbacks => Some(o.borrow_mut().take_callbacks()),
+            _ => None
+        };
+
+        drop(callbacks);
+        let still_alive = match self.owner.owner.take() {
+            Some(o) => {
+                let weak = o.downgrade();
+
+                drop(o);
+                weak.upgrade().is_some()
+            }
+            None => false
+        };
+
+        self.system = ::std::ptr::mut_null();
+        if still_alive && self.owns_callbacks {
+            Err(error::still_in_use(String::from_str("the system is still used by other objects, it will be closed once they are dropped")))
         } else {
-            fmod::Ok
+            Ok(())
         }
     }
 
     /// If music is empty, null is sent
-    pub fn create_sound(&self, music: &str, options: Option<FmodMode>, exinfo: Option<&mut FmodCreateSoundexInfo>) -> Result<Sound, fmod::Result> {
-        let mut sound = sound::from_ptr_first(::std::ptr::mut_null());
+    pub fn create_sound(&self, music: &str, options: Option<FmodMode>, exinfo: Option<&mut FmodCreateSoundexInfo>) -> Result<Sound, FmodError> {
+        let mut sound = sound::from_ptr_first(::std::ptr::mut_null(), self.owner.clone());
         let op = match options {
-            Some(FmodMode(t)) => t,
-            None => FMOD_SOFTWARE | FMOD_LOOP_OFF | FMOD_2D | FMOD_CREATESTREAM
+            Some(o) => {
+                try!(o.check());
+                o.bits()
+            }
+            None => (FMOD_SOFTWARE | FMOD_LOOP_OFF | FMOD_2D | FMOD_CREATESTREAM).bits()
         };
         let ex = match exinfo {
             Some(e) => {
@@ -818,9 +1222,6 @@ impl FmodSys {
                 user_data.non_block = e.non_block_callback;
                 user_data.pcm_read = e.pcm_read_callback;
                 user_data.pcm_set_pos = e.pcm_set_pos_callback;
-                unsafe {
-                    user_data.user_data = ::std::mem::transmute::<&mut ffi::SoundData, *mut c_void>(&mut *e.user_data);
-                }
                 &mut e.convert_to_c() as *mut ffi::FMOD_CREATESOUNDEXINFO
             },
             None => ::std::ptr::mut_null()
@@ -836,15 +1237,64 @@ impl FmodSys {
             fmod::Ok => {
                 Ok(sound)
             },
-            e => Err(e)
+            e => Err(FmodError::new(e))
+        }
+    }
+
+    /// Creates a sound from a file loaded in memory, like an asset extracted from a pack. FMOD_OPENMEMORY is added to `options`.
+    ///
+    /// FMOD copies the data of the samples. Streams read it while playing, so the sound keeps its own copy.
+    pub fn create_sound_from_memory(&self, data: &[u8], options: Option<FmodMode>) -> Result<Sound, FmodError> {
+        let mode = match options {
+            Some(o) => o | FMOD_OPENMEMORY,
+            None => FMOD_SOFTWARE | FMOD_LOOP_OFF | FMOD_2D | FMOD_CREATESAMPLE | FMOD_OPENMEMORY
+        };
+
+        if mode.contains(FMOD_CREATESTREAM) {
+            let memory = Vec::from_slice(data);
+            let mut sound = try!(self.create_sound_from_ptr(memory.as_slice(), mode, &mut memory_exinfo(data)));
+
+            sound::set_memory(&mut sound, memory);
+            Ok(sound)
+        } else {
+            self.create_sound_from_ptr(data, mode, &mut memory_exinfo(data))
+        }
+    }
+
+    /// Creates a sound reading `data` without copying it. FMOD_OPENMEMORY_POINT is added to `options`.
+    ///
+    /// As with FMOD_OPENMEMORY_POINT, compressed data needs FMOD_CREATECOMPRESSEDSAMPLE, which is used by default.
+    pub fn create_sound_from_static<'a>(&self, data: &'a [u8], options: Option<FmodMode>) -> Result<BorrowedSound<'a>, FmodError> {
+        let mode = match options {
+            Some(o) => o | FMOD_OPENMEMORY_POINT,
+            None => FMOD_SOFTWARE | FMOD_LOOP_OFF | FMOD_2D | FMOD_CREATECOMPRESSEDSAMPLE | FMOD_OPENMEMORY_POINT
+        };
+
+        match self.create_sound_from_ptr(data, mode, &mut memory_exinfo(data)) {
+            Ok(sound) => Ok(sound::from_borrowed(sound)),
+            Err(e) => Err(e)
+        }
+    }
+
+    fn create_sound_from_ptr(&self, data: &[u8], mode: FmodMode, exinfo: &mut ffi::FMOD_CREATESOUNDEXINFO) -> Result<Sound, FmodError> {
+        let mut sound = sound::from_ptr_first(::std::ptr::mut_null(), self.owner.clone());
+
+        try!(mode.check());
+        match unsafe { ffi::FMOD_System_CreateSound(self.system, data.as_ptr() as *const c_char, mode.bits(), exinfo,
+            sound::get_fffi(&mut sound)) } {
+            fmod::Ok => Ok(sound),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn create_stream(&self, music: &str, options: Option<FmodMode>, exinfo: Option<&mut FmodCreateSoundexInfo>) -> Result<Sound, fmod::Result> {
-        let mut sound = sound::from_ptr_first(::std::ptr::mut_null());
+    pub fn create_stream(&self, music: &str, options: Option<FmodMode>, exinfo: Option<&mut FmodCreateSoundexInfo>) -> Result<Sound, FmodError> {
+        let mut sound = sound::from_ptr_first(::std::ptr::mut_null(), self.owner.clone());
         let op = match options {
-            Some(FmodMode(t)) => t,
-            None => FMOD_SOFTWARE | FMOD_LOOP_OFF | FMOD_2D | FMOD_CREATESTREAM
+            Some(o) => {
+                try!(o.check());
+                o.bits()
+            }
+            None => (FMOD_SOFTWARE | FMOD_LOOP_OFF | FMOD_2D | FMOD_CREATESTREAM).bits()
         };
         let ex = match exinfo {
             Some(e) => {
@@ -852,9 +1302,6 @@ impl FmodSys {
                 user_data.non_block = e.non_block_callback;
                 user_data.pcm_read = e.pcm_read_callback;
                 user_data.pcm_set_pos = e.pcm_set_pos_callback;
-                unsafe {
-                    user_data.user_data = ::std::mem::transmute::<&mut ffi::SoundData, *mut c_void>(&mut *e.user_data);
-                }
                 &mut e.convert_to_c() as *mut ffi::FMOD_CREATESOUNDEXINFO
             },
             None => ::std::ptr::mut_null()
@@ -868,188 +1315,269 @@ impl FmodSys {
             unsafe { ffi::FMOD_System_CreateStream(self.system, ::std::ptr::null(), op, ex, sound::get_fffi(&mut sound)) }
         } {
             fmod::Ok => Ok(sound),
-            err => Err(err)
+            err => Err(FmodError::new(err))
+        }
+    }
+
+    /// Creates a sound with FMOD_OPENUSER added to `options`, its data being generated by `source`. `length` is the number of samples per channel,
+    /// and `format` must be a PCM format. Fails with an [`OutOfRange`](enum.ErrorKind.html) error if `channels` isn't positive or if the
+    /// size of the sound in bytes doesn't fit in a u32.
+    ///
+    /// By default the sound is a looping stream, so `source` is read while it plays.
+    pub fn create_user_sound(&self, source: Box<PcmSource + Send>, channels: i32, frequency: i32, format: fmod::SoundFormat, length: u32,
+        options: Option<FmodMode>) -> Result<Sound, FmodError> {
+        let mut sound = sound::from_ptr_first(::std::ptr::mut_null(), self.owner.clone());
+        let mode = match options {
+            Some(o) => o | FMOD_OPENUSER,
+            None => FMOD_SOFTWARE | FMOD_LOOP_NORMAL | FMOD_2D | FMOD_CREATESTREAM | FMOD_OPENUSER
+        };
+        let sample_size = match pcm_source::sample_size(format) {
+            Some(s) => s,
+            None => return Err(FmodError::new(fmod::ErrFormat))
+        };
+        if channels <= 0 {
+            return Err(error::out_of_range(format!("FmodSys::create_user_sound: {} channels, at least one is needed", channels)));
+        }
+        let byte_length = match length.checked_mul(&(channels as u32)).and_then(|l| l.checked_mul(&sample_size)) {
+            Some(l) => l,
+            None => return Err(error::out_of_range(format!("FmodSys::create_user_sound: {} samples of {} channels of {} bytes overflow the u32 length",
+                length, channels, sample_size)))
+        };
+        let mut exinfo : ffi::FMOD_CREATESOUNDEXINFO = unsafe { mem::zeroed() };
+
+        try!(mode.check());
+        exinfo.cbsize = mem::size_of::<ffi::FMOD_CREATESOUNDEXINFO>() as i32;
+        exinfo.length = byte_length;
+        exinfo.numchannels = channels;
+        exinfo.defaultfrequency = frequency;
+        exinfo.format = format;
+        exinfo.pcmreadcallback = Some(pcm_source::pcm_read_callback);
+        exinfo.pcmsetposcallback = Some(pcm_source::pcm_set_pos_callback);
+        exinfo.userdata = sound::set_pcm_source(&mut sound, box PcmSourceData::new(source, format));
+        match unsafe { ffi::FMOD_System_CreateSound(self.system, ::std::ptr::null(), mode.bits(), &mut exinfo, sound::get_fffi(&mut sound)) } {
+            fmod::Ok => Ok(sound),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn create_channel_group(&self, group_name: String) -> Result<channel_group::ChannelGroup, fmod::Result> {
+    /// Opens `path` with FMOD_NONBLOCKING added to `options` and returns immediately. The returned
+    /// [`LoadHandle`](struct.LoadHandle.html) tells when the sound can be played.
+    pub fn load_sound_async(&self, path: &str, options: Option<FmodMode>) -> Result<LoadHandle, FmodError> {
+        let mut sound = sound::from_ptr_first(::std::ptr::mut_null(), self.owner.clone());
+        let mode = match options {
+            Some(o) => o | FMOD_NONBLOCKING,
+            None => FMOD_SOFTWARE | FMOD_LOOP_OFF | FMOD_2D | FMOD_CREATESTREAM | FMOD_NONBLOCKING
+        };
+        let mut exinfo : ffi::FMOD_CREATESOUNDEXINFO = unsafe { mem::zeroed() };
+
+        try!(mode.check());
+        exinfo.cbsize = mem::size_of::<ffi::FMOD_CREATESOUNDEXINFO>() as i32;
+        exinfo.nonblockcallback = Some(load_handle::load_callback);
+        exinfo.userdata = sound::set_load_state(&mut sound, box LoadState::new());
+        match path.with_c_str(|c_str| {
+            unsafe { ffi::FMOD_System_CreateSound(self.system, c_str, mode.bits(), &mut exinfo, sound::get_fffi(&mut sound)) }
+        }) {
+            fmod::Ok => Ok(load_handle::from_sound(sound)),
+            e => Err(FmodError::new(e))
+        }
+    }
+
+    pub fn create_channel_group(&self, group_name: String) -> Result<channel_group::ChannelGroup, FmodError> {
         let t_group_name = group_name.clone();
         let mut channel_group = ::std::ptr::mut_null();
 
         t_group_name.with_c_str(|c_str|{
             match unsafe { ffi::FMOD_System_CreateChannelGroup(self.system, c_str, &mut channel_group) } {
-                fmod::Ok => Ok(channel_group::from_ptr(channel_group)),
-                e => Err(e)
+                fmod::Ok => Ok(channel_group::from_ptr(channel_group, self.owner.clone())),
+                e => Err(FmodError::new(e))
             }
         })
     }
 
-    pub fn create_sound_group(&self, group_name: String) -> Result<sound_group::SoundGroup, fmod::Result> {
+    pub fn create_sound_group(&self, group_name: String) -> Result<sound_group::SoundGroup, FmodError> {
         let t_group_name = group_name.clone();
         let mut sound_group = ::std::ptr::mut_null();
 
         t_group_name.with_c_str(|c_str|{
             match unsafe { ffi::FMOD_System_CreateSoundGroup(self.system, c_str, &mut sound_group) } {
-                fmod::Ok => Ok(sound_group::from_ptr(sound_group)),
-                e => Err(e)
+                fmod::Ok => Ok(sound_group::from_ptr(sound_group, self.owner.clone())),
+                e => Err(FmodError::new(e))
             }
         })
     }
 
-    pub fn create_reverb(&self) -> Result<reverb::Reverb, fmod::Result>{
+    pub fn create_reverb(&self) -> Result<reverb::Reverb, FmodError>{
         let mut t_reverb = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_CreateReverb(self.system, &mut t_reverb) } {
-            fmod::Ok => Ok(reverb::from_ptr(t_reverb)),
-            e => Err(e)
+            fmod::Ok => Ok(reverb::from_ptr(t_reverb, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn create_DSP(&self) -> Result<dsp::Dsp, fmod::Result> {
+    pub fn create_DSP(&self) -> Result<dsp::Dsp, FmodError> {
         let mut t_dsp = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_CreateDSP(self.system, ::std::ptr::mut_null(), &mut t_dsp) } {
-            fmod::Ok => Ok(dsp::from_ptr_first(t_dsp)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr_first(t_dsp, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn create_DSP_with_description(&self, description: &mut dsp::DspDescription) -> Result<dsp::Dsp, fmod::Result> {
+    pub fn create_DSP_with_description(&self, description: &mut dsp::DspDescription) -> Result<dsp::Dsp, FmodError> {
         let mut t_dsp = ::std::ptr::mut_null();
         let mut t_description = dsp::get_description_ffi(description);
 
         match unsafe { ffi::FMOD_System_CreateDSP(self.system, &mut t_description, &mut t_dsp) } {
-            fmod::Ok => Ok(dsp::from_ptr_first(t_dsp)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr_first(t_dsp, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn create_DSP_by_type(&self, _type: fmod::DspType) -> Result<dsp::Dsp, fmod::Result> {
+    /// Creates a DSP unit running `processor`, which is dropped when the DSP is released.
+    pub fn create_DSP_from_processor(&self, processor: Box<DspProcessor + Send>) -> Result<dsp::Dsp, FmodError> {
+        let mut t_dsp = ::std::ptr::mut_null();
+        let mut data = box ProcessorData::new(processor);
+        let mut t_description = dsp_processor::get_description_ffi(&mut *data);
+
+        match unsafe { ffi::FMOD_System_CreateDSP(self.system, &mut t_description, &mut t_dsp) } {
+            fmod::Ok => {
+                let mut dsp = dsp::from_ptr_first(t_dsp, self.owner.clone());
+
+                dsp::set_processor(&mut dsp, data);
+                Ok(dsp)
+            }
+            e => Err(FmodError::new(e))
+        }
+    }
+
+    /// Creates a DSP unit running `effect`, its parameters being set to their default value.
+    pub fn create_DSP_from_effect<T: DspEffect>(&self, effect: T) -> Result<dsp::Dsp, FmodError> {
+        self.create_DSP_from_processor(dsp_processor::from_effect(effect))
+    }
+
+    pub fn create_DSP_by_type(&self, _type: fmod::DspType) -> Result<dsp::Dsp, FmodError> {
         let mut t_dsp = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_CreateDSPByType(self.system, _type, &mut t_dsp) } {
-            fmod::Ok => Ok(dsp::from_ptr_first(t_dsp)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr_first(t_dsp, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_output(&self, output_type: fmod::OutputType) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetOutput(self.system, output_type) }
+    pub fn set_output(&self, output_type: fmod::OutputType) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetOutput(self.system, output_type) })
     }
 
-    pub fn get_output(&self) -> Result<fmod::OutputType, fmod::Result> {
+    pub fn get_output(&self) -> Result<fmod::OutputType, FmodError> {
         let mut output_type = fmod::OutputTypeAutoDetect;
         
         match unsafe { ffi::FMOD_System_GetOutput(self.system, &mut output_type) } {
             fmod::Ok => Ok(output_type),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_drivers(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_drivers(&self) -> Result<i32, FmodError> {
         let mut num_drivers = 0i32;
 
         match unsafe { ffi::FMOD_System_GetNumDrivers(self.system, &mut num_drivers) } {
             fmod::Ok => Ok(num_drivers),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_driver_info(&self, id: i32, name_len: uint) -> Result<(FmodGuid, String), fmod::Result> {
-        let tmp_v = String::with_capacity(name_len);
+    pub fn get_driver_info(&self, id: i32) -> Result<(FmodGuid, String), FmodError> {
         let mut guid = ffi::FMOD_GUID{Data1: 0, Data2: 0, Data3: 0, Data4: [0, 0, 0, 0, 0, 0, 0, 0]};
 
-        tmp_v.with_c_str(|c_str|{
-            match unsafe { ffi::FMOD_System_GetDriverInfo(self.system, id, c_str as *mut c_char, name_len as i32, &mut guid) } {
-                fmod::Ok => Ok((FmodGuid{data1: guid.Data1, data2: guid.Data2, data3: guid.Data3, data4: guid.Data4},
-                    unsafe {::std::str::raw::from_c_str(c_str).clone() })),
-                e => Err(e)
-            }
-        })
+        match strings::get_wide_string(|name, name_len| unsafe { ffi::FMOD_System_GetDriverInfoW(self.system, id, name, name_len, &mut guid) }) {
+            Ok(name) => Ok((FmodGuid{data1: guid.Data1, data2: guid.Data2, data3: guid.Data3, data4: guid.Data4}, name)),
+            Err(e) => Err(FmodError::new(e))
+        }
     }
 
-    pub fn get_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, fmod::SpeakerMode), fmod::Result> {
+    pub fn get_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, fmod::SpeakerMode), FmodError> {
         let mut fmod_caps = 0u32;
         let mut speaker_mode = fmod::SpeakerModeRaw;
         let mut control_panel_output_rate = 0i32;
 
         match unsafe { ffi::FMOD_System_GetDriverCaps(self.system, id, &mut fmod_caps, &mut control_panel_output_rate, &mut speaker_mode) } {
             fmod::Ok => Ok((FmodCaps(fmod_caps), control_panel_output_rate, speaker_mode)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_driver(&self, driver: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetDriver(self.system, driver) }
+    pub fn set_driver(&self, driver: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetDriver(self.system, driver) })
     }
 
-    pub fn get_driver(&self) -> Result<i32, fmod::Result> {
+    pub fn get_driver(&self) -> Result<i32, FmodError> {
         let mut driver = 0i32;
 
         match unsafe { ffi::FMOD_System_GetDriver(self.system, &mut driver) } {
             fmod::Ok => Ok(driver),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_hardware_channels(&self, num_hardware_channels: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetHardwareChannels(self.system, num_hardware_channels) }
+    pub fn set_hardware_channels(&self, num_hardware_channels: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetHardwareChannels(self.system, num_hardware_channels) })
     }
 
-    pub fn get_hardware_channels(&self) -> Result<i32, fmod::Result> {
+    pub fn get_hardware_channels(&self) -> Result<i32, FmodError> {
         let mut num_hardware_channels = 0i32;
 
         match unsafe { ffi::FMOD_System_GetHardwareChannels(self.system, &mut num_hardware_channels) } {
             fmod::Ok => Ok(num_hardware_channels),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_software_channels(&self, num_software_channels: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetSoftwareChannels(self.system, num_software_channels) }
+    pub fn set_software_channels(&self, num_software_channels: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetSoftwareChannels(self.system, num_software_channels) })
     }
 
-    pub fn get_software_channels(&self) -> Result<i32, fmod::Result> {
+    pub fn get_software_channels(&self) -> Result<i32, FmodError> {
         let mut num_software_channels = 0i32;
 
         match unsafe { ffi::FMOD_System_GetSoftwareChannels(self.system, &mut num_software_channels) } {
             fmod::Ok => Ok(num_software_channels),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn set_software_format(&self, sample_rate: i32, format: fmod::SoundFormat, num_output_channels: i32,
-        max_input_channels: i32, resample_method: fmod::DSPResampler) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetSoftwareFormat(self.system, sample_rate, format, num_output_channels,
-            max_input_channels, resample_method) }
+        max_input_channels: i32, resample_method: fmod::DSPResampler) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetSoftwareFormat(self.system, sample_rate, format, num_output_channels,
+            max_input_channels, resample_method) })
     }
 
-    pub fn get_software_format(&self) -> Result<FmodSoftwareFormat, fmod::Result> {
+    pub fn get_software_format(&self) -> Result<FmodSoftwareFormat, FmodError> {
         let mut t = FmodSoftwareFormat{sample_rate: 0, format: fmod::SoundFormatNone, num_output_channels: 0,
             max_input_channels: 0, resample_method: fmod::DSPResamplerNoInterp, bits: 0};
 
         match unsafe { ffi::FMOD_System_GetSoftwareFormat(self.system, &mut t.sample_rate, &mut t.format,
             &mut t.num_output_channels, &mut t.max_input_channels, &mut t.resample_method, &mut t.bits) } {
             fmod::Ok => Ok(t),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_DSP_buffer_size(&self, buffer_length: u32, num_buffers: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetDSPBufferSize(self.system, buffer_length, num_buffers) }
+    pub fn set_DSP_buffer_size(&self, buffer_length: u32, num_buffers: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetDSPBufferSize(self.system, buffer_length, num_buffers) })
     }
 
-    pub fn get_DSP_buffer_size(&self) -> Result<(u32, i32), fmod::Result> {
+    pub fn get_DSP_buffer_size(&self) -> Result<(u32, i32), FmodError> {
         let mut buffer_length = 0u32;
         let mut num_buffers = 0i32;
 
         match unsafe { ffi::FMOD_System_GetDSPBufferSize(self.system, &mut buffer_length, &mut num_buffers) } {
             fmod::Ok => Ok((buffer_length, num_buffers)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_advanced_settings(&self, settings: &mut FmodAdvancedSettings) -> fmod::Result {
+    pub fn set_advanced_settings(&self, settings: &mut FmodAdvancedSettings) -> Result<(), FmodError> {
         let mut converted_c_char = Vec::from_fn(settings.ASIO_channel_list.len(), |pos| {
             settings.ASIO_channel_list[pos].clone().with_c_str(|c_str| c_str)
         });
@@ -1084,10 +1612,10 @@ impl FmodSys {
             stackSizeMixer: settings.stack_size_mixer
         };
 
-        unsafe { ffi::FMOD_System_SetAdvancedSettings(self.system, &mut advanced_settings) }
+        error::check(unsafe { ffi::FMOD_System_SetAdvancedSettings(self.system, &mut advanced_settings) })
     }
 
-    pub fn get_advanced_settings(&self) -> Result<FmodAdvancedSettings, fmod::Result> {
+    pub fn get_advanced_settings(&self) -> Result<FmodAdvancedSettings, FmodError> {
         let mut advanced_settings = ffi::FMOD_ADVANCEDSETTINGS{
             cbsize: mem::size_of::<ffi::FMOD_ADVANCEDSETTINGS>() as i32,
             maxMPEGcodecs: 0,
@@ -1184,124 +1712,122 @@ impl FmodSys {
                     stack_size_mixer: advanced_settings.stackSizeMixer
                 })
             }
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_speaker_mode(&self, speaker_mode: fmod::SpeakerMode) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetSpeakerMode(self.system, speaker_mode) }
+    pub fn set_speaker_mode(&self, speaker_mode: fmod::SpeakerMode) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetSpeakerMode(self.system, speaker_mode) })
     }
 
-    pub fn get_speaker_mode(&self) -> Result<fmod::SpeakerMode, fmod::Result> {
+    pub fn get_speaker_mode(&self) -> Result<fmod::SpeakerMode, FmodError> {
         let mut speaker_mode = fmod::SpeakerModeRaw;
 
         match unsafe { ffi::FMOD_System_GetSpeakerMode(self.system, &mut speaker_mode) } {
             fmod::Ok => Ok(speaker_mode),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_plugin_path(&self, path: String) -> fmod::Result {
+    pub fn set_plugin_path(&self, path: String) -> Result<(), FmodError> {
         let tmp_v = path.clone();
 
         tmp_v.with_c_str(|c_str|{
-            unsafe { ffi::FMOD_System_SetPluginPath(self.system, c_str) }
+            error::check(unsafe { ffi::FMOD_System_SetPluginPath(self.system, c_str) })
         })
     }
 
-    pub fn load_plugin(&self, filename: String, priority: u32) -> Result<FmodPluginHandle, fmod::Result> {
+    pub fn load_plugin(&self, filename: String, priority: u32) -> Result<FmodPluginHandle, FmodError> {
         let tmp_v = filename.clone();
         let mut handle = 0u32;
 
         tmp_v.with_c_str(|c_str|{
             match unsafe { ffi::FMOD_System_LoadPlugin(self.system, c_str, &mut handle, priority) } {
                 fmod::Ok => Ok(FmodPluginHandle(handle)),
-                e => Err(e)
+                e => Err(FmodError::new(e))
             }
         })
     }
 
-    pub fn unload_plugin(&self, FmodPluginHandle(handle): FmodPluginHandle) -> fmod::Result {
-        unsafe { ffi::FMOD_System_UnloadPlugin(self.system, handle) }
+    pub fn unload_plugin(&self, FmodPluginHandle(handle): FmodPluginHandle) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_UnloadPlugin(self.system, handle) })
     }
 
-    pub fn get_num_plugins(&self, plugin_type: fmod::PluginType) -> Result<i32, fmod::Result> {
+    pub fn get_num_plugins(&self, plugin_type: fmod::PluginType) -> Result<i32, FmodError> {
         let mut num_plugins = 0i32;
 
         match unsafe { ffi::FMOD_System_GetNumPlugins(self.system, plugin_type, &mut num_plugins) } {
             fmod::Ok => Ok(num_plugins),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_plugin_handle(&self, plugin_type: fmod::PluginType, index: i32) ->Result<FmodPluginHandle, fmod::Result> {
+    pub fn get_plugin_handle(&self, plugin_type: fmod::PluginType, index: i32) ->Result<FmodPluginHandle, FmodError> {
         let mut handle = 0u32;
 
         match unsafe { ffi::FMOD_System_GetPluginHandle(self.system, plugin_type, index, &mut handle) } {
             fmod::Ok => Ok(FmodPluginHandle(handle)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_plugin_info(&self, FmodPluginHandle(handle): FmodPluginHandle, name_len: u32) -> Result<(String, fmod::PluginType, u32), fmod::Result> {
-        let name = String::with_capacity(name_len as uint);
+    pub fn get_plugin_info(&self, FmodPluginHandle(handle): FmodPluginHandle) -> Result<(String, fmod::PluginType, u32), FmodError> {
         let mut plugin_type = fmod::PluginTypeOutput;
         let mut version = 0u32;
 
-        name.with_c_str(|c_str|{
-            match unsafe { ffi::FMOD_System_GetPluginInfo(self.system, handle, &mut plugin_type, c_str as *mut c_char, name_len as i32, &mut version) } {
-                fmod::Ok => Ok((unsafe {::std::str::raw::from_c_str(c_str).clone() }, plugin_type, version)),
-                e => Err(e)
-            }
-        })
+        match strings::get_string(|name, name_len| unsafe { ffi::FMOD_System_GetPluginInfo(self.system, handle, &mut plugin_type, name, name_len,
+            &mut version) }) {
+            Ok(name) => Ok((name, plugin_type, version)),
+            Err(e) => Err(FmodError::new(e))
+        }
     }
 
-    pub fn set_output_by_plugin(&self, FmodPluginHandle(handle): FmodPluginHandle) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetOutputByPlugin(self.system, handle) }
+    pub fn set_output_by_plugin(&self, FmodPluginHandle(handle): FmodPluginHandle) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetOutputByPlugin(self.system, handle) })
     }
 
-    pub fn get_output_by_plugin(&self) -> Result<FmodPluginHandle, fmod::Result> {
+    pub fn get_output_by_plugin(&self) -> Result<FmodPluginHandle, FmodError> {
         let mut handle = 0u32;
 
         match unsafe { ffi::FMOD_System_GetOutputByPlugin(self.system, &mut handle) } {
             fmod::Ok => Ok(FmodPluginHandle(handle)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn create_DSP_by_plugin(&self, FmodPluginHandle(handle): FmodPluginHandle) -> Result<Dsp, fmod::Result> {
+    pub fn create_DSP_by_plugin(&self, FmodPluginHandle(handle): FmodPluginHandle) -> Result<Dsp, FmodError> {
         let mut dsp = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_CreateDSPByPlugin(self.system, handle, &mut dsp) } {
-            fmod::Ok => Ok(dsp::from_ptr_first(dsp)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr_first(dsp, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_num_listeners(&self, num_listeners: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_Set3DNumListeners(self.system, num_listeners) }
+    pub fn set_3D_num_listeners(&self, num_listeners: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_Set3DNumListeners(self.system, num_listeners) })
     }
 
-    pub fn get_3D_num_listeners(&self) -> Result<i32, fmod::Result> {
+    pub fn get_3D_num_listeners(&self) -> Result<i32, FmodError> {
         let mut num_listeners = 0i32;
 
         match unsafe { ffi::FMOD_System_Get3DNumListeners(self.system, &mut num_listeners) } {
             fmod::Ok => Ok(num_listeners),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn set_3D_listener_attributes(&self, listener: i32, pos: &vector::FmodVector, vel: &vector::FmodVector, forward: &vector::FmodVector,
-        up: &vector::FmodVector) -> fmod::Result {
+        up: &vector::FmodVector) -> Result<(), FmodError> {
         let c_p = vector::get_ffi(pos);
         let c_v = vector::get_ffi(vel);
         let c_f = vector::get_ffi(forward);
         let c_u = vector::get_ffi(up);
 
-        unsafe { ffi::FMOD_System_Set3DListenerAttributes(self.system, listener, &c_p, &c_v, &c_f, &c_u) }
+        error::check(unsafe { ffi::FMOD_System_Set3DListenerAttributes(self.system, listener, &c_p, &c_v, &c_f, &c_u) })
     }
 
-    pub fn get_3D_listener_attributes(&self, listener: i32) -> Result<(vector::FmodVector, vector::FmodVector, vector::FmodVector, vector::FmodVector), fmod::Result> {
+    pub fn get_3D_listener_attributes(&self, listener: i32) -> Result<(vector::FmodVector, vector::FmodVector, vector::FmodVector, vector::FmodVector), FmodError> {
         let mut pos = vector::get_ffi(&vector::FmodVector::new());
         let mut vel = vector::get_ffi(&vector::FmodVector::new());
         let mut forward = vector::get_ffi(&vector::FmodVector::new());
@@ -1309,19 +1835,19 @@ impl FmodSys {
 
         match unsafe { ffi::FMOD_System_Get3DListenerAttributes(self.system, listener, &mut pos, &mut vel, &mut forward, &mut up) } {
             fmod::Ok => Ok((vector::from_ptr(pos), vector::from_ptr(vel), vector::from_ptr(forward), vector::from_ptr(up))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_speaker_position(&self, speaker: fmod::Speaker, x: f32, y: f32, active: bool) -> fmod::Result {
+    pub fn set_3D_speaker_position(&self, speaker: fmod::Speaker, x: f32, y: f32, active: bool) -> Result<(), FmodError> {
         let t_active = match active {
             true => 1i32,
             false => 0i32
         };
-        unsafe { ffi::FMOD_System_Set3DSpeakerPosition(self.system, speaker, x, y, t_active) }
+        error::check(unsafe { ffi::FMOD_System_Set3DSpeakerPosition(self.system, speaker, x, y, t_active) })
     }
 
-    pub fn get_3D_speaker_position(&self, speaker: fmod::Speaker) -> Result<(f32, f32, bool), fmod::Result> {
+    pub fn get_3D_speaker_position(&self, speaker: fmod::Speaker) -> Result<(f32, f32, bool), FmodError> {
         let mut x = 0f32;
         let mut y = 0f32;
         let mut active = 0i32;
@@ -1331,67 +1857,67 @@ impl FmodSys {
                 1 => true,
                 _ => false
             })),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_settings(&self, doppler_scale: f32, distance_factor: f32, roll_off_scale: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_Set3DSettings(self.system, doppler_scale, distance_factor, roll_off_scale) }
+    pub fn set_3D_settings(&self, doppler_scale: f32, distance_factor: f32, roll_off_scale: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_Set3DSettings(self.system, doppler_scale, distance_factor, roll_off_scale) })
     }
 
-    pub fn get_3D_settings(&self) -> Result<(f32, f32, f32), fmod::Result> {
+    pub fn get_3D_settings(&self) -> Result<(f32, f32, f32), FmodError> {
         let mut doppler_scale = 0f32;
         let mut distance_factor = 0f32;
         let mut roll_off_scale = 0f32;
 
         match unsafe { ffi::FMOD_System_Get3DSettings(self.system, &mut doppler_scale, &mut distance_factor, &mut roll_off_scale) } {
             fmod::Ok => Ok((doppler_scale, distance_factor, roll_off_scale)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_stream_buffer_size(&self, file_buffer_size: u32, FmodTimeUnit(file_buffer_size_type): FmodTimeUnit) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetStreamBufferSize(self.system, file_buffer_size, file_buffer_size_type) }
+    pub fn set_stream_buffer_size(&self, file_buffer_size: u32, FmodTimeUnit(file_buffer_size_type): FmodTimeUnit) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetStreamBufferSize(self.system, file_buffer_size, file_buffer_size_type) })
     }
 
-    pub fn get_stream_buffer_size(&self) -> Result<(u32, FmodTimeUnit), fmod::Result> {
+    pub fn get_stream_buffer_size(&self) -> Result<(u32, FmodTimeUnit), FmodError> {
         let mut file_buffer_size = 0u32;
         let mut file_buffer_size_type = 0u32;
 
         match unsafe { ffi::FMOD_System_GetStreamBufferSize(self.system, &mut file_buffer_size, &mut file_buffer_size_type) } {
             fmod::Ok => Ok((file_buffer_size, FmodTimeUnit(file_buffer_size_type))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_version(&self) -> Result<u32, fmod::Result> {
+    pub fn get_version(&self) -> Result<u32, FmodError> {
         let mut version = 0u32;
 
         match unsafe { ffi::FMOD_System_GetVersion(self.system, &mut version) } {
             fmod::Ok => Ok(version),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_output_handle(&self) -> Result<FmodOutputHandle, fmod::Result> {
+    pub fn get_output_handle(&self) -> Result<FmodOutputHandle, FmodError> {
         let mut output_h = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_GetOutputHandle(self.system, &mut output_h) } {
             fmod::Ok => Ok(FmodOutputHandle{handle: output_h}),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_channels_playing(&self) -> Result<i32, fmod::Result> {
+    pub fn get_channels_playing(&self) -> Result<i32, FmodError> {
         let mut playing_chans = 0i32;
 
         match unsafe { ffi::FMOD_System_GetChannelsPlaying(self.system, &mut playing_chans) } {
             fmod::Ok => Ok(playing_chans),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_CPU_usage(&self) -> Result<(f32, f32, f32, f32, f32), fmod::Result> {
+    pub fn get_CPU_usage(&self) -> Result<(f32, f32, f32, f32, f32), FmodError> {
         let mut dsp = 0f32;
         let mut stream = 0f32;
         let mut geometry = 0f32;
@@ -1400,51 +1926,46 @@ impl FmodSys {
 
         match unsafe { ffi::FMOD_System_GetCPUUsage(self.system, &mut dsp, &mut stream, &mut geometry, &mut update, &mut total) } {
             fmod::Ok => Ok((dsp, stream, geometry, update, total)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_sound_RAM(&self) -> Result<(i32, i32, i32), fmod::Result> {
+    pub fn get_sound_RAM(&self) -> Result<(i32, i32, i32), FmodError> {
         let mut current_alloced = 0i32;
         let mut max_alloced = 0i32;
         let mut total = 0i32;
 
         match unsafe { ffi::FMOD_System_GetSoundRAM(self.system, &mut current_alloced, &mut max_alloced, &mut total) } {
             fmod::Ok => Ok((current_alloced, max_alloced, total)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_CDROM_drives(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_CDROM_drives(&self) -> Result<i32, FmodError> {
         let mut num_drives = 0i32;
 
         match unsafe { ffi::FMOD_System_GetNumCDROMDrives(self.system, &mut num_drives) } {
             fmod::Ok => Ok(num_drives),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_CDROM_drive_name(&self, drive: i32, drive_name_len: u32, scsi_name_len: u32, device_name_len: u32) -> Result<(String, String, String), fmod::Result> {
-        let drive_name = String::with_capacity(drive_name_len as uint);
-        let scsi_name = String::with_capacity(scsi_name_len as uint);
-        let device_name = String::with_capacity(device_name_len as uint);
+    /// Returns the drive name, the SCSI name and the device name.
+    pub fn get_CDROM_drive_name(&self, drive: i32) -> Result<(String, String, String), FmodError> {
+        match strings::get_strings(3, |buffers, len| unsafe { ffi::FMOD_System_GetCDROMDriveName(self.system, drive, buffers[0].as_mut_ptr(), len,
+            buffers[1].as_mut_ptr(), len, buffers[2].as_mut_ptr(), len) }) {
+            Ok(mut strings) => {
+                let device_name = strings.pop().unwrap();
+                let scsi_name = strings.pop().unwrap();
+                let drive_name = strings.pop().unwrap();
 
-        drive_name.with_c_str(|c_drive_name|{
-            scsi_name.with_c_str(|c_scsi_name|{
-                device_name.with_c_str(|c_device_name|{
-                    match unsafe { ffi::FMOD_System_GetCDROMDriveName(self.system, drive, c_drive_name as *mut c_char, drive_name_len as i32, c_scsi_name as *mut c_char,
-                        scsi_name_len as i32, c_device_name as *mut c_char, device_name_len as i32) } {
-                        fmod::Ok => Ok((unsafe {::std::str::raw::from_c_str(c_drive_name).clone() },
-                                        unsafe {::std::str::raw::from_c_str(c_scsi_name).clone() },
-                                        unsafe {::std::str::raw::from_c_str(c_device_name).clone() })),
-                        e => Err(e)
-                    }
-                })
-            })
-        })
+                Ok((drive_name, scsi_name, device_name))
+            }
+            Err(e) => Err(FmodError::new(e))
+        }
     }
 
-    pub fn get_spectrum(&self, spectrum_size: uint, channel_offset: Option<i32>, window_type: Option<fmod::DSP_FFT_Window>) -> Result<Vec<f32>, fmod::Result> {
+    pub fn get_spectrum(&self, spectrum_size: uint, channel_offset: Option<i32>, window_type: Option<fmod::DSP_FFT_Window>) -> Result<Vec<f32>, FmodError> {
         let mut ptr = Vec::from_elem(spectrum_size, 0f32);
         let c_window_type = match window_type {
             Some(wt) => wt,
@@ -1457,199 +1978,215 @@ impl FmodSys {
 
         match unsafe { ffi::FMOD_System_GetSpectrum(self.system, ptr.as_mut_ptr(), spectrum_size as c_int, c_channel_offset, c_window_type) } {
             fmod::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(FmodError::new(e)),
         }
     }
 
-    pub fn get_wave_data(&self, wave_size: uint, channel_offset: i32) -> Result<Vec<f32>, fmod::Result> {
+    pub fn get_wave_data(&self, wave_size: uint, channel_offset: i32) -> Result<Vec<f32>, FmodError> {
         let mut ptr = Vec::from_elem(wave_size, 0f32);
 
         match unsafe { ffi::FMOD_System_GetWaveData(self.system, ptr.as_mut_ptr(), wave_size as c_int, channel_offset) } {
             fmod::Ok => Ok(ptr),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
     
-    pub fn get_channel(&self, channel_id: i32) -> Result<channel::Channel, fmod::Result> {
+    pub fn get_channel(&self, channel_id: i32) -> Result<channel::Channel, FmodError> {
         let mut channel = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_GetChannel(self.system, channel_id, &mut channel) } {
-            fmod::Ok => Ok(channel::from_ptr(channel)),
-            e => Err(e)
+            fmod::Ok => Ok(channel::from_ptr(channel, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_master_channel_group(&self) -> Result<channel_group::ChannelGroup, fmod::Result> {
+    pub fn get_master_channel_group(&self) -> Result<channel_group::ChannelGroup, FmodError> {
         let mut channel_group = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_GetMasterChannelGroup(self.system, &mut channel_group) } {
-            fmod::Ok => Ok(channel_group::from_ptr(channel_group)),
-            e => Err(e)
+            fmod::Ok => Ok(channel_group::from_ptr(channel_group, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_master_sound_group(&self) -> Result<sound_group::SoundGroup, fmod::Result> {
+    pub fn get_master_sound_group(&self) -> Result<sound_group::SoundGroup, FmodError> {
         let mut sound_group = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_GetMasterSoundGroup(self.system, &mut sound_group) } {
-            fmod::Ok => Ok(sound_group::from_ptr(sound_group)),
-            e => Err(e)
+            fmod::Ok => Ok(sound_group::from_ptr(sound_group, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_reverb_properties(&self, properties: reverb_properties::ReverbProperties) -> fmod::Result {
+    pub fn set_reverb_properties(&self, properties: reverb_properties::ReverbProperties) -> Result<(), FmodError> {
         let t_properties = reverb_properties::get_ffi(properties);
 
-        unsafe { ffi::FMOD_System_SetReverbProperties(self.system, &t_properties) }
+        error::check(unsafe { ffi::FMOD_System_SetReverbProperties(self.system, &t_properties) })
     }
 
-    pub fn get_reverb_properties(&self) -> Result<reverb_properties::ReverbProperties, fmod::Result> {
+    pub fn get_reverb_properties(&self) -> Result<reverb_properties::ReverbProperties, FmodError> {
         let mut properties = reverb_properties::get_ffi(Default::default());
 
         match unsafe { ffi::FMOD_System_GetReverbProperties(self.system, &mut properties) } {
             fmod::Ok => Ok(reverb_properties::from_ptr(properties)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_reverb_ambient_properties(&self, properties: reverb_properties::ReverbProperties) -> fmod::Result {
+    pub fn set_reverb_ambient_properties(&self, properties: reverb_properties::ReverbProperties) -> Result<(), FmodError> {
         let mut t_properties = reverb_properties::get_ffi(properties);
 
-        unsafe { ffi::FMOD_System_SetReverbAmbientProperties(self.system, &mut t_properties) }
+        error::check(unsafe { ffi::FMOD_System_SetReverbAmbientProperties(self.system, &mut t_properties) })
     }
 
-    pub fn get_reverb_ambient_properties(&self) -> Result<reverb_properties::ReverbProperties, fmod::Result> {
+    pub fn get_reverb_ambient_properties(&self) -> Result<reverb_properties::ReverbProperties, FmodError> {
         let mut properties = reverb_properties::get_ffi(Default::default());
 
         match unsafe { ffi::FMOD_System_GetReverbAmbientProperties(self.system, &mut properties) } {
             fmod::Ok => Ok(reverb_properties::from_ptr(properties)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_DSP_head(&self) -> Result<Dsp, fmod::Result> {
+    pub fn get_DSP_head(&self) -> Result<Dsp, FmodError> {
         let mut head = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_GetDSPHead(self.system, &mut head) } {
-            fmod::Ok => Ok(dsp::from_ptr(head)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp::from_ptr(head, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn add_DSP(&self, dsp: &dsp::Dsp) -> Result<dsp_connection::DspConnection, fmod::Result> {
+    pub fn add_DSP(&self, dsp: &dsp::Dsp) -> Result<dsp_connection::DspConnection, FmodError> {
         let mut t_connection = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_AddDSP(self.system, dsp::get_ffi(dsp), &mut t_connection) } {
-            fmod::Ok => Ok(dsp_connection::from_ptr(t_connection)),
-            e => Err(e)
+            fmod::Ok => Ok(dsp_connection::from_ptr(t_connection, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn lock_DSP(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_System_LockDSP(self.system) }
+    pub fn lock_DSP(&self) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_LockDSP(self.system) })
     }
 
-    pub fn unlock_DSP(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_System_UnlockDSP(self.system) }
+    pub fn unlock_DSP(&self) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_UnlockDSP(self.system) })
     }
 
-    pub fn get_DSP_clock(&self) -> Result<(u32, u32), fmod::Result> {
+    pub fn get_DSP_clock(&self) -> Result<(u32, u32), FmodError> {
         let mut hi = 0u32;
         let mut lo = 0u32;
 
         match unsafe { ffi::FMOD_System_GetDSPClock(self.system, &mut hi, &mut lo) } {
             fmod::Ok => Ok((hi, lo)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_record_num_drivers(&self) -> Result<i32, fmod::Result> {
+    pub fn get_record_num_drivers(&self) -> Result<i32, FmodError> {
         let mut num_drivers = 0i32;
 
         match unsafe { ffi::FMOD_System_GetRecordNumDrivers(self.system, &mut num_drivers) } {
             fmod::Ok => Ok(num_drivers),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_record_driver_info(&self, id: i32, name_len: uint) -> Result<(FmodGuid, String), fmod::Result> {
-        let tmp_v = String::with_capacity(name_len);
+    pub fn get_record_driver_info(&self, id: i32) -> Result<(FmodGuid, String), FmodError> {
         let mut guid = ffi::FMOD_GUID{Data1: 0, Data2: 0, Data3: 0, Data4: [0, 0, 0, 0, 0, 0, 0, 0]};
 
-        tmp_v.with_c_str(|c_str|{
-            match unsafe { ffi::FMOD_System_GetRecordDriverInfo(self.system, id, c_str as *mut c_char, name_len as i32, &mut guid) } {
-                fmod::Ok => Ok((FmodGuid{data1: guid.Data1, data2: guid.Data2, data3: guid.Data3, data4: guid.Data4},
-                    unsafe {::std::str::raw::from_c_str(c_str).clone() })),
-                e => Err(e)
-            }
-        })
+        match strings::get_wide_string(|name, name_len| unsafe { ffi::FMOD_System_GetRecordDriverInfoW(self.system, id, name, name_len, &mut guid) }) {
+            Ok(name) => Ok((FmodGuid{data1: guid.Data1, data2: guid.Data2, data3: guid.Data3, data4: guid.Data4}, name)),
+            Err(e) => Err(FmodError::new(e))
+        }
     }
 
-    pub fn get_record_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, i32), fmod::Result> {
+    pub fn get_record_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, i32), FmodError> {
         let mut fmod_caps = 0u32;
         let mut min_frequency = 0i32;
         let mut max_frequency = 0i32;
 
         match unsafe { ffi::FMOD_System_GetRecordDriverCaps(self.system, id, &mut fmod_caps, &mut min_frequency, &mut max_frequency) } {
             fmod::Ok => Ok((FmodCaps(fmod_caps), min_frequency, max_frequency)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_record_position(&self, id: i32) -> Result<u32, fmod::Result> {
+    pub fn get_record_position(&self, id: i32) -> Result<u32, FmodError> {
         let mut position = 0u32;
 
         match unsafe { ffi::FMOD_System_GetRecordPosition(self.system, id, &mut position) } {
             fmod::Ok => Ok(position),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn start_record(&self, id: i32, sound: &sound::Sound, _loop: bool) -> fmod::Result {
+    pub fn start_record(&self, id: i32, sound: &sound::Sound, _loop: bool) -> Result<(), FmodError> {
         let t_loop = match _loop {
             true => 1,
             _ => 0
         };
 
-        unsafe { ffi::FMOD_System_RecordStart(self.system, id, sound::get_ffi(sound), t_loop) }
+        error::check(unsafe { ffi::FMOD_System_RecordStart(self.system, id, sound::get_ffi(sound), t_loop) })
     }
 
-    pub fn stop_record(&self, id: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_RecordStop(self.system, id) }
+    pub fn stop_record(&self, id: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_RecordStop(self.system, id) })
     }
 
-    pub fn is_recording(&self, id: i32) -> Result<bool, fmod::Result> {
+    pub fn is_recording(&self, id: i32) -> Result<bool, FmodError> {
         let mut is_recording = 0i32;
         
         match unsafe { ffi::FMOD_System_IsRecording(self.system, id, &mut is_recording) } {
             fmod::Ok => Ok(is_recording == 1),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn create_geometry(&self, max_polygons: i32, max_vertices: i32) -> Result<geometry::Geometry, fmod::Result> {
+    pub fn create_geometry(&self, max_polygons: i32, max_vertices: i32) -> Result<geometry::Geometry, FmodError> {
         let mut geometry = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_System_CreateGeometry(self.system, max_polygons, max_vertices, &mut geometry) } {
-            fmod::Ok => Ok(geometry::from_ptr(geometry)),
-            e => Err(e)
+            fmod::Ok => Ok(geometry::from_ptr(geometry, self.owner.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_geometry_settings(&self, max_world_size: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetGeometrySettings(self.system, max_world_size) }
+    /// Creates a geometry from data returned by [`Geometry::save`](struct.Geometry.html#method.save).
+    pub fn load_geometry(&self, data: &[u8]) -> Result<geometry::Geometry, FmodError> {
+        let mut geometry = ::std::ptr::mut_null();
+
+        match unsafe { ffi::FMOD_System_LoadGeometry(self.system, data.as_ptr() as *mut c_void, data.len() as c_int, &mut geometry) } {
+            fmod::Ok => Ok(geometry::from_ptr(geometry, self.owner.clone())),
+            e => Err(FmodError::new(e))
+        }
     }
 
-    pub fn get_geometry_settings(&self) -> Result<f32, fmod::Result> {
+    /// Same as [`FmodSys::load_geometry`](struct.FmodSys.html#method.load_geometry) but reads the data from `file_name`.
+    pub fn load_geometry_from_file(&self, file_name: &str) -> Result<geometry::Geometry, FmodError> {
+        let data = match File::open(&Path::new(file_name)).read_to_end() {
+            Ok(d) => d,
+            Err(e) => return Err(error::io_error(e))
+        };
+
+        self.load_geometry(data.as_slice())
+    }
+
+    pub fn set_geometry_settings(&self, max_world_size: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_System_SetGeometrySettings(self.system, max_world_size) })
+    }
+
+    pub fn get_geometry_settings(&self) -> Result<f32, FmodError> {
         let mut max_world_size = 0f32;
 
         match unsafe { ffi::FMOD_System_GetGeometrySettings(self.system, &mut max_world_size) } {
             fmod::Ok => Ok(max_world_size),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_geometry_occlusion(&self) -> Result<(vector::FmodVector, vector::FmodVector, f32, f32), fmod::Result> {
+    pub fn get_geometry_occlusion(&self) -> Result<(vector::FmodVector, vector::FmodVector, f32, f32), FmodError> {
         let listener = vector::get_ffi(&vector::FmodVector::new());
         let source = vector::get_ffi(&vector::FmodVector::new());
         let mut direct = 0f32;
@@ -1657,33 +2194,124 @@ impl FmodSys {
 
         match unsafe { ffi::FMOD_System_GetGeometryOcclusion(self.system, &listener, &source, &mut direct, &mut reverb) } {
             fmod::Ok => Ok((vector::from_ptr(listener), vector::from_ptr(source), direct, reverb)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
         let mut details = get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
         match unsafe { ffi::FMOD_System_GetMemoryInfo(self.system, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
             fmod::Ok => Ok((memory_used, from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    /*pub fn set_file_system(&self, user_open: ffi::FMOD_FILE_OPENCALLBACK, user_close: ffi::FMOD_FILE_CLOSECALLBACK,
-        user_read: ffi::FMOD_FILE_READCALLBACK, user_seek: ffi::FMOD_FILE_SEEKCALLBACK,
-        user_async_read: ffi::FMOD_FILE_ASYNCREADCALLBACK, user_async_cancel: ffi::FMOD_FILE_ASYNCCANCELCALLBACK,
-        block_align: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_System_SetFileSystem(self.system,
-            user_open,
-            user_close,
-            user_read,
-            user_seek,
-            user_async_read,
-            user_async_cancel,
-            block_align)
+    /// Makes FMOD read the files of this system through `file_system`. If another one was set, it is dropped once the files
+    /// already opened through it are closed.
+    ///
+    /// Up to 4 systems can use a custom file system at the same time, the next ones get `ErrMemory`.
+    pub fn set_file_system(&self, file_system: Box<FileSystem>, block_align: i32) -> Result<(), FmodError> {
+        let mut owner = try!(self.owner.borrow_owner());
+
+        match file_system::set_file_system(self.system, &mut owner.file_system, file_system, block_align) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(FmodError::new(e))
         }
-    }*/
-}
\ No newline at end of file
+    }
+
+    /// Lets `observer` watch every file open, read and seek made by FMOD, which keeps using its own file system.
+    ///
+    /// Only one file observer can be attached at a time in the process.
+    pub fn attach_file_observer(&mut self, observer: Box<FileObserver>) -> Result<(), FmodError> {
+        let mut owner = try!(self.owner.borrow_owner());
+
+        match file_system::attach_file_observer(self.system, observer) {
+            Ok(data) => {
+                owner.file_observer = Some(data);
+                Ok(())
+            }
+            Err(e) => Err(FmodError::new(e))
+        }
+    }
+
+    /// Sets a closure called on system events, like a device list change or a bad DSP connection.
+    ///
+    /// Doesn't work on a system obtained inside a callback. Some events are called directly from FMOD threads, so the event is only
+    /// lent to the closure: the DSPs of a `BadDspConnection` aren't linked to their system.
+    pub fn set_callback<F: FnMut(&SystemEvent) + Send>(&mut self, callback: F) -> Result<(), FmodError> {
+        let old = {
+            let mut owner = try!(self.owner.borrow_owner());
+            let old = mem::replace(&mut *owner.user_data.callback.lock(),
+                Some(Arc::new(Mutex::new(box callback as Box<FnMut(&SystemEvent) + Send>))));
+
+            unsafe { LAST_SYSTEM_DATA = ::std::mem::transmute::<&mut SystemUserData, *mut c_void>(&mut *owner.user_data) };
+            old
+        };
+
+        // the previous closure is dropped here, once the owner isn't borrowed anymore
+        drop(old);
+        error::check(unsafe { ffi::FMOD_System_SetCallback(self.system, Some(system_callback)) })
+    }
+
+    /// Sets a closure computing the volume (0.0 to 1.0) of a channel from its distance to the listener. It is used by every sound created with FMOD_3D_CUSTOMROLLOFF
+    /// which has no custom rolloff points set by [`Sound::set_3D_custom_rolloff`](struct.Sound.html#method.set_3D_custom_rolloff)
+    /// or [`Channel::set_3D_custom_rolloff`](struct.Channel.html#method.set_3D_custom_rolloff).
+    ///
+    /// Doesn't work on a system obtained inside a callback. The closure is called from [`FmodSys::update`](struct.FmodSys.html#method.update),
+    /// so it can read the data attached to the channel with [`Channel::with_user_data`](struct.Channel.html#method.with_user_data).
+    pub fn set_3D_rolloff_callback<F: FnMut(&channel::Channel, f32) -> f32 + Send>(&mut self, callback: F) -> Result<(), FmodError> {
+        let old = {
+            let owner = try!(self.owner.borrow_owner());
+
+            mem::replace(&mut *owner.user_data.rolloff_callback.lock(),
+                Some(Arc::new(Mutex::new(box callback as Box<FnMut(&channel::Channel, f32) -> f32 + Send>))))
+        };
+
+        drop(old);
+        error::check(unsafe { ffi::FMOD_System_Set3DRolloffCallback(self.system, Some(rolloff_callback)) })
+    }
+
+    /// Registers a codec written in Rust. A new `C` is created with `Default::default` each time FMOD tries to open a file with it,
+    /// so it can be played with [`FmodSys::create_sound`](struct.FmodSys.html#method.create_sound) like any other file. Returns the codec handle.
+    ///
+    /// Codecs are tried by FMOD in `priority` order, lowest first.
+    pub fn register_codec<C: Codec + Default + 'static>(&self, description: &FmodCodecDescription, priority: u32) -> Result<u32, FmodError> {
+        match codec::register_codec::<C>(self.system, description, priority) {
+            Ok(handle) => Ok(handle),
+            Err(e) => Err(FmodError::new(e))
+        }
+    }
+
+    /// Returns a handle which can be sent to other threads to queue commands on this system.
+    pub fn get_command_sender(&self) -> Result<CommandSender, FmodError> {
+        let mut owner = try!(self.owner.borrow_owner());
+
+        if owner.commands.is_none() {
+            owner.commands = Some(CommandQueue::new());
+        }
+        Ok(owner.commands.get_ref().get_sender())
+    }
+
+    /// Gives `sound` to the system so [`CommandSender`](struct.CommandSender.html)s can play it. It is released with the system
+    /// or by [`FmodSys::release_sound`](struct.FmodSys.html#method.release_sound).
+    pub fn register_sound(&self, sound: Sound) -> Result<SoundId, FmodError> {
+        let mut owner = try!(self.owner.borrow_owner());
+
+        if owner.commands.is_none() {
+            owner.commands = Some(CommandQueue::new());
+        }
+        Ok(owner.commands.get_mut_ref().register_sound(sound))
+    }
+
+    pub fn release_sound(&self, id: SoundId) -> Result<(), FmodError> {
+        let mut owner = try!(self.owner.borrow_owner());
+
+        match owner.commands {
+            Some(ref mut commands) => commands.release_sound(id),
+            None => Err(FmodError::new(fmod::ErrInvalidHandle))
+        }
+    }
+}
@@ -29,11 +29,14 @@ use vector;
 use libc::{c_int, c_void};
 use fmod_sys;
 use fmod_sys::FmodMemoryUsageDetails;
-use std::mem::transmute;
+use error;
+use error::FmodError;
+use std::any::Any;
 use std::default::Default;
+use std::io::File;
 
-pub fn from_ptr(geometry: *mut ffi::FMOD_GEOMETRY) -> Geometry {
-    Geometry{geometry: geometry}
+pub fn from_ptr(geometry: *mut ffi::FMOD_GEOMETRY, system: fmod_sys::SystemRef) -> Geometry {
+    Geometry{geometry: geometry, system: system}
 }
 
 pub fn get_ffi(geometry: Geometry) -> *mut ffi::FMOD_GEOMETRY {
@@ -42,206 +45,232 @@ pub fn get_ffi(geometry: Geometry) -> *mut ffi::FMOD_GEOMETRY {
 
 /// Geometry object
 pub struct Geometry {
-    geometry: *mut ffi::FMOD_GEOMETRY
+    geometry: *mut ffi::FMOD_GEOMETRY,
+    system: fmod_sys::SystemRef
 }
 
 impl Drop for Geometry {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl Geometry {
-    pub fn release(&mut self) -> fmod::Result {
+    pub fn release(&mut self) -> Result<(), FmodError> {
         if self.geometry !=::std::ptr::mut_null() {
             match unsafe { ffi::FMOD_Geometry_Release(self.geometry) } {
                 fmod::Ok => {
+                    self.system.remove_user_data(self.geometry as uint);
                     self.geometry =::std::ptr::mut_null();
-                    fmod::Ok
+                    Ok(())
                 }
-                e => e
+                e => Err(FmodError::new(e))
             }
         } else {
-            fmod::Ok
+            Ok(())
         }
     }
 
-    pub fn add_polygon(&self, direct_occlusion: f32, reverb_occlusion: f32, double_sided: bool, vertices: Vec<vector::FmodVector>) -> Result<i32, fmod::Result> {
+    pub fn add_polygon(&self, direct_occlusion: f32, reverb_occlusion: f32, double_sided: bool, vertices: Vec<vector::FmodVector>) -> Result<i32, FmodError> {
         let t_double_sided = if double_sided == true {
             1
         } else {
             0
         };
         let mut index = 0i32;
-        let t_vertices = Vec::with_capacity(vertices.len());
+        let mut t_vertices = Vec::with_capacity(vertices.len());
 
         for tmp in vertices.iter() {
-            vector::get_ffi(tmp);
+            t_vertices.push(vector::get_ffi(tmp));
         }
 
         match unsafe { ffi::FMOD_Geometry_AddPolygon(self.geometry, direct_occlusion, reverb_occlusion, t_double_sided, vertices.len() as c_int,
             t_vertices.as_ptr(), &mut index) } {
             fmod::Ok => Ok(index),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_polygons(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_polygons(&self) -> Result<i32, FmodError> {
         let mut num = 0i32;
 
         match unsafe { ffi::FMOD_Geometry_GetNumPolygons(self.geometry, &mut num) } {
             fmod::Ok => Ok(num),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_max_polygons(&self) -> Result<(i32, i32), fmod::Result> {
+    pub fn get_max_polygons(&self) -> Result<(i32, i32), FmodError> {
         let mut max_polygons = 0i32;
         let mut max_vertices = 0i32;
 
         match unsafe { ffi::FMOD_Geometry_GetMaxPolygons(self.geometry, &mut max_polygons, &mut max_vertices) } {
             fmod::Ok => Ok((max_polygons, max_vertices)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_polygon_num_vertices(&self, index: i32) -> Result<i32, fmod::Result> {
+    pub fn get_polygon_num_vertices(&self, index: i32) -> Result<i32, FmodError> {
         let mut num = 0i32;
 
         match unsafe { ffi::FMOD_Geometry_GetPolygonNumVertices(self.geometry, index, &mut num) } {
             fmod::Ok => Ok(num),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_polygon_vertex(&self, index: i32, vertex_index: i32, vertex: vector::FmodVector) -> fmod::Result {
+    pub fn set_polygon_vertex(&self, index: i32, vertex_index: i32, vertex: vector::FmodVector) -> Result<(), FmodError> {
         let t_vertex = vector::get_ffi(&vertex);
 
-        unsafe { ffi::FMOD_Geometry_SetPolygonVertex(self.geometry, index, vertex_index, &t_vertex) }
+        error::check(unsafe { ffi::FMOD_Geometry_SetPolygonVertex(self.geometry, index, vertex_index, &t_vertex) })
     }
 
-    pub fn get_polygon_vertex(&self, index: i32, vertex_index: i32) -> Result<vector::FmodVector, fmod::Result> {
+    pub fn get_polygon_vertex(&self, index: i32, vertex_index: i32) -> Result<vector::FmodVector, FmodError> {
         let mut vertex = vector::get_ffi(&vector::FmodVector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetPolygonVertex(self.geometry, index, vertex_index, &mut vertex) } {
             fmod::Ok => Ok(vector::from_ptr(vertex)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_polygon_attributes(&self, index: i32, direct_occlusion: f32, reverb_occlusion: f32, double_sided: bool) -> fmod::Result {
+    pub fn set_polygon_attributes(&self, index: i32, direct_occlusion: f32, reverb_occlusion: f32, double_sided: bool) -> Result<(), FmodError> {
         let t_double_sided = if double_sided == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_Geometry_SetPolygonAttributes(self.geometry, index, direct_occlusion, reverb_occlusion, t_double_sided) }
+        error::check(unsafe { ffi::FMOD_Geometry_SetPolygonAttributes(self.geometry, index, direct_occlusion, reverb_occlusion, t_double_sided) })
     }
 
-    pub fn get_polygon_attributes(&self, index: i32) -> Result<(f32, f32, bool), fmod::Result> {
+    pub fn get_polygon_attributes(&self, index: i32) -> Result<(f32, f32, bool), FmodError> {
         let mut direct_occlusion = 0f32;
         let mut reverb_occlusion = 0f32;
         let mut double_sided = 0;
 
         match unsafe { ffi::FMOD_Geometry_GetPolygonAttributes(self.geometry, index, &mut direct_occlusion, &mut reverb_occlusion, &mut double_sided) } {
             fmod::Ok => Ok((direct_occlusion, reverb_occlusion, double_sided == 1)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_active(&self, active: bool) -> fmod::Result {
+    pub fn set_active(&self, active: bool) -> Result<(), FmodError> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_Geometry_SetActive(self.geometry, t_active) }
+        error::check(unsafe { ffi::FMOD_Geometry_SetActive(self.geometry, t_active) })
     }
 
-    pub fn get_active(&self) -> Result<bool, fmod::Result> {
+    pub fn get_active(&self) -> Result<bool, FmodError> {
         let mut active = 0;
 
         match unsafe { ffi::FMOD_Geometry_GetActive(self.geometry, &mut active) } {
             fmod::Ok => Ok(active == 1),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_rotation(&self, forward: vector::FmodVector, up: vector::FmodVector) -> fmod::Result {
+    pub fn set_rotation(&self, forward: vector::FmodVector, up: vector::FmodVector) -> Result<(), FmodError> {
         let t_forward = vector::get_ffi(&forward);
         let t_up = vector::get_ffi(&up);
 
-        unsafe { ffi::FMOD_Geometry_SetRotation(self.geometry, &t_forward, &t_up) }
+        error::check(unsafe { ffi::FMOD_Geometry_SetRotation(self.geometry, &t_forward, &t_up) })
     }
 
-    pub fn get_rotation(&self) -> Result<(vector::FmodVector, vector::FmodVector), fmod::Result> {
+    pub fn get_rotation(&self) -> Result<(vector::FmodVector, vector::FmodVector), FmodError> {
         let mut forward = vector::get_ffi(&vector::FmodVector::new());
         let mut up = vector::get_ffi(&vector::FmodVector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetRotation(self.geometry, &mut forward, &mut up) } {
             fmod::Ok => Ok((vector::from_ptr(forward), vector::from_ptr(up))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_position(&self, position: vector::FmodVector) -> fmod::Result {
+    pub fn set_position(&self, position: vector::FmodVector) -> Result<(), FmodError> {
         let t_position = vector::get_ffi(&position);
 
-        unsafe { ffi::FMOD_Geometry_SetPosition(self.geometry, &t_position) }
+        error::check(unsafe { ffi::FMOD_Geometry_SetPosition(self.geometry, &t_position) })
     }
 
-    pub fn get_position(&self) -> Result<vector::FmodVector, fmod::Result> {
+    pub fn get_position(&self) -> Result<vector::FmodVector, FmodError> {
         let mut position = vector::get_ffi(&vector::FmodVector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetPosition(self.geometry, &mut position) } {
             fmod::Ok => Ok(vector::from_ptr(position)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_scale(&self, scale: vector::FmodVector) -> fmod::Result {
+    pub fn set_scale(&self, scale: vector::FmodVector) -> Result<(), FmodError> {
         let t_scale = vector::get_ffi(&scale);
 
-        unsafe { ffi::FMOD_Geometry_SetScale(self.geometry, &t_scale) }
+        error::check(unsafe { ffi::FMOD_Geometry_SetScale(self.geometry, &t_scale) })
     }
 
-    pub fn get_scale(&self) -> Result<vector::FmodVector, fmod::Result> {
+    pub fn get_scale(&self) -> Result<vector::FmodVector, FmodError> {
         let mut scale = vector::get_ffi(&vector::FmodVector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetScale(self.geometry, &mut scale) } {
             fmod::Ok => Ok(vector::from_ptr(scale)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
+        }
+    }
+
+    /// Serializes the geometry so it can be loaded later with [`FmodSys::load_geometry`](struct.FmodSys.html#method.load_geometry).
+    pub fn save(&self) -> Result<Vec<u8>, FmodError> {
+        let mut data_size = 0i32;
+
+        match unsafe { ffi::FMOD_Geometry_Save(self.geometry, ::std::ptr::mut_null(), &mut data_size) } {
+            fmod::Ok => {}
+            e => return Err(FmodError::new(e))
+        };
+        let mut data = Vec::from_elem(data_size as uint, 0u8);
+
+        match unsafe { ffi::FMOD_Geometry_Save(self.geometry, data.as_mut_ptr() as *mut c_void, &mut data_size) } {
+            fmod::Ok => Ok(data),
+            e => Err(FmodError::new(e))
+        }
+    }
+
+    /// Same as [`Geometry::save`](struct.Geometry.html#method.save) but writes the data in `file_name`.
+    pub fn save_to_file(&self, file_name: &str) -> Result<(), FmodError> {
+        let data = try!(self.save());
+        let mut file = match File::create(&Path::new(file_name)) {
+            Ok(f) => f,
+            Err(e) => return Err(error::io_error(e))
+        };
+
+        match file.write(data.as_slice()) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(error::io_error(e))
         }
     }
 
     pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
         match unsafe { ffi::FMOD_Geometry_GetMemoryInfo(self.geometry, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
             fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_user_data<T>(&self, user_data: &mut T) -> fmod::Result {
-        unsafe { ffi::FMOD_Geometry_SetUserData(self.geometry, transmute(user_data)) }
+    /// Attaches `user_data` to the geometry, replacing and dropping the previous one. It's dropped when the geometry is released.
+    ///
+    /// Fails on a geometry whose system was obtained inside a callback, as it isn't linked to its system.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.system.set_user_data(self.geometry as uint, false, user_data)
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, fmod::Result> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::mut_null();
-
-            match ffi::FMOD_Geometry_GetUserData(self.geometry, &mut user_data) {
-                fmod::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    Ok(tmp)
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the data attached to the geometry and returns its result. Returns None if no data is attached or if it isn't a `T`.
+    pub fn with_user_data<T: 'static, R>(&self, f: |&T| -> R) -> Option<R> {
+        self.system.with_user_data(self.geometry as uint, f)
     }
 }
\ No newline at end of file
@@ -0,0 +1,149 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use enums::*;
+use sound;
+use sound::Sound;
+use error::FmodError;
+use libc::c_void;
+use std::mem::transmute;
+use std::sync::Mutex;
+
+struct LoadResult {
+    result: Option<fmod::Result>,
+    on_complete: Option<proc(Result<(), FmodError>):Send>
+}
+
+/* Pointed by the user data of the sounds created by FmodSys::load_sound_async. */
+pub struct LoadState {
+    inner: Mutex<LoadResult>
+}
+
+impl LoadState {
+    pub fn new() -> LoadState {
+        LoadState{inner: Mutex::new(LoadResult{result: None, on_complete: None})}
+    }
+
+    fn get_result(&self) -> Option<fmod::Result> {
+        self.inner.lock().result
+    }
+}
+
+fn to_result(result: fmod::Result) -> Result<(), FmodError> {
+    match result {
+        fmod::Ok => Ok(()),
+        e => Err(FmodError::new(e))
+    }
+}
+
+/* Called from the FMOD loading thread, and again by FMOD when a stream seeks or restarts. */
+pub extern "C" fn load_callback(sound: *mut ffi::FMOD_SOUND, result: fmod::Result) -> fmod::Result {
+    unsafe {
+        if sound.is_not_null() {
+            let mut tmp = ::std::ptr::mut_null();
+
+            ffi::FMOD_Sound_GetUserData(sound, &mut tmp);
+            if tmp.is_not_null() {
+                let state : &LoadState = transmute(tmp as *const c_void);
+                let on_complete = {
+                    let mut inner = state.inner.lock();
+
+                    inner.result = Some(result);
+                    inner.cond.broadcast();
+                    inner.on_complete.take()
+                };
+
+                match on_complete {
+                    Some(f) => f(to_result(result)),
+                    None => {}
+                }
+            }
+        }
+    }
+    fmod::Ok
+}
+
+pub fn from_sound(sound: Sound) -> LoadHandle {
+    LoadHandle{sound: sound}
+}
+
+/// Sound being loaded in the background, returned by [`FmodSys::load_sound_async`](struct.FmodSys.html#method.load_sound_async).
+pub struct LoadHandle {
+    sound: Sound
+}
+
+impl LoadHandle {
+    /// Returns true once the sound can be played, or the error which made the loading fail.
+    pub fn is_ready(&self) -> Result<bool, FmodError> {
+        match sound::get_load_state(&self.sound).get_result() {
+            Some(r) => to_result(r).map(|_| true),
+            None => Ok(false)
+        }
+    }
+
+    /// Percentage of the stream buffer filled, as given by [`Sound::get_open_state`](struct.Sound.html#method.get_open_state). Returns 100 once the sound is ready.
+    pub fn progress(&self) -> Result<u32, FmodError> {
+        if try!(self.is_ready()) {
+            Ok(100)
+        } else {
+            match self.sound.get_open_state() {
+                Ok((_, percent_buffered, _, _)) => Ok(percent_buffered),
+                Err(e) => Err(e)
+            }
+        }
+    }
+
+    /// Blocks until the loading is over and returns the sound. The thread sleeps until the FMOD loading thread signals the end of the loading.
+    pub fn wait(self) -> Result<Sound, FmodError> {
+        let result = {
+            let inner = sound::get_load_state(&self.sound).inner.lock();
+
+            while inner.result.is_none() {
+                inner.cond.wait();
+            }
+            inner.result.unwrap()
+        };
+
+        try!(to_result(result));
+        Ok(self.sound)
+    }
+
+    /// Calls `on_complete` with the result of the loading once it's over, from the FMOD loading thread. If the loading is already over,
+    /// it's called immediately. It replaces the previous callback if it wasn't called yet.
+    pub fn on_complete(&self, on_complete: proc(Result<(), FmodError>):Send) {
+        let state = sound::get_load_state(&self.sound);
+        let result = {
+            let mut inner = state.inner.lock();
+
+            if inner.result.is_none() {
+                inner.on_complete = Some(on_complete);
+                return;
+            }
+            inner.result.unwrap()
+        };
+
+        on_complete(to_result(result))
+    }
+}
@@ -0,0 +1,202 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use types::*;
+use enums::*;
+use fmod_sys;
+use fmod_sys::FmodSys;
+use error;
+use error::FmodError;
+use libc::{c_int, c_uint, c_float, c_void};
+use std::mem::transmute;
+use std::slice;
+
+extern "C" fn capture_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, in_buffer: *mut c_float, out_buffer: *mut c_float, length: c_uint,
+    in_channels: c_int, _out_channels: c_int) -> fmod::Result {
+    unsafe {
+        let len = length as uint * in_channels as uint;
+
+        ::std::ptr::copy_nonoverlapping_memory(out_buffer, in_buffer as *const c_float, len);
+        if dsp_state.is_not_null() && (*dsp_state).instance.is_not_null() {
+            let mut tmp = ::std::ptr::mut_null();
+
+            ffi::FMOD_DSP_GetUserData((*dsp_state).instance, &mut tmp);
+            if tmp.is_not_null() {
+                let data : &mut Vec<f32> = transmute(tmp);
+
+                slice::raw::buf_as_slice(in_buffer as *const f32, len, |b| {
+                    data.push_all(b);
+                });
+            }
+        }
+        fmod::Ok
+    }
+}
+
+/// Where an [`OfflineRenderer`](struct.OfflineRenderer.html) sends the mix.
+pub enum RenderTarget {
+    /// Writes the mix in a WAV file, using the OutputTypeWAVWriterNRT output.
+    RenderToFile(String),
+    /// Keeps the mix in memory as interleaved floats, using the OutputTypeNoSoundNRT output.
+    RenderToMemory
+}
+
+/// Non realtime system mixing one DSP block per [`OfflineRenderer::step`](struct.OfflineRenderer.html#method.step).
+/// The output doesn't depend on wall-clock time, so two renders of the same scene give the same result.
+pub struct OfflineRenderer {
+    system: FmodSys,
+    sample_rate: i32,
+    channels: i32,
+    block_length: u32,
+    rendered: u64,
+    data: Box<Vec<f32>>,
+    capture_dsp: *mut ffi::FMOD_DSP
+}
+
+impl Drop for OfflineRenderer {
+    fn drop(&mut self) {
+        let _ = self.release();
+    }
+}
+
+impl OfflineRenderer {
+    /// Creates and initializes the system. `block_length` is the number of samples per channel mixed by each update.
+    pub fn new(target: RenderTarget, sample_rate: i32, channels: i32, block_length: u32, max_channels: i32) -> Result<OfflineRenderer, FmodError> {
+        let system = try!(FmodSys::new());
+        let output = match target {
+            RenderToFile(_) => fmod::OutputTypeWAVWriterNRT,
+            RenderToMemory => fmod::OutputTypeNoSoundNRT
+        };
+        let mut renderer = OfflineRenderer {
+            system: system,
+            sample_rate: sample_rate,
+            channels: channels,
+            block_length: block_length,
+            rendered: 0u64,
+            data: box Vec::new(),
+            capture_dsp: ::std::ptr::mut_null()
+        };
+
+        try!(renderer.system.set_output(output));
+        try!(renderer.system.set_speaker_mode(fmod::SpeakerModeRaw));
+        try!(renderer.system.set_software_format(sample_rate, fmod::SoundFormatPCM16, channels, 0, fmod::DSPResamplerLinear));
+        try!(renderer.system.set_DSP_buffer_size(block_length, 2));
+        match target {
+            RenderToFile(ref file_name) => try!(renderer.system.init_with_output_file(max_channels, FMOD_INIT_STREAM_FROM_UPDATE,
+                file_name.as_slice())),
+            RenderToMemory => {
+                try!(renderer.system.init_with_parameters(max_channels, FMOD_INIT_STREAM_FROM_UPDATE));
+                try!(renderer.add_capture_dsp());
+            }
+        }
+        Ok(renderer)
+    }
+
+    fn add_capture_dsp(&mut self) -> Result<(), FmodError> {
+        let mut description = ffi::FMOD_DSP_DESCRIPTION {
+            name: [0i8, ..32],
+            version: 0u32,
+            channels: 0i32,
+            create: None,
+            release: None,
+            reset: None,
+            read: Some(capture_callback),
+            set_position: None,
+            num_parameters: 0i32,
+            param_desc: ::std::ptr::mut_null(),
+            set_parameter: None,
+            get_parameter: None,
+            config: None,
+            config_width: 0i32,
+            config_height: 0i32,
+            user_data: unsafe { transmute::<&mut Vec<f32>, *mut c_void>(&mut *self.data) }
+        };
+        let system = fmod_sys::get_ffi(&self.system);
+
+        match unsafe { ffi::FMOD_System_CreateDSP(system, &mut description, &mut self.capture_dsp) } {
+            fmod::Ok => error::check(unsafe { ffi::FMOD_System_AddDSP(system, self.capture_dsp, ::std::ptr::mut_null()) }),
+            e => Err(FmodError::new(e))
+        }
+    }
+
+    /// Releases the system. With [`RenderToFile`](enum.RenderTarget.html), the WAV file is finished when the system is closed: if sounds
+    /// or channels created from [`OfflineRenderer::get_system`](struct.OfflineRenderer.html#method.get_system) are still alive, this returns
+    /// a [`StillInUse`](enum.ErrorKind.html) error and the file is only finished once they are dropped.
+    pub fn release(&mut self) -> Result<(), FmodError> {
+        if self.capture_dsp.is_not_null() {
+            unsafe {
+                ffi::FMOD_DSP_Remove(self.capture_dsp);
+                ffi::FMOD_DSP_Release(self.capture_dsp);
+            }
+            self.capture_dsp = ::std::ptr::mut_null();
+        }
+        self.system.release()
+    }
+
+    /// System to create and play sounds with. Don't call update on it, use [`OfflineRenderer::step`](struct.OfflineRenderer.html#method.step) instead.
+    pub fn get_system<'r>(&'r self) -> &'r FmodSys {
+        &self.system
+    }
+
+    /// Mixes one block of `block_length` samples.
+    pub fn step(&mut self) -> Result<(), FmodError> {
+        try!(self.system.update());
+        self.rendered += self.block_length as u64;
+        Ok(())
+    }
+
+    /// Mixes at least `duration_ms` milliseconds, rounded up to a whole number of blocks.
+    pub fn render(&mut self, duration_ms: u32) -> Result<(), FmodError> {
+        let target = self.rendered + duration_ms as u64 * self.sample_rate as u64 / 1000u64;
+
+        while self.rendered < target {
+            try!(self.step());
+        }
+        Ok(())
+    }
+
+    /// Number of samples per channel mixed so far.
+    pub fn get_rendered_samples(&self) -> u64 {
+        self.rendered
+    }
+
+    pub fn get_sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    pub fn get_channels(&self) -> i32 {
+        self.channels
+    }
+
+    /// Interleaved mix captured so far. Always empty when rendering to a file.
+    pub fn get_data<'r>(&'r self) -> &'r [f32] {
+        self.data.as_slice()
+    }
+
+    /// Returns the captured mix and clears it.
+    pub fn take_data(&mut self) -> Vec<f32> {
+        ::std::mem::replace(&mut *self.data, Vec::new())
+    }
+}
@@ -0,0 +1,162 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use enums::*;
+use types::FmodTimeUnit;
+use libc::{c_int, c_uint, c_void};
+use std::mem::transmute;
+use std::raw;
+
+/// Buffer to fill in [`PcmSource::read`](trait.PcmSource.html#tymethod.read), typed after the format of the sound. Samples are interleaved.
+pub enum PcmBuffer<'a> {
+    /// 8bit integer PCM data.
+    BufferPCM8(&'a mut [i8]),
+    /// 16bit integer PCM data.
+    BufferPCM16(&'a mut [i16]),
+    /// 24bit integer PCM data, 3 little endian bytes per sample.
+    BufferPCM24(&'a mut [u8]),
+    /// 32bit integer PCM data.
+    BufferPCM32(&'a mut [i32]),
+    /// 32bit floating point PCM data.
+    BufferPCMFloat(&'a mut [f32])
+}
+
+impl<'a> PcmBuffer<'a> {
+    /// Number of samples in the buffer, all channels included.
+    pub fn len(&self) -> uint {
+        match *self {
+            BufferPCM8(ref b) => b.len(),
+            BufferPCM16(ref b) => b.len(),
+            BufferPCM24(ref b) => b.len() / 3,
+            BufferPCM32(ref b) => b.len(),
+            BufferPCMFloat(ref b) => b.len()
+        }
+    }
+}
+
+/// Generates the data of a sound created with [`FmodSys::create_user_sound`](struct.FmodSys.html#method.create_user_sound).
+///
+/// Streams call it from the FMOD stream thread while playing, samples only when they're created.
+pub trait PcmSource: Send {
+    /// Fills `data` with the next samples of the sound.
+    fn read(&mut self, data: PcmBuffer) -> fmod::Result;
+
+    /// Called when the position of the sound is changed, and when it is opened. Does nothing by default.
+    #[allow(unused_variable)]
+    fn set_position(&mut self, sub_sound: i32, position: u32, postype: FmodTimeUnit) -> fmod::Result {
+        fmod::Ok
+    }
+}
+
+/* Pointed by the user data of the sounds created by FmodSys::create_user_sound. */
+pub struct PcmSourceData {
+    source: Box<PcmSource + Send>,
+    format: fmod::SoundFormat
+}
+
+impl PcmSourceData {
+    pub fn new(source: Box<PcmSource + Send>, format: fmod::SoundFormat) -> PcmSourceData {
+        PcmSourceData{source: source, format: format}
+    }
+}
+
+/// Size of a sample in bytes, or None if the format isn't PCM.
+pub fn sample_size(format: fmod::SoundFormat) -> Option<u32> {
+    match format {
+        fmod::SoundFormatPCM8 => Some(1),
+        fmod::SoundFormatPCM16 => Some(2),
+        fmod::SoundFormatPCM24 => Some(3),
+        fmod::SoundFormatPCM32 | fmod::SoundFormatPCMFloat => Some(4),
+        _ => None
+    }
+}
+
+unsafe fn buffer<'r, T>(data: *mut c_void, data_len: c_uint, size: uint) -> &'r mut [T] {
+    transmute(raw::Slice{data: data as *const T, len: data_len as uint / size})
+}
+
+unsafe fn get_source<'r>(sound: *mut ffi::FMOD_SOUND) -> Option<&'r mut PcmSourceData> {
+    if sound.is_null() {
+        return None;
+    }
+    let mut tmp = ::std::ptr::mut_null();
+
+    ffi::FMOD_Sound_GetUserData(sound, &mut tmp);
+    if tmp.is_null() {
+        None
+    } else {
+        Some(transmute(tmp))
+    }
+}
+
+pub extern "C" fn pcm_read_callback(sound: *mut ffi::FMOD_SOUND, data: *mut c_void, data_len: c_uint) -> fmod::Result {
+    unsafe {
+        match get_source(sound) {
+            Some(s) => {
+                let data = match s.format {
+                    fmod::SoundFormatPCM8 => BufferPCM8(buffer(data, data_len, 1)),
+                    fmod::SoundFormatPCM16 => BufferPCM16(buffer(data, data_len, 2)),
+                    fmod::SoundFormatPCM24 => BufferPCM24(buffer(data, data_len - data_len % 3, 1)),
+                    fmod::SoundFormatPCM32 => BufferPCM32(buffer(data, data_len, 4)),
+                    fmod::SoundFormatPCMFloat => BufferPCMFloat(buffer(data, data_len, 4)),
+                    _ => return fmod::ErrFormat
+                };
+
+                s.source.read(data)
+            }
+            None => fmod::Ok
+        }
+    }
+}
+
+pub extern "C" fn pcm_set_pos_callback(sound: *mut ffi::FMOD_SOUND, sub_sound: c_int, position: c_uint, postype: ffi::FMOD_TIMEUNIT) -> fmod::Result {
+    unsafe {
+        match get_source(sound) {
+            Some(s) => s.source.set_position(sub_sound, position, FmodTimeUnit(postype)),
+            None => fmod::Ok
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use enums::*;
+    use super::sample_size;
+
+    #[test]
+    fn sample_size_of_pcm_formats() {
+        assert_eq!(sample_size(fmod::SoundFormatPCM8), Some(1));
+        assert_eq!(sample_size(fmod::SoundFormatPCM16), Some(2));
+        assert_eq!(sample_size(fmod::SoundFormatPCM24), Some(3));
+        assert_eq!(sample_size(fmod::SoundFormatPCM32), Some(4));
+        assert_eq!(sample_size(fmod::SoundFormatPCMFloat), Some(4));
+    }
+
+    #[test]
+    fn sample_size_of_compressed_formats() {
+        assert_eq!(sample_size(fmod::SoundFormatNone), None);
+        assert_eq!(sample_size(fmod::SoundFormatMPEG), None);
+    }
+}
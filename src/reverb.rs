@@ -29,12 +29,13 @@ use vector;
 use reverb_properties;
 use fmod_sys;
 use fmod_sys::FmodMemoryUsageDetails;
-use std::mem::transmute;
-use libc::{c_void};
+use error;
+use error::FmodError;
+use std::any::Any;
 use std::default::Default;
 
-pub fn from_ptr(reverb: *mut ffi::FMOD_REVERB) -> Reverb {
-    Reverb{reverb: reverb}
+pub fn from_ptr(reverb: *mut ffi::FMOD_REVERB, system: fmod_sys::SystemRef) -> Reverb {
+    Reverb{reverb: reverb, system: system}
 }
 
 pub fn get_ffi(reverb: Reverb) -> *mut ffi::FMOD_REVERB {
@@ -43,107 +44,103 @@ pub fn get_ffi(reverb: Reverb) -> *mut ffi::FMOD_REVERB {
 
 /// Reverb object
 pub struct Reverb {
-    reverb: *mut ffi::FMOD_REVERB
+    reverb: *mut ffi::FMOD_REVERB,
+    system: fmod_sys::SystemRef
 }
 
 impl Drop for Reverb {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl Reverb {
-    pub fn release(&mut self) -> fmod::Result {
+    pub fn release(&mut self) -> Result<(), FmodError> {
         if self.reverb !=::std::ptr::mut_null() {
             match unsafe { ffi::FMOD_Reverb_Release(self.reverb) } {
                 fmod::Ok => {
+                    self.system.remove_user_data(self.reverb as uint);
                     self.reverb = ::std::ptr::mut_null();
-                    fmod::Ok
+                    Ok(())
                 }
-                e => e
+                e => Err(FmodError::new(e))
             }
         } else {
-            fmod::Ok
+            Ok(())
         }
     }
 
-    pub fn set_3D_attributes(&self, position: vector::FmodVector, min_distance: f32, max_distance: f32) -> fmod::Result {
+    pub fn set_3D_attributes(&self, position: vector::FmodVector, min_distance: f32, max_distance: f32) -> Result<(), FmodError> {
         let t_position = vector::get_ffi(&position);
 
-        unsafe { ffi::FMOD_Reverb_Set3DAttributes(self.reverb, &t_position, min_distance, max_distance) }
+        error::check(unsafe { ffi::FMOD_Reverb_Set3DAttributes(self.reverb, &t_position, min_distance, max_distance) })
     }
 
-    pub fn get_3D_attributes(&self) -> Result<(vector::FmodVector, f32, f32), fmod::Result> {
+    pub fn get_3D_attributes(&self) -> Result<(vector::FmodVector, f32, f32), FmodError> {
         let mut position = vector::get_ffi(&vector::FmodVector::new());
         let mut min_distance = 0f32;
         let mut max_distance = 0f32;
 
         match unsafe { ffi::FMOD_Reverb_Get3DAttributes(self.reverb, &mut position, &mut min_distance, &mut max_distance) } {
             fmod::Ok => Ok((vector::from_ptr(position), min_distance, max_distance)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_properties(&self, reverb_properties: reverb_properties::ReverbProperties) -> fmod::Result {
+    pub fn set_properties(&self, reverb_properties: reverb_properties::ReverbProperties) -> Result<(), FmodError> {
         let t_reverb_properties = reverb_properties::get_ffi(reverb_properties);
 
-        unsafe { ffi::FMOD_Reverb_SetProperties(self.reverb, &t_reverb_properties) }
+        error::check(unsafe { ffi::FMOD_Reverb_SetProperties(self.reverb, &t_reverb_properties) })
     }
 
-    pub fn get_properties(&self, reverb_properties: reverb_properties::ReverbProperties) -> Result<reverb_properties::ReverbProperties, fmod::Result> {
+    pub fn get_properties(&self, reverb_properties: reverb_properties::ReverbProperties) -> Result<reverb_properties::ReverbProperties, FmodError> {
         let mut t_reverb_properties = reverb_properties::get_ffi(reverb_properties);
 
         match unsafe { ffi::FMOD_Reverb_GetProperties(self.reverb, &mut t_reverb_properties) } {
             fmod::Ok => Ok(reverb_properties::from_ptr(t_reverb_properties)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_active(&self, active: bool) -> fmod::Result {
+    pub fn set_active(&self, active: bool) -> Result<(), FmodError> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_Reverb_SetActive(self.reverb, t_active) }
+        error::check(unsafe { ffi::FMOD_Reverb_SetActive(self.reverb, t_active) })
     }
 
-    pub fn get_active(&self) -> Result<bool, fmod::Result> {
+    pub fn get_active(&self) -> Result<bool, FmodError> {
         let mut active = 0i32;
 
         match unsafe { ffi::FMOD_Reverb_GetActive(self.reverb, &mut active) } {
             fmod::Ok => Ok(active == 1),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
         match unsafe { ffi::FMOD_Reverb_GetMemoryInfo(self.reverb, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
             fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_user_data<T>(&self, user_data: &mut T) -> fmod::Result {
-        unsafe { ffi::FMOD_Reverb_SetUserData(self.reverb, transmute(user_data)) }
+    /// Attaches `user_data` to the reverb, replacing and dropping the previous one. It's dropped when the reverb is released.
+    ///
+    /// Fails on a reverb whose system was obtained inside a callback, as it isn't linked to its system.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.system.set_user_data(self.reverb as uint, false, user_data)
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, fmod::Result> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::mut_null();
-
-            match ffi::FMOD_Reverb_GetUserData(self.reverb, &mut user_data) {
-                fmod::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    Ok(tmp)
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the data attached to the reverb and returns its result. Returns None if no data is attached or if it isn't a `T`.
+    pub fn with_user_data<T: 'static, R>(&self, f: |&T| -> R) -> Option<R> {
+        self.system.with_user_data(self.reverb as uint, f)
     }
 }
\ No newline at end of file
@@ -74,8 +74,8 @@ fn main() {
     };
 
     match fmod.init() {
-        fmod::Ok => {}
-        e => {
+        Ok(_) => {}
+        Err(e) => {
             fmod.release();
             fail!("FmodSys.init failed : {}", e);
         }
@@ -87,8 +87,8 @@ fn main() {
                     };
 
     match sound.play_to_the_end() {
-        fmod::Ok => {println!("Ok !");}
-        err => {fail!("Error code : {}", err);}
+        Ok(_) => {println!("Ok !");}
+        Err(err) => {fail!("Error code : {}", err);}
     };
 }
 ```
@@ -122,13 +122,15 @@ Here is the list of all modules :
 #![allow(dead_code)]
 #![allow(uppercase_variables)]
 
-#![feature(globs)]
+#![feature(globs, macro_rules, unboxed_closures, overloaded_calls)]
 
 extern crate libc;
 
-pub use channel::{Channel, FmodSpeakerMixOptions, FmodReverbChannelProperties};
-pub use fmod_sys::{FmodSys, FmodGuid, FmodSoftwareFormat, FmodAdvancedSettings, FmodOutputHandle, FmodCreateSoundexInfo, FmodMemoryUsageDetails};
-pub use sound::{Sound, FmodTag, FmodSyncPoint};
+pub use channel::{Channel, FmodSpeakerMixOptions, FmodReverbChannelProperties, ChannelEvent, End, VirtualVoice, SyncPoint, Occlusion,
+    ChannelState, ChannelPlaying, ChannelPaused, ChannelVirtual, ChannelStopped, ChannelStolen, ChannelSettings, ResumableChannel};
+pub use fmod_sys::{FmodSys, FmodGuid, FmodSoftwareFormat, FmodAdvancedSettings, FmodOutputHandle, FmodCreateSoundexInfo, FmodMemoryUsageDetails, FmodCodecDescription,
+    SystemEvent, DeviceListChanged, DeviceLost, MemoryAllocationFailed, ThreadCreated, ThreadDestroyed, BadDspConnection, BadDspLevel};
+pub use sound::{Sound, BorrowedSound, FmodTag, FmodSyncPoint, SoundLock, SampleReader, Sample, SamplePCM8, SamplePCM16, SamplePCM24, SamplePCM32, SamplePCMFloat};
 pub use channel_group::{ChannelGroup};
 pub use sound_group::SoundGroup;
 pub use dsp::{Dsp, DspParameterDesc, DspDescription, DspState};
@@ -137,6 +139,20 @@ pub use reverb::Reverb;
 pub use reverb_properties::ReverbProperties;
 pub use vector::FmodVector;
 pub use geometry::Geometry;
+pub use codec::{Codec, CodecFile, CodecWaveFormat, CodecTag};
+pub use file_system::{FileSystem, AsyncReadInfo, FileObserver, FileStatsObserver, FileStats};
+pub use error::{FmodError, ErrorKind, FmodFailure, DeadHandle, InvalidFlags, OutOfRange, StillInUse};
+pub use sound_builder::SoundBuilder;
+pub use load_handle::LoadHandle;
+pub use dsp_processor::{DspProcessor, DspEffect, DspParam};
+pub use dsp_effects::{OscillatorDsp, OscillatorWaveform, WaveformSine, WaveformSquare, WaveformSawUp, WaveformSawDown, WaveformTriangle, WaveformNoise,
+    LowPassDsp, ITLowPassDsp, HighPassDsp, EchoDsp, DelayDsp, FlangeDsp, TremoloDsp, DistortionDsp, NormalizeDsp, ParameqDsp, PitchShiftDsp, PitchShiftFFTSize,
+    FFTSize256, FFTSize512, FFTSize1024, FFTSize2048, FFTSize4096, ChorusDsp,
+    ITEchoDsp, CompressorDsp, SfxReverbDsp, LowPassSimpleDsp, HighPassSimpleDsp};
+pub use dsp_graph::{DspGraph, DspNode, DspEdge};
+pub use pcm_source::{PcmSource, PcmBuffer, BufferPCM8, BufferPCM16, BufferPCM24, BufferPCM32, BufferPCMFloat};
+pub use command_queue::{CommandSender, SoundId, ChannelId};
+pub use offline::{OfflineRenderer, RenderTarget, RenderToFile, RenderToMemory};
 
 mod ffi;
 mod sound;
@@ -150,6 +166,18 @@ mod geometry;
 mod vector;
 mod reverb;
 mod reverb_properties;
+mod file_system;
+mod codec;
+mod offline;
+mod error;
+mod command_queue;
+mod strings;
+mod sound_builder;
+mod load_handle;
+mod pcm_source;
+mod dsp_processor;
+mod dsp_effects;
+mod dsp_graph;
 pub mod types;
 pub mod enums;
 pub mod callbacks;
@@ -34,12 +34,20 @@ use std::io::timer::sleep;
 use vector;
 use fmod_sys;
 use fmod_sys::{FmodMemoryUsageDetails, FmodSys};
+use error;
+use error::FmodError;
+use strings;
+use load_handle::LoadState;
+use pcm_source::PcmSourceData;
+use std::any::Any;
 use std::mem::transmute;
 use std::io::File;
 use std::mem;
-use std::io::BufferedWriter;
+use std::io::{BufferedWriter, IoResult};
 use std::slice;
+use std::raw;
 use std::default::Default;
+use std::kinds::marker::ContravariantLifetime;
 
 struct RiffChunk {
     id: [c_char, ..4],
@@ -60,6 +68,35 @@ struct DataChunk {
     chunk: RiffChunk
 }
 
+fn write_wav_header(buf: &mut BufferedWriter<File>, wav_header: &WavHeader, fmt_chunk: &FmtChunk, data_chunk: &DataChunk) -> IoResult<()> {
+    /* wav header */
+    for it in range(0u, 4u) {
+        try!(buf.write_i8(wav_header.chunk.id[it]));
+    }
+    try!(buf.write_le_i32(wav_header.chunk.size));
+    for it in range(0u, 4u) {
+        try!(buf.write_i8(wav_header.riff_type[it]));
+    }
+
+    /* wav chunk */
+    for it in range(0u, 4u) {
+        try!(buf.write_i8(fmt_chunk.chunk.id[it]));
+    }
+    try!(buf.write_le_i32(fmt_chunk.chunk.size));
+    try!(buf.write_le_u16(fmt_chunk.w_format_tag));
+    try!(buf.write_le_u16(fmt_chunk.n_channels));
+    try!(buf.write_le_u32(fmt_chunk.n_samples_per_sec));
+    try!(buf.write_le_u32(fmt_chunk.n_avg_bytes_per_sec));
+    try!(buf.write_le_u16(fmt_chunk.n_block_align));
+    try!(buf.write_le_u16(fmt_chunk.w_bits_per_sample));
+
+    /* wav data chunk */
+    for it in range(0u, 4u) {
+        try!(buf.write_i8(data_chunk.chunk.id[it]));
+    }
+    buf.write_le_i32(data_chunk.chunk.size)
+}
+
 struct WavHeader {
     chunk: RiffChunk,
     riff_type: [c_char, ..4]
@@ -147,11 +184,218 @@ impl FmodTag {
     }
 }
 
+/// Interleaved sample returned by [`SampleReader`](struct.SampleReader.html), in the format of the sound.
+#[deriving(Show, PartialEq, Clone)]
+pub enum Sample {
+    /// 8bit integer PCM data.
+    SamplePCM8(i8),
+    /// 16bit integer PCM data.
+    SamplePCM16(i16),
+    /// 24bit integer PCM data, sign extended.
+    SamplePCM24(i32),
+    /// 32bit integer PCM data.
+    SamplePCM32(i32),
+    /// 32bit floating point PCM data.
+    SamplePCMFloat(f32)
+}
+
+impl Sample {
+    /// Converts the sample to a float between -1.0 and 1.0.
+    pub fn to_f32(&self) -> f32 {
+        match *self {
+            SamplePCM8(v) => v as f32 / 128f32,
+            SamplePCM16(v) => v as f32 / 32768f32,
+            SamplePCM24(v) => v as f32 / 8388608f32,
+            SamplePCM32(v) => v as f32 / 2147483648f32,
+            SamplePCMFloat(v) => v
+        }
+    }
+}
+
+unsafe fn locked_region<'r, T>(ptr: *mut c_void, len: u32) -> &'r mut [T] {
+    let len = if ptr.is_null() {
+        0u
+    } else {
+        len as uint / mem::size_of::<T>()
+    };
+
+    transmute(raw::Slice{data: ptr as *const T, len: len})
+}
+
+/// Sample data of a sound locked by [`Sound::lock`](struct.Sound.html#method.lock), unlocked when dropped.
+///
+/// The data is made of two regions: the second one is only used when the locked range wraps around the end of the sound.
+pub struct SoundLock<'a> {
+    sound: &'a Sound,
+    format: fmod::SoundFormat,
+    ptr1: *mut c_void,
+    ptr2: *mut c_void,
+    len1: u32,
+    len2: u32
+}
+
+impl<'a> Drop for SoundLock<'a> {
+    fn drop(&mut self) {
+        let _ = self.release();
+    }
+}
+
+impl<'a> SoundLock<'a> {
+    fn release(&mut self) -> Result<(), FmodError> {
+        if self.ptr1.is_not_null() {
+            let ret = error::check(unsafe { ffi::FMOD_Sound_Unlock(self.sound.sound, self.ptr1, self.ptr2, self.len1, self.len2) });
+
+            self.ptr1 = ::std::ptr::mut_null();
+            self.ptr2 = ::std::ptr::mut_null();
+            ret
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Unlocks the data now, to get the error FMOD may return.
+    pub fn unlock(mut self) -> Result<(), FmodError> {
+        self.release()
+    }
+
+    pub fn get_format(&self) -> fmod::SoundFormat {
+        self.format
+    }
+
+    /// Both locked regions as raw bytes.
+    pub fn as_mut_slices<'r>(&'r mut self) -> (&'r mut [u8], &'r mut [u8]) {
+        unsafe { (locked_region(self.ptr1, self.len1), locked_region(self.ptr2, self.len2)) }
+    }
+
+    /// Both locked regions as 16 bits samples. Returns an ErrFormat error if the sound isn't in the PCM16 format.
+    pub fn as_i16_slices<'r>(&'r mut self) -> Result<(&'r mut [i16], &'r mut [i16]), FmodError> {
+        match self.format {
+            fmod::SoundFormatPCM16 => Ok(unsafe { (locked_region(self.ptr1, self.len1), locked_region(self.ptr2, self.len2)) }),
+            _ => Err(FmodError::new(fmod::ErrFormat))
+        }
+    }
+
+    /// Both locked regions as float samples. Returns an ErrFormat error if the sound isn't in the PCMFloat format.
+    pub fn as_f32_slices<'r>(&'r mut self) -> Result<(&'r mut [f32], &'r mut [f32]), FmodError> {
+        match self.format {
+            fmod::SoundFormatPCMFloat => Ok(unsafe { (locked_region(self.ptr1, self.len1), locked_region(self.ptr2, self.len2)) }),
+            _ => Err(FmodError::new(fmod::ErrFormat))
+        }
+    }
+}
+
+/// Sound created by [`FmodSys::create_sound_from_static`](struct.FmodSys.html#method.create_sound_from_static). FMOD reads
+/// the borrowed data directly, so it can't outlive it.
+pub struct BorrowedSound<'a> {
+    sound: Sound,
+    marker: ContravariantLifetime<'a>
+}
+
+pub fn from_borrowed<'a>(sound: Sound) -> BorrowedSound<'a> {
+    BorrowedSound{sound: sound, marker: ContravariantLifetime}
+}
+
+impl<'a> Deref<Sound> for BorrowedSound<'a> {
+    fn deref<'r>(&'r self) -> &'r Sound {
+        &self.sound
+    }
+}
+
+/* No DerefMut: a `&mut Sound` would let the sound be swapped out of its wrapper and outlive the borrowed data. */
+impl<'a> BorrowedSound<'a> {
+    /// Releases the sound before the wrapper is dropped. See [`Sound::release`](struct.Sound.html#method.release).
+    pub fn release(&mut self) -> Result<(), FmodError> {
+        self.sound.release()
+    }
+
+    /// See [`Sound::set_user_data`](struct.Sound.html#method.set_user_data).
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.sound.set_user_data(user_data)
+    }
+}
+
+/// Iterator over the decoded samples of a sound, created by [`Sound::samples`](struct.Sound.html#method.samples).
+///
+/// The iteration also stops when decoding fails: check [`SampleReader::get_error`](struct.SampleReader.html#method.get_error)
+/// to tell a failure from the end of the sound.
+pub struct SampleReader<'a> {
+    sound: &'a Sound,
+    format: fmod::SoundFormat,
+    channels: i32,
+    sample_size: uint,
+    buffer: Vec<u8>,
+    pos: uint,
+    len: uint,
+    error: Option<FmodError>
+}
+
+impl<'a> SampleReader<'a> {
+    pub fn get_format(&self) -> fmod::SoundFormat {
+        self.format
+    }
+
+    /// Number of interleaved channels.
+    pub fn get_channels(&self) -> i32 {
+        self.channels
+    }
+
+    /// Error which stopped the iteration, None if it reached the end of the sound or is still running.
+    pub fn get_error(&self) -> Option<FmodError> {
+        self.error.clone()
+    }
+
+    fn fill(&mut self) -> bool {
+        let left = self.len - self.pos;
+
+        for it in range(0u, left) {
+            *self.buffer.get_mut(it) = self.buffer[self.pos + it];
+        }
+        self.pos = 0;
+        self.len = left;
+        match self.sound.read_data(self.buffer.mut_slice_from(left)) {
+            Ok(read) => {
+                self.len += read as uint;
+                read > 0
+            }
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+}
+
+impl<'a> Iterator<Sample> for SampleReader<'a> {
+    fn next(&mut self) -> Option<Sample> {
+        if self.error.is_some() || (self.pos + self.sample_size > self.len && (!self.fill() || self.sample_size > self.len)) {
+            return None;
+        }
+        let b = self.buffer.slice(self.pos, self.pos + self.sample_size);
+        let sample = match self.format {
+            fmod::SoundFormatPCM8 => SamplePCM8(b[0] as i8),
+            fmod::SoundFormatPCM16 => SamplePCM16((b[0] as u16 | b[1] as u16 << 8) as i16),
+            fmod::SoundFormatPCM24 => SamplePCM24((b[0] as i32 << 8 | b[1] as i32 << 16 | b[2] as i32 << 24) >> 8),
+            fmod::SoundFormatPCM32 => SamplePCM32(b[0] as i32 | b[1] as i32 << 8 | b[2] as i32 << 16 | b[3] as i32 << 24),
+            _ => SamplePCMFloat(unsafe { transmute::<u32, f32>(b[0] as u32 | b[1] as u32 << 8 | b[2] as u32 << 16 | b[3] as u32 << 24) })
+        };
+
+        self.pos += self.sample_size;
+        Some(sample)
+    }
+}
+
 /// Sound object
 pub struct Sound {
     sound: *mut ffi::FMOD_SOUND,
     can_be_deleted: bool,
-    user_data: ffi::SoundData
+    user_data: ffi::SoundData,
+    /* data streamed by FMOD for the sounds created by FmodSys::create_sound_from_memory */
+    memory: Vec<u8>,
+    /* pointed by the FMOD user data of the sounds created by FmodSys::load_sound_async */
+    load_state: Option<Box<LoadState>>,
+    /* pointed by the FMOD user data of the sounds created by FmodSys::create_user_sound */
+    pcm_source: Option<Box<PcmSourceData>>,
+    system: fmod_sys::SystemRef
 }
 
 pub fn get_fffi<'r>(sound: &'r mut Sound) -> &'r mut *mut ffi::FMOD_SOUND {
@@ -162,12 +406,41 @@ pub fn get_ffi(sound: &Sound) -> *mut ffi::FMOD_SOUND {
     sound.sound
 }
 
-pub fn from_ptr(sound: *mut ffi::FMOD_SOUND) -> Sound {
-    Sound{sound: sound, can_be_deleted: false, user_data: ffi::SoundData::new()}
+pub fn from_ptr(sound: *mut ffi::FMOD_SOUND, system: fmod_sys::SystemRef) -> Sound {
+    Sound{sound: sound, can_be_deleted: false, user_data: ffi::SoundData::new(), memory: Vec::new(), load_state: None, pcm_source: None, system: system}
+}
+
+pub fn from_ptr_first(sound: *mut ffi::FMOD_SOUND, system: fmod_sys::SystemRef) -> Sound {
+    Sound{sound: sound, can_be_deleted: true, user_data: ffi::SoundData::new(), memory: Vec::new(), load_state: None, pcm_source: None, system: system}
+}
+
+pub fn set_memory(sound: &mut Sound, memory: Vec<u8>) {
+    sound.memory = memory;
+}
+
+pub fn set_load_state(sound: &mut Sound, state: Box<LoadState>) -> *mut c_void {
+    let ptr = &*state as *const LoadState as *mut c_void;
+
+    sound.load_state = Some(state);
+    ptr
+}
+
+pub fn set_pcm_source(sound: &mut Sound, source: Box<PcmSourceData>) -> *mut c_void {
+    let ptr = &*source as *const PcmSourceData as *mut c_void;
+
+    sound.pcm_source = Some(source);
+    ptr
+}
+
+pub fn get_load_state<'r>(sound: &'r Sound) -> &'r LoadState {
+    match sound.load_state {
+        Some(ref state) => &**state,
+        None => fail!("sound not created by FmodSys::load_sound_async")
+    }
 }
 
-pub fn from_ptr_first(sound: *mut ffi::FMOD_SOUND) -> Sound {
-    Sound{sound: sound, can_be_deleted: true, user_data: ffi::SoundData::new()}
+pub fn detach_system(sound: &mut Sound) {
+    sound.system = fmod_sys::SystemRef::none();
 }
 
 pub fn get_user_data<'r>(sound: &'r mut Sound) -> &'r mut ffi::SoundData {
@@ -176,63 +449,64 @@ pub fn get_user_data<'r>(sound: &'r mut Sound) -> &'r mut ffi::SoundData {
 
 impl Drop for Sound {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl Sound {
-    pub fn get_system_object(&self) -> Result<FmodSys, fmod::Result> {
+    pub fn get_system_object(&self) -> Result<FmodSys, FmodError> {
         let mut system = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Sound_GetSystemObject(self.sound, &mut system) } {
-            fmod::Ok => Ok(fmod_sys::from_ptr(system)),
-            e => Err(e)
+            fmod::Ok => Ok(fmod_sys::from_ptr(system, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn release(&mut self) -> fmod::Result {
+    pub fn release(&mut self) -> Result<(), FmodError> {
         if self.can_be_deleted && self.sound.is_not_null() {
             match unsafe { ffi::FMOD_Sound_Release(self.sound) } {
                 fmod::Ok => {
+                    self.system.remove_user_data(self.sound as uint);
                     self.sound = ::std::ptr::mut_null();
-                    fmod::Ok
+                    Ok(())
                 }
-                e => e
+                e => Err(FmodError::new(e))
             }
         } else {
-            fmod::Ok
+            Ok(())
         }
     }
 
-    pub fn play(&self) -> Result<channel::Channel, fmod::Result> {
+    pub fn play(&self) -> Result<channel::Channel, FmodError> {
         let mut channel = ::std::ptr::mut_null();
 
         match match self.get_system_object() {
             Ok(s) => { 
                 unsafe { ffi::FMOD_System_PlaySound(fmod_sys::get_ffi(&s), fmod::ChannelFree, self.sound, 0, &mut channel) }
             }
-            Err(e) => e
+            Err(e) => return Err(e)
         } {
-            fmod::Ok => Ok(channel::from_ptr(channel)),
-            e => Err(e)
+            fmod::Ok => Ok(channel::from_new_ptr(channel, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn play_with_parameters(&self, paused: bool, channel: &mut channel::Channel) -> fmod::Result {
+    pub fn play_with_parameters(&self, paused: bool, channel: &mut channel::Channel) -> Result<(), FmodError> {
         let mut chan = channel::get_ffi(channel);
         
         match self.get_system_object() {
             Ok(s) => { 
-                unsafe { ffi::FMOD_System_PlaySound(fmod_sys::get_ffi(&s), fmod::ChannelReUse, self.sound, match paused {
+                error::check(unsafe { ffi::FMOD_System_PlaySound(fmod_sys::get_ffi(&s), fmod::ChannelReUse, self.sound, match paused {
                     true => 1,
                     false => 0
-                }, &mut chan) }
+                }, &mut chan) })
             }
-            Err(e) => e
+            Err(e) => Err(e)
         }
     }
 
-    pub fn play_to_the_end(&self) -> fmod::Result {
+    pub fn play_to_the_end(&self) -> Result<(), FmodError> {
         match self.play() {
             Ok(mut chan) => {
                 loop {
@@ -244,21 +518,21 @@ impl Sound {
                                 break;
                             }
                         },
-                        Err(e) => return e,
+                        Err(e) => return Err(e),
                     }
                 }
                 chan.release();
-                fmod::Ok
+                Ok(())
             }
-            Err(err) => err,
+            Err(err) => Err(err),
         }
     }
 
-    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetDefaults(self.sound, frequency, volume, pan, priority) }
+    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetDefaults(self.sound, frequency, volume, pan, priority) })
     }
 
-    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), fmod::Result> {
+    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), FmodError> {
         let mut frequency = 0f32;
         let mut volume = 0f32;
         let mut pan = 0f32;
@@ -266,65 +540,65 @@ impl Sound {
 
         match unsafe { ffi::FMOD_Sound_GetDefaults(self.sound, &mut frequency, &mut volume, &mut pan, &mut priority) } {
             fmod::Ok => Ok((frequency, volume, pan, priority)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_variations(&self, frequency_var: f32, volume_var: f32, pan_var: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetVariations(self.sound, frequency_var, volume_var, pan_var) }
+    pub fn set_variations(&self, frequency_var: f32, volume_var: f32, pan_var: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetVariations(self.sound, frequency_var, volume_var, pan_var) })
     }
 
-    pub fn get_variations(&self) -> Result<(f32, f32, f32), fmod::Result> {
+    pub fn get_variations(&self) -> Result<(f32, f32, f32), FmodError> {
         let mut frequency_var = 0f32;
         let mut volume_var = 0f32;
         let mut pan_var = 0f32;
 
         match unsafe { ffi::FMOD_Sound_GetVariations(self.sound, &mut frequency_var, &mut volume_var, &mut pan_var) } {
             fmod::Ok => Ok((frequency_var, volume_var, pan_var)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_min_max_distance(&self, min: f32, max: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_Set3DMinMaxDistance(self.sound, min, max) }
+    pub fn set_3D_min_max_distance(&self, min: f32, max: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_Set3DMinMaxDistance(self.sound, min, max) })
     }
 
-    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), fmod::Result> {
+    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), FmodError> {
         let mut max = 0f32;
         let mut min = 0f32;
 
         match unsafe { ffi::FMOD_Sound_Get3DMinMaxDistance(self.sound, &mut min, &mut max) } {
             fmod::Ok => Ok((min, max)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_cone_settings(&self, inside_cone_angle: f32, outside_cone_angle: f32, outside_volume: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_Set3DConeSettings(self.sound, inside_cone_angle, outside_cone_angle, outside_volume) }
+    pub fn set_3D_cone_settings(&self, inside_cone_angle: f32, outside_cone_angle: f32, outside_volume: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_Set3DConeSettings(self.sound, inside_cone_angle, outside_cone_angle, outside_volume) })
     }
 
-    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), fmod::Result> {
+    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), FmodError> {
         let mut inside_cone_angle = 0f32;
         let mut outside_cone_angle = 0f32;
         let mut outside_volume = 0f32;
 
         match unsafe { ffi::FMOD_Sound_Get3DConeSettings(self.sound, &mut inside_cone_angle, &mut outside_cone_angle, &mut outside_volume) } {
             fmod::Ok => Ok((inside_cone_angle, outside_cone_angle, outside_volume)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_3D_custom_rolloff(&self, points: Vec<vector::FmodVector>) -> fmod::Result {
+    pub fn set_3D_custom_rolloff(&self, points: Vec<vector::FmodVector>) -> Result<(), FmodError> {
         let mut points_vec = Vec::with_capacity(points.len());
 
         for tmp in points.move_iter() {
             points_vec.push(vector::get_ffi(&tmp));
         }
-        unsafe { ffi::FMOD_Sound_Set3DCustomRolloff(self.sound, points_vec.as_mut_ptr(), points_vec.len() as i32) }
+        error::check(unsafe { ffi::FMOD_Sound_Set3DCustomRolloff(self.sound, points_vec.as_mut_ptr(), points_vec.len() as i32) })
     }
 
     //to test
-    pub fn get_3D_custom_rolloff(&self, num_points: u32) -> Result<Vec<vector::FmodVector>, fmod::Result> {
+    pub fn get_3D_custom_rolloff(&self, num_points: u32) -> Result<Vec<vector::FmodVector>, FmodError> {
         let mut points_vec = Vec::with_capacity(num_points as uint);
         let mut pointer = points_vec.as_mut_ptr();
 
@@ -337,44 +611,40 @@ impl Sound {
                 }
                 Ok(points)
             }
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_sub_sound(&self, index: i32, sub_sound: Sound) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetSubSound(self.sound, index, sub_sound.sound) }
+    pub fn set_sub_sound(&self, index: i32, sub_sound: Sound) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetSubSound(self.sound, index, sub_sound.sound) })
     }
 
-    pub fn get_sub_sound(&self, index: i32) -> Result<Sound, fmod::Result> {
+    pub fn get_sub_sound(&self, index: i32) -> Result<Sound, FmodError> {
         let mut sub_sound = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Sound_GetSubSound(self.sound, index, &mut sub_sound) } {
-            fmod::Ok => Ok(from_ptr(sub_sound)),
-            e => Err(e)
+            fmod::Ok => Ok(from_ptr(sub_sound, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_name(&self, name_len: u32) -> Result<String, fmod::Result> {
-        let name = String::with_capacity(name_len as uint).into_string();
-
-        name.with_c_str(|c_name|{
-            match unsafe { ffi::FMOD_Sound_GetName(self.sound, c_name as *mut c_char, name_len as i32) } {
-                fmod::Ok => Ok(unsafe {::std::str::raw::from_c_str(c_name).clone() }),
-                e => Err(e)
-            }
-        })
+    pub fn get_name(&self) -> Result<String, FmodError> {
+        match strings::get_string(|name, name_len| unsafe { ffi::FMOD_Sound_GetName(self.sound, name, name_len) }) {
+            Ok(name) => Ok(name),
+            Err(e) => Err(FmodError::new(e))
+        }
     }
 
-    pub fn get_length(&self, FmodTimeUnit(length_type): FmodTimeUnit) -> Result<u32, fmod::Result> {
+    pub fn get_length(&self, FmodTimeUnit(length_type): FmodTimeUnit) -> Result<u32, FmodError> {
         let mut length = 0u32;
 
         match unsafe { ffi::FMOD_Sound_GetLength(self.sound, &mut length, length_type) } {
             fmod::Ok => Ok(length),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_format(&self) -> Result<(fmod::SoundType, fmod::SoundFormat, i32, i32), fmod::Result> {
+    pub fn get_format(&self) -> Result<(fmod::SoundType, fmod::SoundFormat, i32, i32), FmodError> {
         let mut _type = fmod::SoundTypeUnknown;
         let mut format = fmod::SoundFormatNone;
         let mut channels = 0i32;
@@ -382,41 +652,41 @@ impl Sound {
 
         match unsafe { ffi::FMOD_Sound_GetFormat(self.sound, &mut _type, &mut format, &mut channels, &mut bits) } {
             fmod::Ok => Ok((_type, format, channels, bits)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_sub_sounds(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_sub_sounds(&self) -> Result<i32, FmodError> {
         let mut num_sub_sound = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetNumSubSounds(self.sound, &mut num_sub_sound) } {
             fmod::Ok => Ok(num_sub_sound),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_tags(&self) -> Result<(i32, i32), fmod::Result> {
+    pub fn get_num_tags(&self) -> Result<(i32, i32), FmodError> {
         let mut num_tags = 0i32;
         let mut num_tags_updated = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetNumTags(self.sound, &mut num_tags, &mut num_tags_updated) } {
             fmod::Ok => Ok((num_tags, num_tags_updated)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     //to test if tag's data needs to be filled by user
-    pub fn get_tag(&self, name: String, index: i32) -> Result<FmodTag, fmod::Result> {
+    pub fn get_tag(&self, name: String, index: i32) -> Result<FmodTag, FmodError> {
         let mut tag = ffi::FMOD_TAG{_type: fmod::TagTypeUnknown, datatype: fmod::TagDataTypeBinary, name: ::std::ptr::mut_null(),
             data: ::std::ptr::mut_null(), datalen: 0, updated: 0};
 
         match unsafe { ffi::FMOD_Sound_GetTag(self.sound, name.into_string().with_c_str(|c_name|{c_name}), index, &mut tag) } {
             fmod::Ok => Ok(FmodTag::from_ptr(tag)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_open_state(&self) -> Result<(fmod::OpenState, u32, bool, bool), fmod::Result> {
+    pub fn get_open_state(&self) -> Result<(fmod::OpenState, u32, bool, bool), FmodError> {
         let mut open_state = fmod::OpenStateReady;
         let mut percent_buffered = 0u32;
         let mut starving = 0;
@@ -432,323 +702,299 @@ impl Sound {
                             } else {
                                 false
                             })),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_sound_group(&self, sound_group: sound_group::SoundGroup) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetSoundGroup(self.sound, sound_group::get_ffi(&sound_group)) }
+    pub fn set_sound_group(&self, sound_group: sound_group::SoundGroup) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetSoundGroup(self.sound, sound_group::get_ffi(&sound_group)) })
     }
 
-    pub fn get_sound_group(&self) -> Result<sound_group::SoundGroup, fmod::Result> {
+    pub fn get_sound_group(&self) -> Result<sound_group::SoundGroup, FmodError> {
         let mut sound_group = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Sound_GetSoundGroup(self.sound, &mut sound_group) } {
-            fmod::Ok => Ok(sound_group::from_ptr(sound_group)),
-            e => Err(e)
+            fmod::Ok => Ok(sound_group::from_ptr(sound_group, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_sync_points(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_sync_points(&self) -> Result<i32, FmodError> {
         let mut num_sync_points = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetNumSyncPoints(self.sound, &mut num_sync_points) } {
             fmod::Ok => Ok(num_sync_points),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_sync_point(&self, index: i32) -> Result<FmodSyncPoint, fmod::Result> {
+    pub fn get_sync_point(&self, index: i32) -> Result<FmodSyncPoint, FmodError> {
         let mut sync_point = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Sound_GetSyncPoint(self.sound, index, &mut sync_point) } {
             fmod::Ok => Ok(FmodSyncPoint::from_ptr(sync_point)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_sync_point_info(&self, sync_point: FmodSyncPoint, name_len: u32, FmodTimeUnit(offset_type): FmodTimeUnit) -> Result<(String, u32), fmod::Result> {
-        let name = String::with_capacity(name_len as uint).into_string();
+    pub fn get_sync_point_info(&self, sync_point: FmodSyncPoint, FmodTimeUnit(offset_type): FmodTimeUnit) -> Result<(String, u32), FmodError> {
         let mut offset = 0u32;
 
-        match unsafe { ffi::FMOD_Sound_GetSyncPointInfo(self.sound, sync_point.sync_point, name.with_c_str(|c_name|{c_name as *mut c_char}),
-            name_len as i32, &mut offset, offset_type) } {
-            fmod::Ok => Ok((name.clone(), offset)),
-            e => Err(e)
+        match strings::get_string(|name, name_len| unsafe { ffi::FMOD_Sound_GetSyncPointInfo(self.sound, sync_point.sync_point, name, name_len,
+            &mut offset, offset_type) }) {
+            Ok(name) => Ok((name, offset)),
+            Err(e) => Err(FmodError::new(e))
         }
     }
 
-    pub fn add_sync_point(&self, offset: u32, FmodTimeUnit(offset_type): FmodTimeUnit, name: String) -> Result<FmodSyncPoint, fmod::Result> {
+    pub fn add_sync_point(&self, offset: u32, FmodTimeUnit(offset_type): FmodTimeUnit, name: String) -> Result<FmodSyncPoint, FmodError> {
         let mut sync_point = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_Sound_AddSyncPoint(self.sound, offset, offset_type, name.into_string().with_c_str(|c_name|{c_name}), &mut sync_point) } {
             fmod::Ok => Ok(FmodSyncPoint::from_ptr(sync_point)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn delete_sync_point(&self, sync_point: FmodSyncPoint) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_DeleteSyncPoint(self.sound, sync_point.sync_point) }
+    pub fn delete_sync_point(&self, sync_point: FmodSyncPoint) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_DeleteSyncPoint(self.sound, sync_point.sync_point) })
     }
 
-    pub fn set_mode(&self, FmodMode(mode): FmodMode) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetMode(self.sound, mode) }
+    /// Returns an [`InvalidFlags`](enum.ErrorKind.html) error if `mode` holds contradictory flags.
+    pub fn set_mode(&self, mode: FmodMode) -> Result<(), FmodError> {
+        try!(mode.check());
+        error::check(unsafe { ffi::FMOD_Sound_SetMode(self.sound, mode.bits()) })
     }
 
-    pub fn get_mode(&self) -> Result<FmodMode, fmod::Result> {
+    pub fn get_mode(&self) -> Result<FmodMode, FmodError> {
         let mut mode = 0u32;
 
         match unsafe { ffi::FMOD_Sound_GetMode(self.sound, &mut mode) } {
             fmod::Ok => Ok(FmodMode(mode)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_loop_count(&self, loop_count: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetLoopCount(self.sound, loop_count) }
+    pub fn set_loop_count(&self, loop_count: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetLoopCount(self.sound, loop_count) })
     }
 
-    pub fn get_loop_count(&self) -> Result<i32, fmod::Result> {
+    pub fn get_loop_count(&self) -> Result<i32, FmodError> {
         let mut loop_count = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetLoopCount(self.sound, &mut loop_count) } {
             fmod::Ok => Ok(loop_count),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn set_loop_points(&self, loop_start: u32, FmodTimeUnit(loop_start_type): FmodTimeUnit, loop_end: u32,
-        FmodTimeUnit(loop_end_type): FmodTimeUnit) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetLoopPoints(self.sound, loop_start, loop_start_type, loop_end, loop_end_type) }
+        FmodTimeUnit(loop_end_type): FmodTimeUnit) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetLoopPoints(self.sound, loop_start, loop_start_type, loop_end, loop_end_type) })
     }
 
-    pub fn get_loop_points(&self, FmodTimeUnit(loop_start_type): FmodTimeUnit, FmodTimeUnit(loop_end_type): FmodTimeUnit) -> Result<(u32, u32), fmod::Result> {
+    pub fn get_loop_points(&self, FmodTimeUnit(loop_start_type): FmodTimeUnit, FmodTimeUnit(loop_end_type): FmodTimeUnit) -> Result<(u32, u32), FmodError> {
         let mut loop_start = 0u32;
         let mut loop_end = 0u32;
 
         match unsafe { ffi::FMOD_Sound_GetLoopPoints(self.sound, &mut loop_start, loop_start_type, &mut loop_end, loop_end_type) } {
             fmod::Ok => Ok((loop_start, loop_end)),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_channels(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_channels(&self) -> Result<i32, FmodError> {
         let mut num_channels = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetMusicNumChannels(self.sound, &mut num_channels) } {
             fmod::Ok => Ok(num_channels),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     // TODO: see how to replace i32 channel by Channel struct
-    pub fn set_music_channel_volume(&self, channel: i32, volume: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetMusicChannelVolume(self.sound, channel, volume) }
+    pub fn set_music_channel_volume(&self, channel: i32, volume: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetMusicChannelVolume(self.sound, channel, volume) })
     }
 
     // TODO: see how to replace i32 channel by Channel struct
-    pub fn get_music_channel_volume(&self, channel: i32) -> Result<f32, fmod::Result> {
+    pub fn get_music_channel_volume(&self, channel: i32) -> Result<f32, FmodError> {
         let mut volume = 0f32;
 
         match unsafe { ffi::FMOD_Sound_GetMusicChannelVolume(self.sound, channel, &mut volume) } {
             fmod::Ok => Ok(volume),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_music_speed(&self, speed: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetMusicSpeed(self.sound, speed) }
+    pub fn set_music_speed(&self, speed: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetMusicSpeed(self.sound, speed) })
     }
 
-    pub fn get_music_speed(&self) -> Result<f32, fmod::Result> {
+    pub fn get_music_speed(&self) -> Result<f32, FmodError> {
         let mut speed = 0f32;
 
         match unsafe { ffi::FMOD_Sound_GetMusicSpeed(self.sound, &mut speed) } {
             fmod::Ok => Ok(speed),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_sub_sound_sentence(&self, sub_sounds: &mut Vec<i32>) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SetSubSoundSentence(self.sound, sub_sounds.as_mut_ptr(), sub_sounds.len() as c_int) }
+    pub fn set_sub_sound_sentence(&self, sub_sounds: &mut Vec<i32>) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SetSubSoundSentence(self.sound, sub_sounds.as_mut_ptr(), sub_sounds.len() as c_int) })
     }
 
-    pub fn seek_data(&self, pcm: u32) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_SeekData(self.sound, pcm) }
+    pub fn seek_data(&self, pcm: u32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_Sound_SeekData(self.sound, pcm) })
     }
 
-    pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
-        let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
-        let mut memory_used = 0u32;
+    /// Decodes data of a sound opened with FMOD_OPENONLY into `buffer`, in the format given by [`Sound::get_format`](struct.Sound.html#method.get_format).
+    /// Returns the number of bytes read, 0 meaning the end of the sound has been reached.
+    pub fn read_data(&self, buffer: &mut [u8]) -> Result<u32, FmodError> {
+        let mut read = 0u32;
 
-        match unsafe { ffi::FMOD_Sound_GetMemoryInfo(self.sound, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
-            fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+        match unsafe { ffi::FMOD_Sound_ReadData(self.sound, buffer.as_mut_ptr() as *mut c_void, buffer.len() as c_uint, &mut read) } {
+            fmod::Ok | fmod::ErrFileEOF => Ok(read),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn lock(&self, offset: u32, length: u32) -> Result<(Vec<u8>, Vec<u8>), fmod::Result> {
-        let mut len1 = 0u32;
-        let mut len2 = 0u32;
-        let mut ptr1 =::std::ptr::mut_null();
-        let mut ptr2 =::std::ptr::mut_null();
+    /// Returns an iterator decoding the interleaved samples of a sound opened with FMOD_OPENONLY, from the current
+    /// [`Sound::seek_data`](struct.Sound.html#method.seek_data) position. Only works with PCM formats.
+    pub fn samples<'a>(&'a self) -> Result<SampleReader<'a>, FmodError> {
+        let (_, format, channels, _) = match self.get_format() {
+            Ok(f) => f,
+            Err(e) => return Err(e)
+        };
+        let sample_size = match format {
+            fmod::SoundFormatPCM8 => 1u,
+            fmod::SoundFormatPCM16 => 2u,
+            fmod::SoundFormatPCM24 => 3u,
+            fmod::SoundFormatPCM32 | fmod::SoundFormatPCMFloat => 4u,
+            _ => return Err(FmodError::new(fmod::ErrFormat))
+        };
 
-        match unsafe { ffi::FMOD_Sound_Lock(self.sound, offset, length, &mut ptr1, &mut ptr2, &mut len1, &mut len2) } {
-            fmod::Ok => {
-                let mut v_ptr1 = Vec::new();
-                let mut v_ptr2 = Vec::new();
-
-                unsafe { slice::raw::buf_as_slice(ptr1 as *const u8, len1 as uint, |b| {
-                   v_ptr1 = Vec::from_slice(b).clone();
-                }); }
-                unsafe { slice::raw::buf_as_slice(ptr2 as *const u8, len2 as uint, |b| {
-                   v_ptr2 = Vec::from_slice(b).clone();
-                }); }
-                Ok((v_ptr1, v_ptr2))
-            }
-            e => Err(e)
-        }
-    }
-
-    pub fn unlock(&self, v_ptr1: Vec<u8>, v_ptr2: Vec<u8>) -> fmod::Result {
-        unsafe { ffi::FMOD_Sound_Unlock(self.sound, v_ptr1.as_ptr() as *mut c_void, v_ptr2.as_ptr() as *mut c_void, v_ptr1.len() as c_uint,
-            v_ptr2.len() as c_uint) }
+        Ok(SampleReader {
+            sound: self,
+            format: format,
+            channels: channels,
+            sample_size: sample_size,
+            buffer: Vec::from_elem(4096u, 0u8),
+            pos: 0u,
+            len: 0u,
+            error: None
+        })
     }
 
-    pub fn set_user_data<T>(&mut self, user_data: &mut T) -> fmod::Result {
-        let mut data : *mut c_void = ::std::ptr::mut_null();
-
-        unsafe {
-            match ffi::FMOD_Sound_GetUserData(self.sound, &mut data) {
-                fmod::Ok => {
-                    if data.is_null() {
-                        self.user_data.user_data = ::std::ptr::mut_null();
-
-                        ffi::FMOD_Sound_SetUserData(self.sound, transmute(&mut self.user_data))
-                    } else {
-                        let tmp : &mut ffi::SoundData = transmute::<*mut c_void, &mut ffi::SoundData>(data);
-
-                        tmp.user_data = transmute::<&mut T, *mut c_void>(user_data);
-                        ffi::FMOD_Sound_SetUserData(self.sound, transmute(tmp))
-                    }
-                }
-                _ => {
-                    self.user_data.user_data = transmute::<&mut T, *mut c_void>(user_data);
+    pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
+        let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
+        let mut memory_used = 0u32;
 
-                    ffi::FMOD_Sound_SetUserData(self.sound, transmute(&mut self.user_data))
-                }
+        match unsafe { ffi::FMOD_Sound_GetMemoryInfo(self.sound, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
+            fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
+            e => Err(FmodError::new(e))
+        }
+    }
+
+    /// Gives direct access to `length` bytes of the sample data from `offset`. The data is unlocked when the returned
+    /// [`SoundLock`](struct.SoundLock.html) is dropped.
+    pub fn lock<'a>(&'a self, offset: u32, length: u32) -> Result<SoundLock<'a>, FmodError> {
+        let format = match self.get_format() {
+            Ok((_, format, _, _)) => format,
+            Err(e) => return Err(e)
+        };
+        let mut lock = SoundLock {
+            sound: self,
+            format: format,
+            ptr1: ::std::ptr::mut_null(),
+            ptr2: ::std::ptr::mut_null(),
+            len1: 0u32,
+            len2: 0u32
+        };
+
+        match unsafe { ffi::FMOD_Sound_Lock(self.sound, offset, length, &mut lock.ptr1, &mut lock.ptr2, &mut lock.len1, &mut lock.len2) } {
+            fmod::Ok => Ok(lock),
+            e => {
+                lock.ptr1 = ::std::ptr::mut_null();
+                Err(FmodError::new(e))
             }
         }
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, fmod::Result> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::mut_null();
-
-            match ffi::FMOD_Sound_GetUserData(self.sound, &mut user_data) {
-                fmod::Ok => {
-                    if user_data.is_not_null() {
-                        let tmp : &mut ffi::SoundData = transmute::<*mut c_void, &mut ffi::SoundData>(user_data);
-                        let tmp2 : &mut T = transmute::<*mut c_void, &mut T>(tmp.user_data);
-                        Ok(tmp2)
-                    } else {
-                        Err(fmod::Ok)
-                    }
-                },
-                e => Err(e)
-            }
-        }
+    /// Attaches `user_data` to the sound, replacing and dropping the previous one. It's dropped when the sound is released.
+    ///
+    /// Fails on the sounds given to the callbacks of a [`FmodCreateSoundexInfo`](struct.FmodCreateSoundexInfo.html), as they aren't linked to their system.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.system.set_user_data(self.sound as uint, false, user_data)
     }
 
-    pub fn save_to_wav(&self, file_name: &String) -> Result<bool, String> {
-        unsafe {
-            let mut channels = 0i32;
-            let mut bits = 0i32;
-            let mut rate = 0f32;
-            let len_bytes = match self.get_length(FMOD_TIMEUNIT_PCMBYTES) {
-                Ok(l) => l,
-                Err(e) => return Err(format!("{}", e))
-            };
-            let mut len1 = 0u32;
-            let mut len2 = 0u32;
-            let mut ptr1: *mut c_void =::std::ptr::mut_null();
-            let mut ptr2: *mut c_void =::std::ptr::mut_null();
-
-            match ffi::FMOD_Sound_GetFormat(self.sound, ::std::ptr::mut_null(), ::std::ptr::mut_null(), &mut channels, &mut bits) {
-                fmod::Ok => match ffi::FMOD_Sound_GetDefaults(self.sound, &mut rate, ::std::ptr::mut_null(), ::std::ptr::mut_null(), ::std::ptr::mut_null()) {
-                    fmod::Ok => {}
-                    e => return Err(format!("{}", e))
-                },
-                e => return Err(format!("{}", e))
-            };
-            let fmt_chunk = FmtChunk {
-                chunk: RiffChunk {
-                    id: ['f' as i8, 'm' as i8, 't' as i8, ' ' as i8],
-                    size: mem::size_of::<FmtChunk>() as i32 - mem::size_of::<RiffChunk>() as i32
-                },
-                w_format_tag: 1,
-                n_channels: channels as u16,
-                n_samples_per_sec: rate as u32,
-                n_avg_bytes_per_sec: rate as u32 * channels as u32 * bits as u32 / 8u32,
-                n_block_align: 1u16 * channels as u16 * bits as u16 / 8u16,
-                w_bits_per_sample: bits as u16
-            };
-            let data_chunk = DataChunk {
-                chunk: RiffChunk {
-                    id: ['d' as i8, 'a' as i8, 't' as i8, 'a' as i8],
-                    size: len_bytes as i32
-                }
-            };
-            let wav_header = WavHeader {
-                chunk: RiffChunk {
-                    id: ['R' as i8, 'I' as i8, 'F' as i8, 'F' as i8],
-                    size: mem::size_of::<FmtChunk>() as i32 + mem::size_of::<RiffChunk>() as i32 + len_bytes as i32
-                },
-                riff_type: ['W' as i8, 'A' as i8, 'V' as i8, 'E' as i8]
-            };
-
-            let file = match File::create(&Path::new(file_name.as_slice())) {
-                Ok(f) => f,
-                Err(e) => return Err(format!("{}", e))
-            };
-            let mut buf: BufferedWriter<File> = BufferedWriter::new(file);
-
-            /* wav header */
-            for it in range(0u, 4u) {
-                buf.write_i8(wav_header.chunk.id[it]).unwrap();
-            }
-            buf.write_le_i32(wav_header.chunk.size).unwrap();
-            for it in range(0u, 4u) {
-                buf.write_i8(wav_header.riff_type[it]).unwrap();
-            }
+    /// Calls `f` with the data attached to the sound and returns its result. Returns None if no data is attached or if it isn't a `T`.
+    pub fn with_user_data<T: 'static, R>(&self, f: |&T| -> R) -> Option<R> {
+        self.system.with_user_data(self.sound as uint, f)
+    }
 
-            /* wav chunk */
-            for it in range(0u, 4u) {
-                buf.write_i8(fmt_chunk.chunk.id[it]).unwrap();
-            }
-            buf.write_le_i32(fmt_chunk.chunk.size).unwrap();
-            buf.write_le_u16(fmt_chunk.w_format_tag).unwrap();
-            buf.write_le_u16(fmt_chunk.n_channels).unwrap();
-            buf.write_le_u32(fmt_chunk.n_samples_per_sec).unwrap();
-            buf.write_le_u32(fmt_chunk.n_avg_bytes_per_sec).unwrap();
-            buf.write_le_u16(fmt_chunk.n_block_align).unwrap();
-            buf.write_le_u16(fmt_chunk.w_bits_per_sample).unwrap();
-
-            /* wav data chunk */
-            for it in range(0u, 4u) {
-                buf.write_i8(data_chunk.chunk.id[it]).unwrap();
+    /// Writes the PCM data of the sound in a WAV file.
+    pub fn save_to_wav(&self, file_name: &str) -> Result<(), FmodError> {
+        let mut channels = 0i32;
+        let mut bits = 0i32;
+        let mut rate = 0f32;
+        let len_bytes = try!(self.get_length(FMOD_TIMEUNIT_PCMBYTES));
+        let mut len1 = 0u32;
+        let mut len2 = 0u32;
+        let mut ptr1: *mut c_void =::std::ptr::mut_null();
+        let mut ptr2: *mut c_void =::std::ptr::mut_null();
+
+        try!(error::check(unsafe { ffi::FMOD_Sound_GetFormat(self.sound, ::std::ptr::mut_null(), ::std::ptr::mut_null(), &mut channels, &mut bits) }));
+        try!(error::check(unsafe { ffi::FMOD_Sound_GetDefaults(self.sound, &mut rate, ::std::ptr::mut_null(), ::std::ptr::mut_null(),
+            ::std::ptr::mut_null()) }));
+        let fmt_chunk = FmtChunk {
+            chunk: RiffChunk {
+                id: ['f' as i8, 'm' as i8, 't' as i8, ' ' as i8],
+                size: mem::size_of::<FmtChunk>() as i32 - mem::size_of::<RiffChunk>() as i32
+            },
+            w_format_tag: 1,
+            n_channels: channels as u16,
+            n_samples_per_sec: rate as u32,
+            n_avg_bytes_per_sec: rate as u32 * channels as u32 * bits as u32 / 8u32,
+            n_block_align: 1u16 * channels as u16 * bits as u16 / 8u16,
+            w_bits_per_sample: bits as u16
+        };
+        let data_chunk = DataChunk {
+            chunk: RiffChunk {
+                id: ['d' as i8, 'a' as i8, 't' as i8, 'a' as i8],
+                size: len_bytes as i32
             }
-            buf.write_le_i32(data_chunk.chunk.size).unwrap();
-
-            ffi::FMOD_Sound_Lock(self.sound, 0, len_bytes, &mut ptr1, &mut ptr2, &mut len1, &mut len2);
-
-            slice::raw::buf_as_slice(ptr1 as *const u8, len_bytes as uint, |b| {
-               buf.write(b).unwrap();
-            });
-
-            ffi::FMOD_Sound_Unlock(self.sound, ptr1, ptr2, len1, len2);
+        };
+        let wav_header = WavHeader {
+            chunk: RiffChunk {
+                id: ['R' as i8, 'I' as i8, 'F' as i8, 'F' as i8],
+                size: mem::size_of::<FmtChunk>() as i32 + mem::size_of::<RiffChunk>() as i32 + len_bytes as i32
+            },
+            riff_type: ['W' as i8, 'A' as i8, 'V' as i8, 'E' as i8]
+        };
+
+        let file = match File::create(&Path::new(file_name)) {
+            Ok(f) => f,
+            Err(e) => return Err(error::io_error(e))
+        };
+        let mut buf: BufferedWriter<File> = BufferedWriter::new(file);
+
+        match write_wav_header(&mut buf, &wav_header, &fmt_chunk, &data_chunk) {
+            Ok(()) => {}
+            Err(e) => return Err(error::io_error(e))
+        }
+        try!(error::check(unsafe { ffi::FMOD_Sound_Lock(self.sound, 0, len_bytes, &mut ptr1, &mut ptr2, &mut len1, &mut len2) }));
+        let written = unsafe {
+            slice::raw::buf_as_slice(ptr1 as *const u8, len1 as uint, |b| {
+                buf.write(b)
+            })
+        };
+
+        try!(error::check(unsafe { ffi::FMOD_Sound_Unlock(self.sound, ptr1, ptr2, len1, len2) }));
+        match written.and_then(|_| buf.flush()) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(error::io_error(e))
         }
-        Ok(true)
     }
 }
\ No newline at end of file
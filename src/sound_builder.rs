@@ -0,0 +1,122 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use enums::*;
+use types::*;
+use fmod_sys;
+use fmod_sys::FmodSys;
+use sound::Sound;
+use error;
+use error::FmodError;
+use std::mem;
+use std::raw;
+
+fn as_bytes<'a, T>(data: &'a [T]) -> &'a [u8] {
+    unsafe { mem::transmute(raw::Slice{data: data.as_ptr() as *const u8, len: data.len() * mem::size_of::<T>()}) }
+}
+
+/// Creates a static sound from PCM samples generated by the program, like tones or UI sounds:
+///
+/// ```ignore
+/// let sound = SoundBuilder::from_f32(1, 44100, samples.as_slice()).loop_points(0, 44099).build(&fmod);
+/// ```
+///
+/// The samples are interleaved when there are several channels. FMOD copies them, so the buffer can be dropped once the sound is built.
+pub struct SoundBuilder<'a> {
+    data: &'a [u8],
+    format: fmod::SoundFormat,
+    channels: i32,
+    sample_rate: i32,
+    loop_points: Option<(u32, u32)>,
+    is_3D: bool
+}
+
+impl<'a> SoundBuilder<'a> {
+    /// Signed 8 bits samples, like FMOD's PCM8 format and the [`BufferPCM8`](enum.PcmBuffer.html) given to a [`PcmSource`](trait.PcmSource.html).
+    pub fn from_i8(channels: i32, sample_rate: i32, data: &'a [i8]) -> SoundBuilder<'a> {
+        SoundBuilder::new(channels, sample_rate, as_bytes(data), fmod::SoundFormatPCM8)
+    }
+
+    /// Signed 16 bits samples, in the native byte order.
+    pub fn from_i16(channels: i32, sample_rate: i32, data: &'a [i16]) -> SoundBuilder<'a> {
+        SoundBuilder::new(channels, sample_rate, as_bytes(data), fmod::SoundFormatPCM16)
+    }
+
+    /// Samples between -1 and 1.
+    pub fn from_f32(channels: i32, sample_rate: i32, data: &'a [f32]) -> SoundBuilder<'a> {
+        SoundBuilder::new(channels, sample_rate, as_bytes(data), fmod::SoundFormatPCMFloat)
+    }
+
+    fn new(channels: i32, sample_rate: i32, data: &'a [u8], format: fmod::SoundFormat) -> SoundBuilder<'a> {
+        SoundBuilder {
+            data: data,
+            format: format,
+            channels: channels,
+            sample_rate: sample_rate,
+            loop_points: None,
+            is_3D: false
+        }
+    }
+
+    /// Makes the sound loop between `start` and `end`, both included and counted in samples per channel.
+    pub fn loop_points(mut self, start: u32, end: u32) -> SoundBuilder<'a> {
+        self.loop_points = Some((start, end));
+        self
+    }
+
+    /// Makes the sound positionable in 3D. Sounds are 2D by default.
+    pub fn with_3D(mut self, is_3D: bool) -> SoundBuilder<'a> {
+        self.is_3D = is_3D;
+        self
+    }
+
+    /// Creates the sound on `system`. The builder can be used again to create other sounds from the same data.
+    ///
+    /// Fails with an [`OutOfRange`](enum.ErrorKind.html) error if the number of channels or the sample rate isn't positive.
+    pub fn build(&self, system: &FmodSys) -> Result<Sound, FmodError> {
+        if self.channels <= 0 {
+            return Err(error::out_of_range(format!("SoundBuilder::build: {} channels, at least one is needed", self.channels)));
+        }
+        if self.sample_rate <= 0 {
+            return Err(error::out_of_range(format!("SoundBuilder::build: sample rate {} isn't positive", self.sample_rate)));
+        }
+        let dimension = if self.is_3D {
+            FMOD_3D
+        } else {
+            FMOD_2D
+        };
+        let loop_mode = match self.loop_points {
+            Some(_) => FMOD_LOOP_NORMAL,
+            None => FMOD_LOOP_OFF
+        };
+        let mode = FMOD_SOFTWARE | FMOD_CREATESAMPLE | dimension | loop_mode;
+        let sound = try!(fmod_sys::create_raw_sound(system, self.data, mode, self.channels, self.sample_rate, self.format));
+
+        match self.loop_points {
+            Some((start, end)) => try!(sound.set_loop_points(start, FMOD_TIMEUNIT_PCM, end, FMOD_TIMEUNIT_PCM)),
+            None => {}
+        }
+        Ok(sound)
+    }
+}
@@ -27,167 +27,161 @@ use types::*;
 use ffi;
 use sound;
 use fmod_sys;
-use libc::{c_int, c_void};
+use libc::c_int;
 use fmod_sys;
 use fmod_sys::FmodMemoryUsageDetails;
-use std::mem::transmute;
-use libc::{c_char};
+use error;
+use error::FmodError;
+use strings;
+use std::any::Any;
 use std::default::Default;
 
 /// SoundGroup object
 pub struct SoundGroup {
     sound_group: *mut ffi::FMOD_SOUNDGROUP,
+    system: fmod_sys::SystemRef
 }
 
 pub fn get_ffi(sound_group: &SoundGroup) -> *mut ffi::FMOD_SOUNDGROUP {
     sound_group.sound_group
 }
 
-pub fn from_ptr(sound_group: *mut ffi::FMOD_SOUNDGROUP) -> SoundGroup {
-    SoundGroup{sound_group: sound_group}
+pub fn from_ptr(sound_group: *mut ffi::FMOD_SOUNDGROUP, system: fmod_sys::SystemRef) -> SoundGroup {
+    SoundGroup{sound_group: sound_group, system: system}
 }
 
 impl Drop for SoundGroup {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl SoundGroup {
-    pub fn release(&mut self) -> fmod::Result {
+    pub fn release(&mut self) -> Result<(), FmodError> {
         if self.sound_group.is_not_null() {
             match unsafe { ffi::FMOD_SoundGroup_Release(self.sound_group) } {
                 fmod::Ok => {
+                    self.system.remove_user_data(self.sound_group as uint);
                     self.sound_group =::std::ptr::mut_null();
-                    fmod::Ok
+                    Ok(())
                 }
-                e => e
+                e => Err(FmodError::new(e))
             }
         } else {
-            fmod::Ok
+            Ok(())
         }
     }
 
-    pub fn set_max_audible(&self, max_audible: i32) -> fmod::Result {
-        unsafe { ffi::FMOD_SoundGroup_SetMaxAudible(self.sound_group, max_audible) }
+    pub fn set_max_audible(&self, max_audible: i32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_SoundGroup_SetMaxAudible(self.sound_group, max_audible) })
     }
 
-    pub fn get_max_audible(&self) -> Result<i32, fmod::Result> {
+    pub fn get_max_audible(&self) -> Result<i32, FmodError> {
         let mut max_audible = 0i32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetMaxAudible(self.sound_group, &mut max_audible) } {
             fmod::Ok => Ok(max_audible),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_max_audible_behavior(&self, max_audible_behavior: fmod::SoundGroupBehavior) -> fmod::Result {
-        unsafe { ffi::FMOD_SoundGroup_SetMaxAudibleBehavior(self.sound_group, max_audible_behavior) }
+    pub fn set_max_audible_behavior(&self, max_audible_behavior: fmod::SoundGroupBehavior) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_SoundGroup_SetMaxAudibleBehavior(self.sound_group, max_audible_behavior) })
     }
 
-    pub fn get_max_audible_behavior(&self) -> Result<fmod::SoundGroupBehavior, fmod::Result> {
+    pub fn get_max_audible_behavior(&self) -> Result<fmod::SoundGroupBehavior, FmodError> {
         let mut max_audible_behavior = fmod::SoundGroupBehaviorFail;
 
         match unsafe { ffi::FMOD_SoundGroup_GetMaxAudibleBehavior(self.sound_group, &mut max_audible_behavior) } {
             fmod::Ok => Ok(max_audible_behavior),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_mute_fade_speed(&self, speed: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_SoundGroup_SetMuteFadeSpeed(self.sound_group, speed) }
+    pub fn set_mute_fade_speed(&self, speed: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_SoundGroup_SetMuteFadeSpeed(self.sound_group, speed) })
     }
 
-    pub fn get_mute_fade_speed(&self) -> Result<f32, fmod::Result> {
+    pub fn get_mute_fade_speed(&self) -> Result<f32, FmodError> {
         let mut speed = 0f32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetMuteFadeSpeed(self.sound_group, &mut speed) } {
             fmod::Ok => Ok(speed),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_volume(&self, volume: f32) -> fmod::Result {
-        unsafe { ffi::FMOD_SoundGroup_SetVolume(self.sound_group, volume) }
+    pub fn set_volume(&self, volume: f32) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_SoundGroup_SetVolume(self.sound_group, volume) })
     }
 
-    pub fn get_volume(&self) -> Result<f32, fmod::Result> {
+    pub fn get_volume(&self) -> Result<f32, FmodError> {
         let mut volume = 0f32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetVolume(self.sound_group, &mut volume) } {
             fmod::Ok => Ok(volume),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn stop(&self) -> fmod::Result {
-        unsafe { ffi::FMOD_SoundGroup_Stop(self.sound_group) }
+    pub fn stop(&self) -> Result<(), FmodError> {
+        error::check(unsafe { ffi::FMOD_SoundGroup_Stop(self.sound_group) })
     }
 
-    pub fn get_name(&self, name_len: u32) -> Result<String, fmod::Result> {
-        let name = String::with_capacity(name_len as uint);
-
-        name.with_c_str(|c_name|{
-            match unsafe { ffi::FMOD_SoundGroup_GetName(self.sound_group, c_name as *mut c_char, name_len as i32) } {
-                fmod::Ok => Ok(unsafe {::std::str::raw::from_c_str(c_name).clone() }),
-                e => Err(e)
-            }
-        })
+    pub fn get_name(&self) -> Result<String, FmodError> {
+        match strings::get_string(|name, name_len| unsafe { ffi::FMOD_SoundGroup_GetName(self.sound_group, name, name_len) }) {
+            Ok(name) => Ok(name),
+            Err(e) => Err(FmodError::new(e))
+        }
     }
 
-    pub fn get_num_sounds(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_sounds(&self) -> Result<i32, FmodError> {
         let mut num_sounds = 0i32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetNumSounds(self.sound_group, &mut num_sounds) } {
             fmod::Ok => Ok(num_sounds),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_sound(&self, index: i32) -> Result<sound::Sound, fmod::Result> {
+    pub fn get_sound(&self, index: i32) -> Result<sound::Sound, FmodError> {
         let mut sound = ::std::ptr::mut_null();
 
         match unsafe { ffi::FMOD_SoundGroup_GetSound(self.sound_group, index, &mut sound) } {
-            fmod::Ok => Ok(sound::from_ptr(sound)),
-            e => Err(e)
+            fmod::Ok => Ok(sound::from_ptr(sound, self.system.clone())),
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn get_num_playing(&self) -> Result<i32, fmod::Result> {
+    pub fn get_num_playing(&self) -> Result<i32, FmodError> {
         let mut num_playing = 0i32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetNumPlaying(self.sound_group, &mut num_playing) } {
             fmod::Ok => Ok(num_playing),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
     pub fn get_memory_info(&self, FmodMemoryBits(memory_bits): FmodMemoryBits,
-        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), fmod::Result> {
+        FmodEventMemoryBits(event_memory_bits): FmodEventMemoryBits) -> Result<(u32, FmodMemoryUsageDetails), FmodError> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetMemoryInfo(self.sound_group, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
             fmod::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(FmodError::new(e))
         }
     }
 
-    pub fn set_user_data<T>(&self, user_data: &mut T) -> fmod::Result {
-        unsafe { ffi::FMOD_SoundGroup_SetUserData(self.sound_group, transmute(user_data)) }
+    /// Attaches `user_data` to the sound group, replacing and dropping the previous one. It's dropped when the sound group is released.
+    ///
+    /// Fails on a sound group obtained from a sound given to a callback, as it isn't linked to its system.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Result<(), FmodError> {
+        self.system.set_user_data(self.sound_group as uint, false, user_data)
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, fmod::Result> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::mut_null();
-
-            match ffi::FMOD_SoundGroup_GetUserData(self.sound_group, &mut user_data) {
-                fmod::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    Ok(tmp)
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the data attached to the sound group and returns its result. Returns None if no data is attached or if it isn't a `T`.
+    pub fn with_user_data<T: 'static, R>(&self, f: |&T| -> R) -> Option<R> {
+        self.system.with_user_data(self.sound_group as uint, f)
     }
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use enums::*;
+use libc::{c_char, c_short, c_int};
+use std::num::Zero;
+
+/* FMOD truncates the strings which don't fit, so the buffers are grown until the string is shorter than them. */
+static FIRST_LEN : uint = 256;
+static MAX_LEN : uint = 65536;
+
+fn string_len<T: PartialEq + Zero>(buffer: &[T]) -> uint {
+    buffer.iter().position(|c| *c == Zero::zero()).unwrap_or(buffer.len())
+}
+
+/// Decodes the UTF-8 string written by FMOD in `buffer`.
+pub fn from_buffer(buffer: &[c_char]) -> String {
+    let bytes : Vec<u8> = buffer.slice_to(string_len(buffer)).iter().map(|c| *c as u8).collect();
+
+    String::from_utf8_lossy(bytes.as_slice()).into_string()
+}
+
+/// Calls `f` with `count` buffers of the same length and returns the strings FMOD wrote in them, decoded as UTF-8.
+pub fn get_strings(count: uint, f: |&mut [Vec<c_char>], c_int| -> fmod::Result) -> Result<Vec<String>, fmod::Result> {
+    let mut len = FIRST_LEN;
+
+    loop {
+        let mut buffers = Vec::from_fn(count, |_| Vec::from_elem(len, 0 as c_char));
+
+        match f(buffers.as_mut_slice(), len as c_int) {
+            fmod::Ok => {}
+            e => return Err(e)
+        }
+        if len >= MAX_LEN || buffers.iter().all(|b| string_len(b.as_slice()) + 1 < len) {
+            return Ok(buffers.iter().map(|b| from_buffer(b.as_slice())).collect());
+        }
+        len *= 2;
+    }
+}
+
+pub fn get_string(f: |*mut c_char, c_int| -> fmod::Result) -> Result<String, fmod::Result> {
+    match get_strings(1, |buffers, len| f(buffers[0].as_mut_ptr(), len)) {
+        Ok(mut strings) => Ok(strings.pop().unwrap()),
+        Err(e) => Err(e)
+    }
+}
+
+/// Same as [`get_string`](fn.get_string.html) for the functions writing UTF-16 strings. The length given to `f` is
+/// counted in characters.
+pub fn get_wide_string(f: |*mut c_short, c_int| -> fmod::Result) -> Result<String, fmod::Result> {
+    let mut len = FIRST_LEN;
+
+    loop {
+        let mut buffer = Vec::from_elem(len, 0 as c_short);
+
+        match f(buffer.as_mut_ptr(), len as c_int) {
+            fmod::Ok => {}
+            e => return Err(e)
+        }
+        let size = string_len(buffer.as_slice());
+
+        if len >= MAX_LEN || size + 1 < len {
+            let chars : Vec<u16> = buffer.slice_to(size).iter().map(|c| *c as u16).collect();
+
+            return Ok(String::from_utf16_lossy(chars.as_slice()));
+        }
+        len *= 2;
+    }
+}
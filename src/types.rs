@@ -22,10 +22,166 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use enums::*;
+use error;
+use error::FmodError;
+use std::fmt;
+
+/* Gives a flag set the |, & operators, contains and a Show listing the names of the flags it holds. */
+macro_rules! flags(
+    ($name:ident, $($flag:ident),+) => (
+        impl $name {
+            pub fn bits(&self) -> u32 {
+                let $name(bits) = *self;
+
+                bits
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.bits() == 0
+            }
+
+            /// Returns true if all the flags of `other` are set.
+            pub fn contains(&self, other: $name) -> bool {
+                self.bits() & other.bits() == other.bits()
+            }
+        }
+
+        impl BitOr<$name, $name> for $name {
+            fn bitor(&self, other: &$name) -> $name {
+                $name(self.bits() | other.bits())
+            }
+        }
+
+        impl BitAnd<$name, $name> for $name {
+            fn bitand(&self, other: &$name) -> $name {
+                $name(self.bits() & other.bits())
+            }
+        }
+
+        impl fmt::Show for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut names = Vec::new();
+                let mut known = 0u32;
+
+                $(
+                    if !$flag.is_empty() && self.contains($flag) {
+                        names.push(stringify!($flag).to_string());
+                        known |= $flag.bits();
+                    }
+                )+
+                if self.bits() & !known != 0 {
+                    names.push(format!("{:#x}", self.bits() & !known));
+                }
+                write!(f, "{}({})", stringify!($name), names.connect(" | "))
+            }
+        }
+    )
+)
+
+#[deriving(PartialEq, Clone)]
 pub struct FmodMode(pub u32);
+#[deriving(PartialEq, Clone)]
 pub struct FmodTimeUnit(pub u32);
+#[deriving(PartialEq, Clone)]
 pub struct FmodCaps(pub u32);
 pub struct FmodPluginHandle(pub u32);
+#[deriving(PartialEq, Clone)]
 pub struct FmodInitFlag(pub u32);
+#[deriving(PartialEq, Clone)]
 pub struct FmodMemoryBits(pub u32);
-pub struct FmodEventMemoryBits(pub u32);
\ No newline at end of file
+pub struct FmodEventMemoryBits(pub u32);
+
+flags!(FmodMode, FMOD_LOOP_OFF, FMOD_LOOP_NORMAL, FMOD_LOOP_BIDI, FMOD_2D, FMOD_3D, FMOD_HARDWARE, FMOD_SOFTWARE, FMOD_CREATESTREAM,
+    FMOD_CREATESAMPLE, FMOD_CREATECOMPRESSEDSAMPLE, FMOD_OPENUSER, FMOD_OPENMEMORY, FMOD_OPENMEMORY_POINT, FMOD_OPENRAW, FMOD_OPENONLY,
+    FMOD_ACCURATETIME, FMOD_MPEGSEARCH, FMOD_NONBLOCKING, FMOD_UNIQUE, FMOD_3D_HEADRELATIVE, FMOD_3D_WORLDRELATIVE, FMOD_3D_INVERSEROLLOFF,
+    FMOD_3D_LINEARROLLOFF, FMOD_3D_LINEARSQUAREROLLOFF, FMOD_3D_CUSTOMROLLOFF, FMOD_3D_IGNOREGEOMETRY, FMOD_UNICODE, FMOD_IGNORETAGS,
+    FMOD_LOWMEM, FMOD_LOADSECONDARYRAM, FMOD_VIRTUAL_PLAYFROMSTART)
+flags!(FmodTimeUnit, FMOD_TIMEUNIT_MS, FMOD_TIMEUNIT_PCM, FMOD_TIMEUNIT_PCMBYTES, FMOD_TIMEUNIT_RAWBYTES, FMOD_TIMEUNIT_PCMFRACTION,
+    FMOD_TIMEUNIT_MODORDER, FMOD_TIMEUNIT_MODROW, FMOD_TIMEUNIT_MODPATTERN, FMOD_TIMEUNIT_SENTENCE_MS, FMOD_TIMEUNIT_SENTENCE_PCM,
+    FMOD_TIMEUNIT_SENTENCE_PCMBYTES, FMOD_TIMEUNIT_SENTENCE, FMOD_TIMEUNIT_SENTENCE_SUBSOUND, FMOD_TIMEUNIT_BUFFERED)
+flags!(FmodCaps, FMOD_CAPS_HARDWARE, FMOD_CAPS_HARDWARE_EMULATED, FMOD_CAPS_OUTPUT_MULTICHANNEL, FMOD_CAPS_OUTPUT_FORMAT_PCM8,
+    FMOD_CAPS_OUTPUT_FORMAT_PCM16, FMOD_CAPS_OUTPUT_FORMAT_PCM24, FMOD_CAPS_OUTPUT_FORMAT_PCM32, FMOD_CAPS_OUTPUT_FORMAT_PCMFLOAT,
+    FMOD_CAPS_REVERB_LIMITED, FMOD_CAPS_LOOPBACK)
+flags!(FmodInitFlag, FMOD_INIT_STREAM_FROM_UPDATE, FMOD_INIT_3D_RIGHTHANDED, FMOD_INIT_SOFTWARE_DISABLE, FMOD_INIT_OCCLUSION_LOWPASS,
+    FMOD_INIT_HRTF_LOWPASS, FMOD_INIT_DISTANCE_FILTERING, FMOD_INIT_REVERB_PREALLOCBUFFERS, FMOD_INIT_ENABLE_PROFILE,
+    FMOD_INIT_VOL0_BECOMES_VIRTUAL, FMOD_INIT_WASAPI_EXCLUSIVE, FMOD_INIT_PS3_PREFERDTS, FMOD_INIT_PS3_FORCE2CHLPCM, FMOD_INIT_DISABLEDOLBY,
+    FMOD_INIT_SYSTEM_MUSICMUTENOTPAUSE, FMOD_INIT_SYNCMIXERWITHUPDATE, FMOD_INIT_GEOMETRY_USECLOSEST, FMOD_INIT_DISABLE_MYEARS_AUTODETECT,
+    FMOD_INIT_PS3_DISABLEDTS, FMOD_INIT_PS3_DISABLEDOLBYDIGITAL, FMOD_INIT_7POINT1_DOLBYMAPPING)
+flags!(FmodMemoryBits, FMOD_MEMBITS_OTHER, FMOD_MEMBITS_STRING, FMOD_MEMBITS_SYSTEM, FMOD_MEMBITS_PLUGINS, FMOD_MEMBITS_OUTPUT,
+    FMOD_MEMBITS_CHANNEL, FMOD_MEMBITS_CHANNELGROUP, FMOD_MEMBITS_CODEC, FMOD_MEMBITS_FILE, FMOD_MEMBITS_SOUND,
+    FMOD_MEMBITS_SOUND_SECONDARYRAM, FMOD_MEMBITS_SOUNDGROUP, FMOD_MEMBITS_STREAMBUFFER, FMOD_MEMBITS_DSPCONNECTION, FMOD_MEMBITS_DSP,
+    FMOD_MEMBITS_DSPCODEC, FMOD_MEMBITS_PROFILE, FMOD_MEMBITS_RECORDBUFFER, FMOD_MEMBITS_REVERB, FMOD_MEMBITS_REVERBCHANNELPROPS,
+    FMOD_MEMBITS_GEOMETRY, FMOD_MEMBITS_SYNCPOINT)
+
+impl FmodMode {
+    /// Returns an error if the mode holds flags which contradict each other, like FMOD_LOOP_OFF | FMOD_LOOP_NORMAL
+    /// or FMOD_2D | FMOD_3D.
+    pub fn check(&self) -> Result<(), FmodError> {
+        // FMOD silently keeps only one flag of each group
+        let groups : [&[FmodMode], ..7] = [
+            &[FMOD_LOOP_OFF, FMOD_LOOP_NORMAL, FMOD_LOOP_BIDI],
+            &[FMOD_2D, FMOD_3D],
+            &[FMOD_HARDWARE, FMOD_SOFTWARE],
+            &[FMOD_CREATESTREAM, FMOD_CREATESAMPLE, FMOD_CREATECOMPRESSEDSAMPLE],
+            &[FMOD_OPENUSER, FMOD_OPENMEMORY, FMOD_OPENMEMORY_POINT],
+            &[FMOD_3D_HEADRELATIVE, FMOD_3D_WORLDRELATIVE],
+            &[FMOD_3D_INVERSEROLLOFF, FMOD_3D_LINEARROLLOFF, FMOD_3D_LINEARSQUAREROLLOFF, FMOD_3D_CUSTOMROLLOFF]
+        ];
+
+        for group in groups.iter() {
+            let set = group.iter().fold(FmodMode(0), |set, flag| set | (*self & *flag));
+
+            if group.iter().filter(|flag| set.contains(**flag)).count() > 1 {
+                return Err(error::invalid_flags(format!("{} can't be used together", set)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use enums::*;
+    use error::InvalidFlags;
+    use std::error::Error;
+    use super::FmodMode;
+
+    #[test]
+    fn check_accepts_one_flag_per_group() {
+        let mode = FMOD_LOOP_NORMAL | FMOD_3D | FMOD_SOFTWARE | FMOD_CREATESTREAM | FMOD_OPENMEMORY | FMOD_3D_WORLDRELATIVE
+            | FMOD_3D_LINEARROLLOFF | FMOD_UNIQUE;
+
+        assert!(mode.check().is_ok());
+        assert!(FMOD_DEFAULT.check().is_ok());
+    }
+
+    #[test]
+    fn check_rejects_two_flags_of_a_group() {
+        let modes = [FMOD_LOOP_OFF | FMOD_LOOP_BIDI, FMOD_2D | FMOD_3D, FMOD_HARDWARE | FMOD_SOFTWARE,
+            FMOD_CREATESAMPLE | FMOD_CREATECOMPRESSEDSAMPLE, FMOD_OPENUSER | FMOD_OPENMEMORY_POINT,
+            FMOD_3D_HEADRELATIVE | FMOD_3D_WORLDRELATIVE, FMOD_3D_INVERSEROLLOFF | FMOD_3D_CUSTOMROLLOFF];
+
+        for mode in modes.iter() {
+            match (*mode | FMOD_UNIQUE).check() {
+                Ok(_) => fail!("{} was accepted", mode),
+                Err(e) => assert_eq!(e.get_kind(), InvalidFlags)
+            }
+        }
+    }
+
+    #[test]
+    fn check_names_the_conflicting_flags() {
+        match (FMOD_2D | FMOD_3D | FMOD_SOFTWARE).check() {
+            Ok(_) => fail!("FMOD_2D | FMOD_3D was accepted"),
+            Err(e) => assert_eq!(e.description(), "FmodMode(FMOD_2D | FMOD_3D) can't be used together")
+        }
+    }
+
+    #[test]
+    fn show_lists_the_flags() {
+        assert_eq!(format!("{}", FMOD_LOOP_NORMAL | FMOD_3D), "FmodMode(FMOD_LOOP_NORMAL | FMOD_3D)".to_string());
+        assert_eq!(format!("{}", FMOD_DEFAULT), "FmodMode()".to_string());
+        assert_eq!(format!("{}", FMOD_2D | FmodMode(0x00800000)), "FmodMode(FMOD_2D | 0x800000)".to_string());
+    }
+}